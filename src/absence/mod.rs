@@ -0,0 +1,180 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{NaiveDate, TimeDelta};
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::{t, Texts},
+    model::{date_range::DateRange, error::ApplicationError},
+    rules::DayDuration,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AbsenceKind {
+    Vacation,
+    SickLeave,
+    /// Time off that reduces the overtime balance.
+    OvertimeCompensation,
+}
+
+impl AbsenceKind {
+    pub const ALL: [AbsenceKind; 3] = [
+        AbsenceKind::Vacation,
+        AbsenceKind::SickLeave,
+        AbsenceKind::OvertimeCompensation,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        t(match self {
+            AbsenceKind::Vacation => Texts::Vacation,
+            AbsenceKind::SickLeave => Texts::SickLeave,
+            AbsenceKind::OvertimeCompensation => Texts::OvertimeCompensation,
+        })
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            AbsenceKind::Vacation => Color32::from_rgb(70, 140, 220),
+            AbsenceKind::SickLeave => Color32::from_rgb(220, 120, 60),
+            AbsenceKind::OvertimeCompensation => Color32::from_rgb(160, 100, 200),
+        }
+    }
+
+    /// The value stored in the database.
+    pub fn key(&self) -> &'static str {
+        match self {
+            AbsenceKind::Vacation => "vacation",
+            AbsenceKind::SickLeave => "sick_leave",
+            AbsenceKind::OvertimeCompensation => "overtime_compensation",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.key() == key)
+    }
+}
+
+pub type AbsenceId = i64;
+
+/// A whole or half day absence, for every day from `start` to `end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbsenceData {
+    pub kind: AbsenceKind,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub half_day: bool,
+    pub remark: String,
+}
+
+impl AbsenceData {
+    pub fn new(
+        kind: AbsenceKind,
+        start: NaiveDate,
+        end: NaiveDate,
+        half_day: bool,
+        remark: String,
+    ) -> Result<Self, ApplicationError> {
+        if end < start {
+            return Err(ApplicationError::InvalidRange);
+        }
+        Ok(Self {
+            kind,
+            start,
+            end,
+            half_day,
+            remark,
+        })
+    }
+
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// The part of a day the absence takes.
+    pub fn share(&self) -> f64 {
+        if self.half_day {
+            0.5
+        } else {
+            1.0
+        }
+    }
+}
+
+pub type Absence = (AbsenceId, AbsenceData);
+
+/// The absence on a date, the first one wins if several overlap.
+pub fn absence_on(absences: &[Absence], date: NaiveDate) -> Option<&AbsenceData> {
+    absences.iter().map(|a| &a.1).find(|a| a.covers(date))
+}
+
+/// Which absences count towards the daily target, and the default vacation entitlement.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AbsenceRules {
+    pub credit_vacation: bool,
+    pub credit_sick_leave: bool,
+    pub credit_overtime_compensation: bool,
+    /// Vacation days per year, unless set for a specific year.
+    pub vacation_days: f64,
+}
+
+impl Default for AbsenceRules {
+    fn default() -> Self {
+        Self {
+            credit_vacation: true,
+            credit_sick_leave: true,
+            credit_overtime_compensation: false,
+            vacation_days: 30.0,
+        }
+    }
+}
+
+impl AbsenceRules {
+    pub fn credits(&self, kind: AbsenceKind) -> bool {
+        match kind {
+            AbsenceKind::Vacation => self.credit_vacation,
+            AbsenceKind::SickLeave => self.credit_sick_leave,
+            AbsenceKind::OvertimeCompensation => self.credit_overtime_compensation,
+        }
+    }
+
+    /// A credited absence counts as worked for its share of the planned hours.
+    pub fn credit(&self, absence: &AbsenceData, planned: TimeDelta) -> TimeDelta {
+        if !self.credits(absence.kind) {
+            return TimeDelta::zero();
+        }
+        TimeDelta::seconds((planned.num_seconds() as f64 * absence.share()).round() as i64)
+    }
+
+    /// Adds the credited absence time to the durations of the days in the range.
+    pub fn apply(
+        &self,
+        range: DateRange,
+        absences: &[Absence],
+        planned_hours: &HashMap<NaiveDate, TimeDelta>,
+        durations: &mut BTreeMap<NaiveDate, DayDuration>,
+    ) {
+        for date in range.0.iter_days().take_while(|d| *d <= range.1) {
+            let Some(absence) = absence_on(absences, date) else {
+                continue;
+            };
+            let planned = planned_hours.get(&date).copied().unwrap_or(TimeDelta::zero());
+            let credit = self.credit(absence, planned);
+            if credit > TimeDelta::zero() {
+                durations.entry(date).or_insert(DayDuration::zero()).credit(credit);
+            }
+        }
+    }
+}
+
+/// Vacation days taken, only days with planned hours count.
+pub fn vacation_days_taken(absences: &[Absence], planned_hours: &HashMap<NaiveDate, TimeDelta>) -> f64 {
+    planned_hours
+        .iter()
+        .filter(|(_, planned)| **planned > TimeDelta::zero())
+        .filter_map(|(date, _)| absence_on(absences, *date))
+        .filter(|a| a.kind == AbsenceKind::Vacation)
+        .map(|a| a.share())
+        .sum()
+}
@@ -0,0 +1,239 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use chrono::NaiveDateTime;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    export::ExportError,
+    i18n::{t, Texts},
+    model::time_entry::{TimeEntryData, TimeEntryId},
+};
+
+pub type ChangeId = i64;
+
+/// Previous hash of the first change in the log.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Updated,
+    /// Moved to the trash.
+    Removed,
+    Restored,
+    /// Deleted from the trash permanently.
+    Purged,
+}
+
+impl ChangeKind {
+    pub const ALL: [ChangeKind; 5] = [
+        ChangeKind::Added,
+        ChangeKind::Updated,
+        ChangeKind::Removed,
+        ChangeKind::Restored,
+        ChangeKind::Purged,
+    ];
+
+    /// The stored representation, which is part of the hash and must not change.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "add",
+            ChangeKind::Updated => "update",
+            ChangeKind::Removed => "remove",
+            ChangeKind::Restored => "restore",
+            ChangeKind::Purged => "purge",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ChangeKind> {
+        Self::ALL.into_iter().find(|k| k.as_str() == value)
+    }
+
+    pub fn name(&self) -> &'static str {
+        t(match self {
+            ChangeKind::Added => Texts::ChangeAdded,
+            ChangeKind::Updated => Texts::ChangeUpdated,
+            ChangeKind::Removed => Texts::ChangeRemoved,
+            ChangeKind::Restored => Texts::ChangeRestored,
+            ChangeKind::Purged => Texts::ChangePurged,
+        })
+    }
+}
+
+/// One record of the append-only change log. The old value is missing for new
+/// entries, the new value for removed ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub id: ChangeId,
+    pub entry_id: TimeEntryId,
+    pub changed_at: NaiveDateTime,
+    pub kind: ChangeKind,
+    /// The values as stored, the hash covers exactly this text.
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    /// Hash of the change written before, links the records to a chain.
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+impl Change {
+    /// The hash the change must have if neither it nor its predecessor was modified.
+    pub fn expected_hash(&self) -> String {
+        compute_hash(
+            &self.previous_hash,
+            self.entry_id,
+            self.changed_at,
+            self.kind,
+            self.old_value.as_deref(),
+            self.new_value.as_deref(),
+        )
+    }
+}
+
+/// The stored form of an entry's values.
+pub fn encode(data: Option<&TimeEntryData>) -> Option<String> {
+    data.map(|d| serde_json::to_string(d).expect("entry data is serializable"))
+}
+
+/// `None` for values written in a form this version can't read.
+pub fn decode(value: Option<&str>) -> Option<TimeEntryData> {
+    value.and_then(|v| serde_json::from_str(v).ok())
+}
+
+pub fn compute_hash(
+    previous_hash: &str,
+    entry_id: TimeEntryId,
+    changed_at: NaiveDateTime,
+    kind: ChangeKind,
+    old: Option<&str>,
+    new: Option<&str>,
+) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        previous_hash,
+        &entry_id.to_string(),
+        &changed_at.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+        kind.as_str(),
+        old.unwrap_or_default(),
+        new.unwrap_or_default(),
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Checks the complete log in the order it was written and returns the first
+/// change that was modified, inserted or follows a removed one.
+pub fn verify(changes: &[Change]) -> Result<(), ChangeId> {
+    let mut previous = GENESIS_HASH;
+    for change in changes {
+        if change.previous_hash != previous || change.hash != change.expected_hash() {
+            return Err(change.id);
+        }
+        previous = &change.hash;
+    }
+    Ok(())
+}
+
+pub const HEADER: [Texts; 8] = [
+    Texts::Number,
+    Texts::Entry,
+    Texts::ChangedAt,
+    Texts::Action,
+    Texts::Before,
+    Texts::After,
+    Texts::PreviousHash,
+    Texts::Hash,
+];
+
+/// Writes the log with the stored values, so the chain can be verified from the file.
+pub fn write_csv<W: Write>(changes: &[Change], delimiter: char, out: W) -> Result<(), ExportError> {
+    if !delimiter.is_ascii() {
+        return Err(ExportError::Unknown(format!("Unsupported delimiter: {delimiter}")));
+    }
+    let mut writer = ::csv::WriterBuilder::new().delimiter(delimiter as u8).from_writer(out);
+    writer.write_record(HEADER.map(t))?;
+    for change in changes {
+        writer.write_record([
+            change.id.to_string(),
+            change.entry_id.to_string(),
+            change.changed_at.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+            change.kind.as_str().to_owned(),
+            change.old_value.clone().unwrap_or_default(),
+            change.new_value.clone().unwrap_or_default(),
+            change.previous_hash.clone(),
+            change.hash.clone(),
+        ])?;
+    }
+    writer.flush().map_err(|err| ExportError::Unknown(err.to_string()))
+}
+
+pub fn export_to_file(changes: &[Change], delimiter: char, path: &Path) -> Result<(), ExportError> {
+    let io_error = |err: std::io::Error| ExportError::Io {
+        path: path.to_owned(),
+        message: err.to_string(),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let mut out = BufWriter::new(File::create(path).map_err(io_error)?);
+    write_csv(changes, delimiter, &mut out)?;
+    out.flush().map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn chain(values: &[Option<&str>]) -> Vec<Change> {
+        let changed_at = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let mut previous_hash = GENESIS_HASH.to_owned();
+        let mut res = vec![];
+        for (id, value) in values.iter().enumerate() {
+            let hash = compute_hash(&previous_hash, 1, changed_at, ChangeKind::Updated, None, *value);
+            res.push(Change {
+                id: id as ChangeId + 1,
+                entry_id: 1,
+                changed_at,
+                kind: ChangeKind::Updated,
+                old_value: None,
+                new_value: value.map(str::to_owned),
+                previous_hash,
+                hash: hash.clone(),
+            });
+            previous_hash = hash;
+        }
+        res
+    }
+
+    #[test]
+    fn verifies_the_stored_text() {
+        // values of a later version with fields this one doesn't know
+        let changes = chain(&[Some(r#"{"date":"2024-03-04","unknown":1}"#), Some("{}"), None]);
+        assert_eq!(verify(&changes), Ok(()));
+        assert_eq!(decode(changes[0].new_value.as_deref()), None);
+    }
+
+    #[test]
+    fn detects_modified_and_removed_changes() {
+        let changes = chain(&[Some("a"), Some("b"), Some("c")]);
+
+        let mut modified = changes.clone();
+        modified[1].new_value = Some("B".to_owned());
+        assert_eq!(verify(&modified), Err(2));
+
+        let mut rehashed = modified.clone();
+        rehashed[1].hash = rehashed[1].expected_hash();
+        assert_eq!(verify(&rehashed), Err(3));
+
+        let removed = [changes[0].clone(), changes[2].clone()];
+        assert_eq!(verify(&removed), Err(3));
+    }
+}
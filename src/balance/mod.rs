@@ -0,0 +1,304 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    extensions::naive_date_ext::NaiveDateExt,
+    i18n::{t, Texts},
+    model::{date_range::DateRange, time_entry::TimeEntry},
+    rules::DayDuration,
+    storage::{error::DataStorageError, AbsenceStorage, BalanceStorage, PlannedHoursStorage, TimeStorage},
+    TitraConfig,
+};
+
+/// Where the flextime account starts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct BalanceSettings {
+    /// No balance is computed without a start.
+    pub start: Option<NaiveDate>,
+    /// Balance in minutes at the start of `start`.
+    pub opening_balance: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdjustmentKind {
+    /// Adds the amount, negative values reduce the balance.
+    Correction,
+    /// Overtime paid out, reduces the balance by the amount.
+    Payout,
+}
+
+impl AdjustmentKind {
+    pub const ALL: [AdjustmentKind; 2] = [AdjustmentKind::Correction, AdjustmentKind::Payout];
+
+    pub fn name(&self) -> &'static str {
+        t(match self {
+            AdjustmentKind::Correction => Texts::Correction,
+            AdjustmentKind::Payout => Texts::Payout,
+        })
+    }
+
+    /// The value stored in the database.
+    pub fn key(&self) -> &'static str {
+        match self {
+            AdjustmentKind::Correction => "correction",
+            AdjustmentKind::Payout => "payout",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.key() == key)
+    }
+}
+
+pub type AdjustmentId = i64;
+
+/// A manual change of the flextime balance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdjustmentData {
+    pub date: NaiveDate,
+    pub kind: AdjustmentKind,
+    pub amount: TimeDelta,
+    pub remark: String,
+}
+
+impl AdjustmentData {
+    pub fn new(date: NaiveDate, kind: AdjustmentKind, amount: TimeDelta, remark: String) -> Self {
+        Self {
+            date,
+            kind,
+            amount,
+            remark,
+        }
+    }
+
+    /// The change of the balance.
+    pub fn effect(&self) -> TimeDelta {
+        match self.kind {
+            AdjustmentKind::Correction => self.amount,
+            AdjustmentKind::Payout => -self.amount,
+        }
+    }
+}
+
+pub type Adjustment = (AdjustmentId, AdjustmentData);
+
+/// Actual against planned time of a range and the flextime balance at its end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Balance {
+    /// Worked time after breaks and rounding, including credited absences.
+    pub actual: TimeDelta,
+    pub planned: TimeDelta,
+    /// Balance before the range, `None` without a configured start.
+    pub carry_over: Option<TimeDelta>,
+    /// Sum of corrections and payouts within the range.
+    pub adjustments: TimeDelta,
+    /// Balance after the last day of the range.
+    pub closing: Option<TimeDelta>,
+}
+
+impl Balance {
+    pub fn difference(&self) -> TimeDelta {
+        self.actual - self.planned
+    }
+}
+
+/// Storages needed to compute balances, borrowed from the services or their clones.
+pub struct Sources<'a> {
+    pub time: &'a dyn TimeStorage,
+    pub hours: &'a dyn PlannedHoursStorage,
+    pub absences: &'a dyn AbsenceStorage,
+    pub balance: &'a dyn BalanceStorage,
+}
+
+/// Entries, durations and planned hours of a range, counted the same way as in the overview.
+pub struct Days {
+    /// Entries touching the range, including the day before for entries crossing midnight.
+    pub entries: Vec<TimeEntry>,
+    /// Durations after rules and credited absences.
+    pub durations: BTreeMap<NaiveDate, DayDuration>,
+    pub planned_hours: HashMap<NaiveDate, TimeDelta>,
+}
+
+pub fn load_days(sources: &Sources, config: &TitraConfig, range: DateRange) -> Result<Days, DataStorageError> {
+    // entries of the day before may cross midnight into the range
+    let entries = sources.time.get_in_range((range.0.pred_opt().unwrap(), range.1))?;
+    let mut durations = config.rules.evaluate(&config.compliance, &entries);
+    durations.retain(|date, _| *date >= range.0 && *date <= range.1);
+    let planned_hours = config.planned_hours(range, sources.hours.get_range(range, config.federal_state)?);
+    let absences = sources.absences.get_absences(range)?;
+    config.absences.apply(range, &absences, &planned_hours, &mut durations);
+    Ok(Days {
+        entries,
+        durations,
+        planned_hours,
+    })
+}
+
+fn actual_and_planned(sources: &Sources, config: &TitraConfig, range: DateRange) -> Result<(TimeDelta, TimeDelta), DataStorageError> {
+    let days = load_days(sources, config, range)?;
+    Ok((
+        days.durations.values().map(|d| d.adjusted).sum(),
+        days.planned_hours.values().sum(),
+    ))
+}
+
+fn adjustments(sources: &Sources, range: DateRange) -> Result<TimeDelta, DataStorageError> {
+    Ok(sources.balance.get_adjustments(range)?.iter().map(|a| a.1.effect()).sum())
+}
+
+/// Totals of the range and the balance carried over from the configured start.
+/// Days before the start do not count towards the balance.
+pub fn compute(sources: &Sources, config: &TitraConfig, range: DateRange) -> Result<Balance, DataStorageError> {
+    let (actual, planned) = actual_and_planned(sources, config, range)?;
+    let mut balance = Balance {
+        actual,
+        planned,
+        carry_over: None,
+        adjustments: TimeDelta::zero(),
+        closing: None,
+    };
+    let Some(start) = config.balance.start.filter(|start| *start <= range.1) else {
+        return Ok(balance);
+    };
+
+    let opening = TimeDelta::minutes(config.balance.opening_balance);
+    let carry_over = if start < range.0 {
+        let before = (start, range.0.pred_opt().unwrap());
+        let (actual, planned) = actual_and_planned(sources, config, before)?;
+        opening + actual - planned + adjustments(sources, before)?
+    } else {
+        opening
+    };
+    let counted = (start.max(range.0), range.1);
+    let (actual, planned) = if counted.0 == range.0 {
+        (actual, planned)
+    } else {
+        actual_and_planned(sources, config, counted)?
+    };
+    balance.adjustments = adjustments(sources, counted)?;
+    balance.carry_over = Some(carry_over);
+    balance.closing = Some(carry_over + actual - planned + balance.adjustments);
+    Ok(balance)
+}
+
+/// Balances of the twelve months of a year, the same as `compute` for each month.
+/// The days before the year are loaded once for the carry-over, the year itself once.
+pub fn months(sources: &Sources, config: &TitraConfig, year: i32) -> Result<Vec<Balance>, DataStorageError> {
+    let range = (NaiveDate::from_ymd_opt(year, 1, 1).unwrap(), NaiveDate::from_ymd_opt(year, 12, 31).unwrap());
+    let days = load_days(sources, config, range)?;
+    let adjustments = sources.balance.get_adjustments(range)?;
+    let actual = |range: DateRange| -> TimeDelta { days.durations.range(range.0..=range.1).map(|d| d.1.adjusted).sum() };
+    let planned = |range: DateRange| -> TimeDelta {
+        days.planned_hours
+            .iter()
+            .filter(|(date, _)| **date >= range.0 && **date <= range.1)
+            .map(|(_, planned)| *planned)
+            .sum()
+    };
+    let adjusted = |range: DateRange| -> TimeDelta {
+        adjustments
+            .iter()
+            .filter(|a| a.1.date >= range.0 && a.1.date <= range.1)
+            .map(|a| a.1.effect())
+            .sum()
+    };
+
+    let start = config.balance.start;
+    let opening = TimeDelta::minutes(config.balance.opening_balance);
+    // balance at the start of the current month, `None` until the account starts
+    let mut running = match start {
+        Some(start) if start < range.0 => {
+            let before = compute(sources, config, (start, range.0.pred_opt().unwrap()))?;
+            Some(before.closing.unwrap_or(opening))
+        }
+        _ => None,
+    };
+    let mut res = Vec::with_capacity(12);
+    for month in 1..=12 {
+        let month = NaiveDate::from_ymd_opt(year, month, 1).unwrap().as_month_range();
+        let mut balance = Balance {
+            actual: actual(month),
+            planned: planned(month),
+            carry_over: None,
+            adjustments: TimeDelta::zero(),
+            closing: None,
+        };
+        if let Some(start) = start.filter(|start| *start <= month.1) {
+            let carry_over = running.unwrap_or(opening);
+            let counted = (start.max(month.0), month.1);
+            balance.adjustments = adjusted(counted);
+            balance.carry_over = Some(carry_over);
+            balance.closing = Some(carry_over + actual(counted) - planned(counted) + balance.adjustments);
+            running = balance.closing;
+        }
+        res.push(balance);
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use crate::{
+        absence::{AbsenceData, AbsenceKind},
+        model::time_entry::TimeEntryData,
+        storage::sqlite::SqliteStorage,
+    };
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn entry(day: NaiveDate, start: u32, end: u32) -> TimeEntryData {
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        TimeEntryData::new(day, time(start), time(end), "".to_owned(), None).unwrap()
+    }
+
+    #[test]
+    fn months_match_compute() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        for data in [
+            entry(date(2023, 11, 20), 8, 16),
+            entry(date(2023, 12, 31), 22, 2),
+            entry(date(2024, 1, 2), 8, 18),
+            entry(date(2024, 2, 29), 7, 12),
+            entry(date(2024, 6, 30), 20, 3),
+            entry(date(2024, 12, 31), 23, 1),
+        ] {
+            storage.add_entry(data).unwrap();
+        }
+        storage
+            .add_adjustment(AdjustmentData::new(date(2024, 2, 10), AdjustmentKind::Payout, TimeDelta::hours(3), "".to_owned()))
+            .unwrap();
+        storage
+            .add_adjustment(AdjustmentData::new(date(2023, 12, 1), AdjustmentKind::Correction, TimeDelta::hours(1), "".to_owned()))
+            .unwrap();
+        storage
+            .add_absence(AbsenceData::new(AbsenceKind::Vacation, date(2024, 5, 6), date(2024, 5, 8), false, "".to_owned()).unwrap())
+            .unwrap();
+        let sources = Sources {
+            time: &storage,
+            hours: &storage,
+            absences: &storage,
+            balance: &storage,
+        };
+
+        let mut config = TitraConfig::default();
+        config.rules.deduct_breaks = true;
+        config.balance.opening_balance = 90;
+        for start in [None, Some(date(2023, 11, 15)), Some(date(2024, 6, 12)), Some(date(2025, 1, 1))] {
+            config.balance.start = start;
+            let months = months(&sources, &config, 2024).unwrap();
+            for (month, balance) in (1..=12).zip(months) {
+                let expected = compute(&sources, &config, date(2024, month, 1).as_month_range()).unwrap();
+                assert_eq!(balance, expected, "start {start:?}, month {month}");
+            }
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::{format::formatter, i18n::{t, Texts}, model::time_entry::TimeEntry};
+
+/// Thresholds of the Arbeitszeitgesetz, all values in minutes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct ComplianceRules {
+    pub first_break_after: i64,
+    pub first_break: i64,
+    pub second_break_after: i64,
+    pub second_break: i64,
+    /// Shorter interruptions do not count as a break (§ 4 ArbZG).
+    pub min_break_length: i64,
+    pub max_daily_work: i64,
+    pub min_rest: i64,
+}
+
+impl Default for ComplianceRules {
+    fn default() -> Self {
+        Self {
+            first_break_after: 6 * 60,
+            first_break: 30,
+            second_break_after: 9 * 60,
+            second_break: 45,
+            min_break_length: 15,
+            max_daily_work: 10 * 60,
+            min_rest: 11 * 60,
+        }
+    }
+}
+
+impl ComplianceRules {
+    /// The break required for the given amount of work.
+    pub fn required_break(&self, worked: TimeDelta) -> TimeDelta {
+        if worked > TimeDelta::minutes(self.second_break_after) {
+            TimeDelta::minutes(self.second_break)
+        } else if worked > TimeDelta::minutes(self.first_break_after) {
+            TimeDelta::minutes(self.first_break)
+        } else {
+            TimeDelta::zero()
+        }
+    }
+
+    /// Sum of the gaps between the given intervals that are long enough to count as a break.
+    pub fn taken_break(&self, intervals: &[(NaiveDateTime, NaiveDateTime)]) -> TimeDelta {
+        intervals
+            .windows(2)
+            .map(|w| w[1].0 - w[0].1)
+            .filter(|gap| *gap >= TimeDelta::minutes(self.min_break_length))
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    MissingBreak { worked: TimeDelta, required: TimeDelta, taken: TimeDelta },
+    MaxDailyWorkExceeded { worked: TimeDelta, max: TimeDelta },
+    InsufficientRest { rest: TimeDelta, required: TimeDelta },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let formatter = formatter();
+        let text = match self {
+            Violation::MissingBreak { worked, required, taken } => t(Texts::MissingBreak)
+                .replace("{worked}", &formatter.duration(*worked))
+                .replace("{required}", &formatter.duration(*required))
+                .replace("{taken}", &formatter.duration(*taken)),
+            Violation::MaxDailyWorkExceeded { worked, max } => t(Texts::MaxDailyWorkExceeded)
+                .replace("{worked}", &formatter.duration(*worked))
+                .replace("{max}", &formatter.duration(*max)),
+            Violation::InsufficientRest { rest, required } => t(Texts::InsufficientRest)
+                .replace("{rest}", &formatter.duration(*rest))
+                .replace("{required}", &formatter.duration(*required)),
+        };
+        f.write_str(&text)
+    }
+}
+
+pub type Violations = BTreeMap<NaiveDate, Vec<Violation>>;
+
+/// Checks the entries against the rules. Entries are grouped into working
+/// days by their start date, so a night shift belongs to the day it began.
+pub fn check(rules: &ComplianceRules, entries: &[TimeEntry]) -> Violations {
+    let mut days: BTreeMap<NaiveDate, Vec<(NaiveDateTime, NaiveDateTime)>> = BTreeMap::new();
+    for (_, e) in entries {
+        days.entry(e.date)
+            .or_default()
+            .push((e.start_datetime(), e.end_datetime()));
+    }
+
+    let mut res = Violations::new();
+    let mut previous_end: Option<NaiveDateTime> = None;
+    for (date, mut intervals) in days {
+        intervals.sort();
+        let mut violations = vec![];
+
+        let worked: TimeDelta = intervals.iter().map(|(s, e)| *e - *s).sum();
+        let required = rules.required_break(worked);
+        let taken = rules.taken_break(&intervals);
+        if taken < required {
+            violations.push(Violation::MissingBreak { worked, required, taken });
+        }
+
+        let max = TimeDelta::minutes(rules.max_daily_work);
+        if worked > max {
+            violations.push(Violation::MaxDailyWorkExceeded { worked, max });
+        }
+
+        let first_start = intervals[0].0;
+        if let Some(previous_end) = previous_end {
+            let rest = first_start - previous_end;
+            let required = TimeDelta::minutes(rules.min_rest);
+            if rest < required {
+                violations.push(Violation::InsufficientRest { rest, required });
+            }
+        }
+        previous_end = intervals.iter().map(|i| i.1).max();
+
+        if !violations.is_empty() {
+            res.insert(date, violations);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use crate::model::time_entry::TimeEntryData;
+
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    /// Day of March 2024 with start and end written as `(hour, minute)`.
+    type Span = (u32, (u32, u32), (u32, u32));
+
+    fn entries(spans: &[Span]) -> Vec<TimeEntry> {
+        let time = |(h, m)| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        spans
+            .iter()
+            .zip(1..)
+            .map(|((d, start, end), id)| (id, TimeEntryData::new(day(*d), time(*start), time(*end), "".to_owned(), None).unwrap()))
+            .collect()
+    }
+
+    fn violations(spans: &[Span]) -> Vec<Violation> {
+        check(&ComplianceRules::default(), &entries(spans)).into_values().flatten().collect()
+    }
+
+    #[test]
+    fn first_break_after_six_hours() {
+        assert_eq!(violations(&[(4, (8, 0), (14, 0))]), vec![]);
+        assert_eq!(
+            violations(&[(4, (8, 0), (14, 1))]),
+            vec![Violation::MissingBreak {
+                worked: TimeDelta::minutes(6 * 60 + 1),
+                required: TimeDelta::minutes(30),
+                taken: TimeDelta::zero(),
+            }]
+        );
+        assert_eq!(violations(&[(4, (8, 0), (12, 0)), (4, (12, 30), (14, 1))]), vec![]);
+    }
+
+    #[test]
+    fn second_break_after_nine_hours() {
+        assert_eq!(violations(&[(4, (7, 0), (12, 0)), (4, (12, 30), (16, 30))]), vec![]);
+        assert_eq!(
+            violations(&[(4, (7, 0), (12, 0)), (4, (12, 30), (16, 31))]),
+            vec![Violation::MissingBreak {
+                worked: TimeDelta::minutes(9 * 60 + 1),
+                required: TimeDelta::minutes(45),
+                taken: TimeDelta::minutes(30),
+            }]
+        );
+    }
+
+    #[test]
+    fn short_interruptions_are_no_break() {
+        let taken = |gap| violations(&[(4, (8, 0), (12, 0)), (4, (12, gap), (14, 30))]);
+        assert_eq!(
+            taken(14),
+            vec![Violation::MissingBreak {
+                worked: TimeDelta::minutes(6 * 60 + 16),
+                required: TimeDelta::minutes(30),
+                taken: TimeDelta::zero(),
+            }]
+        );
+        assert_eq!(
+            taken(15),
+            vec![Violation::MissingBreak {
+                worked: TimeDelta::minutes(6 * 60 + 15),
+                required: TimeDelta::minutes(30),
+                taken: TimeDelta::minutes(15),
+            }]
+        );
+        assert_eq!(violations(&[(4, (8, 0), (12, 0)), (4, (12, 15), (12, 30)), (4, (12, 45), (14, 30))]), vec![]);
+    }
+
+    #[test]
+    fn at_most_ten_hours_a_day() {
+        assert_eq!(violations(&[(4, (7, 0), (12, 0)), (4, (12, 45), (17, 45))]), vec![]);
+        assert_eq!(
+            violations(&[(4, (7, 0), (12, 0)), (4, (12, 45), (17, 46))]),
+            vec![Violation::MaxDailyWorkExceeded {
+                worked: TimeDelta::minutes(10 * 60 + 1),
+                max: TimeDelta::minutes(10 * 60),
+            }]
+        );
+    }
+
+    #[test]
+    fn eleven_hours_of_rest() {
+        assert_eq!(violations(&[(4, (15, 0), (20, 0)), (5, (7, 0), (12, 0))]), vec![]);
+        let res = check(&ComplianceRules::default(), &entries(&[(4, (15, 0), (20, 1)), (5, (7, 0), (12, 0))]));
+        assert_eq!(
+            res,
+            Violations::from([(
+                day(5),
+                vec![Violation::InsufficientRest {
+                    rest: TimeDelta::minutes(10 * 60 + 59),
+                    required: TimeDelta::minutes(11 * 60),
+                }]
+            )])
+        );
+    }
+
+    #[test]
+    fn rest_after_a_night_shift() {
+        let res = check(&ComplianceRules::default(), &entries(&[(4, (22, 0), (3, 0)), (5, (13, 0), (17, 0))]));
+        assert_eq!(
+            res,
+            Violations::from([(
+                day(5),
+                vec![Violation::InsufficientRest {
+                    rest: TimeDelta::hours(10),
+                    required: TimeDelta::minutes(11 * 60),
+                }]
+            )])
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use directories::{ProjectDirs, UserDirs};
+use egui::ThemePreference;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{model::date_range::DateRange, TitraConfig};
+
+pub const CONFIG_FILE_ENV: &str = "TITRA_CONFIG";
+pub const DATA_DIR_ENV: &str = "TITRA_DATA_DIR";
+pub const USAGE: &str = "Usage: titra [--config <file>] [--data-dir <directory>]";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error("Could not read config {path}: {message}")]
+    Read { path: PathBuf, message: String },
+    #[error("Could not write {path}: {message}")]
+    Write { path: PathBuf, message: String },
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Theme {
+    System,
+    Light,
+    #[default]
+    Dark,
+}
+
+impl Theme {
+    pub fn preference(&self) -> ThemePreference {
+        match self {
+            Theme::System => ThemePreference::System,
+            Theme::Light => ThemePreference::Light,
+            Theme::Dark => ThemePreference::Dark,
+        }
+    }
+}
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "titra")
+}
+
+pub fn default_config_file() -> PathBuf {
+    project_dirs()
+        .map(|dirs| dirs.config_dir().join("config.json"))
+        .unwrap_or_else(|| PathBuf::from("config.json"))
+}
+
+pub fn default_data_dir() -> PathBuf {
+    project_dirs()
+        .map(|dirs| dirs.data_dir().to_owned())
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn default_export_dir() -> PathBuf {
+    UserDirs::new()
+        .and_then(|dirs| dirs.document_dir().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Values from the environment or the command line. They take precedence over
+/// the config file and are never written back to it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Overrides {
+    pub config_file: Option<PathBuf>,
+    pub root_dir: Option<PathBuf>,
+}
+
+impl Overrides {
+    /// Command line arguments win over environment variables.
+    pub fn from_env_and_args(mut args: impl Iterator<Item = String>) -> Result<Self, ConfigError> {
+        let mut overrides = Self {
+            config_file: std::env::var_os(CONFIG_FILE_ENV).map(PathBuf::from),
+            root_dir: std::env::var_os(DATA_DIR_ENV).map(PathBuf::from),
+        };
+        while let Some(arg) = args.next() {
+            let target = match arg.as_str() {
+                "--config" => &mut overrides.config_file,
+                "--data-dir" => &mut overrides.root_dir,
+                _ => return Err(ConfigError::InvalidArgument(arg)),
+            };
+            let value = args.next().ok_or(ConfigError::InvalidArgument(arg))?;
+            *target = Some(PathBuf::from(value));
+        }
+        Ok(overrides)
+    }
+}
+
+fn write(path: &Path, config: &TitraConfig) -> Result<(), ConfigError> {
+    let write_error = |message: String| ConfigError::Write { path: path.to_owned(), message };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| write_error(err.to_string()))?;
+    }
+    let content = serde_json::to_string_pretty(config).map_err(|err| write_error(err.to_string()))?;
+    fs::write(path, content).map_err(|err| write_error(err.to_string()))
+}
+
+fn read(path: &Path) -> Result<TitraConfig, ConfigError> {
+    let read_error = |message: String| ConfigError::Read { path: path.to_owned(), message };
+    let content = fs::read_to_string(path).map_err(|err| read_error(err.to_string()))?;
+    serde_json::from_str(&content).map_err(|err| read_error(err.to_string()))
+}
+
+/// Loads the config file, writing the defaults on first start, and makes sure
+/// the data directory exists.
+pub fn load(overrides: &Overrides) -> Result<TitraConfig, ConfigError> {
+    let path = overrides.config_file.clone().unwrap_or_else(default_config_file);
+    let mut config = if path.exists() {
+        read(&path)?
+    } else {
+        let config = TitraConfig::default();
+        write(&path, &config)?;
+        config
+    };
+    config.config_file = Some(path);
+    if let Some(root_dir) = &overrides.root_dir {
+        config.root_dir = root_dir.clone();
+    }
+    fs::create_dir_all(&config.root_dir).map_err(|err| ConfigError::Write {
+        path: config.root_dir.clone(),
+        message: err.to_string(),
+    })?;
+    Ok(config)
+}
+
+/// Writes the config back to the file it was loaded from. The data directory
+/// is kept as stored in the file, so an override does not become permanent.
+pub fn save(config: &TitraConfig) -> Result<(), ConfigError> {
+    let Some(path) = &config.config_file else {
+        return Ok(());
+    };
+    let mut persisted = config.clone();
+    if let Ok(stored) = read(path) {
+        persisted.root_dir = stored.root_dir;
+    }
+    write(path, &persisted)
+}
+
+impl TitraConfig {
+    /// Fills weekdays not covered by a schedule, an override or a holiday with the default working hours.
+    pub fn planned_hours(
+        &self,
+        range: DateRange,
+        mut planned_hours: HashMap<NaiveDate, TimeDelta>,
+    ) -> HashMap<NaiveDate, TimeDelta> {
+        for date in range.0.iter_days().take_while(|d| *d <= range.1) {
+            if self.default_working_hours > 0 && date.weekday().num_days_from_monday() < 5 {
+                planned_hours.entry(date).or_insert(TimeDelta::minutes(self.default_working_hours));
+            }
+        }
+        planned_hours
+    }
+}
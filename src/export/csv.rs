@@ -0,0 +1,278 @@
+use std::{fmt::Write as _, io::Write};
+
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::{format::Formatter, i18n::{t, Texts}, model::time_entry::TimeEntry};
+
+use super::{ExportData, ExportError, Exporter};
+
+/// Settings of the csv output. Unset formats follow the selected language.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub decimal_separator: Option<char>,
+    /// chrono format string, e.g. `%d.%m.%Y`
+    pub date_format: Option<String>,
+    /// chrono format string, e.g. `%H:%M`
+    pub time_format: Option<String>,
+    pub header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ';',
+            decimal_separator: None,
+            date_format: None,
+            time_format: None,
+            header: true,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Fails for a delimiter the writer can't use or a format chrono can't render.
+    pub fn validate(&self) -> Result<(), ExportError> {
+        if !self.delimiter.is_ascii() {
+            return Err(ExportError::Unknown(format!("Unsupported delimiter: {}", self.delimiter)));
+        }
+        // chrono panics in `to_string` for unknown specifiers and fields the value doesn't have
+        let mut sample = String::new();
+        if let Some(format) = &self.date_format {
+            write!(sample, "{}", NaiveDate::MIN.format(format)).map_err(|_| ExportError::InvalidFormat(format.clone()))?;
+        }
+        if let Some(format) = &self.time_format {
+            write!(sample, "{}", NaiveTime::MIN.format(format)).map_err(|_| ExportError::InvalidFormat(format.clone()))?;
+        }
+        Ok(())
+    }
+}
+
+impl From<::csv::Error> for ExportError {
+    fn from(value: ::csv::Error) -> Self {
+        Self::Unknown(value.to_string())
+    }
+}
+
+pub const HEADER: [Texts; 7] = [
+    Texts::Date,
+    Texts::Start,
+    Texts::EndDate,
+    Texts::End,
+    Texts::Duration,
+    Texts::Project,
+    Texts::Remark,
+];
+
+pub struct CsvExporter {
+    options: CsvOptions,
+    formatter: Formatter,
+}
+
+impl CsvExporter {
+    pub fn new(options: CsvOptions, formatter: Formatter) -> Self {
+        Self { options, formatter }
+    }
+
+    fn date_format(&self) -> &str {
+        self.options.date_format.as_deref().unwrap_or(self.formatter.date_pattern())
+    }
+
+    fn time_format(&self) -> &str {
+        self.options.time_format.as_deref().unwrap_or(self.formatter.time_pattern())
+    }
+
+    /// Duration in decimal hours with two digits.
+    fn format_duration(&self, duration: TimeDelta) -> String {
+        let hours = format!("{:.2}", duration.num_minutes() as f64 / 60.0);
+        let separator = self.options.decimal_separator.unwrap_or(self.formatter.decimal_separator());
+        hours.replace('.', &separator.to_string())
+    }
+
+    fn record(&self, entry: &TimeEntry, data: &ExportData) -> [String; 7] {
+        let (_, entry) = entry;
+        let project = data
+            .projects
+            .iter()
+            .find(|p| Some(p.0) == entry.project_id)
+            .map(|p| p.1.name.clone())
+            .unwrap_or_default();
+        [
+            entry.date.format(self.date_format()).to_string(),
+            entry.start.format(self.time_format()).to_string(),
+            entry.end_date.format(self.date_format()).to_string(),
+            entry.end.format(self.time_format()).to_string(),
+            self.format_duration(entry.duration()),
+            project,
+            entry.remark.clone(),
+        ]
+    }
+
+    pub fn write<W: Write>(&self, data: &ExportData, out: W) -> Result<(), ExportError> {
+        self.options.validate()?;
+        let mut writer = ::csv::WriterBuilder::new()
+            .delimiter(self.options.delimiter as u8)
+            .from_writer(out);
+        if self.options.header {
+            writer.write_record(HEADER.map(t))?;
+        }
+        for entry in &data.entries {
+            writer.write_record(self.record(entry, data))?;
+        }
+        writer
+            .flush()
+            .map_err(|err| ExportError::Unknown(err.to_string()))
+    }
+}
+
+impl Exporter for CsvExporter {
+    fn extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn export(&self, data: &ExportData, out: &mut dyn Write) -> Result<(), ExportError> {
+        self.write(data, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use chrono::{NaiveDate, NaiveTime};
+
+    use crate::{
+        balance::{Balance, Days},
+        format::{FormatSettings, Formatter},
+        i18n::Language,
+        model::{project::ProjectData, time_entry::TimeEntryData},
+        statistics::{self, Granularity},
+        user::UserData,
+    };
+
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn data() -> ExportData {
+        let range = (NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(), NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+        let days = Days {
+            entries: vec![],
+            durations: BTreeMap::new(),
+            planned_hours: HashMap::new(),
+        };
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        ExportData {
+            range,
+            entries: vec![
+                (1, TimeEntryData::new(date, time(8, 0), time(12, 15), "Review; \"final\"".to_owned(), Some(7)).unwrap()),
+                (2, TimeEntryData::new(date, time(22, 30), time(1, 0), "".to_owned(), None).unwrap()),
+            ],
+            user_data: UserData::new("Test".to_owned()),
+            violations: BTreeMap::new(),
+            durations: BTreeMap::new(),
+            planned_hours: HashMap::new(),
+            holidays: BTreeMap::new(),
+            absences: vec![],
+            balance: Balance::default(),
+            statistics: statistics::compute(&days, range, Granularity::Week),
+            projects: vec![(7, ProjectData::new("Titra".to_owned(), None))],
+        }
+    }
+
+    fn round_trip(options: CsvOptions, language: Language) -> Vec<Vec<String>> {
+        let delimiter = options.delimiter as u8;
+        let header = options.header;
+        let exporter = CsvExporter::new(options, Formatter::new(language, FormatSettings::default()));
+        let mut out = Vec::new();
+        exporter.write(&data(), &mut out).unwrap();
+        ::csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(false)
+            .from_reader(out.as_slice())
+            .records()
+            .map(|r| r.unwrap().iter().map(str::to_owned).collect::<Vec<_>>())
+            .skip(if header { 1 } else { 0 })
+            .collect()
+    }
+
+    #[test]
+    fn language_defaults() {
+        let rows = round_trip(CsvOptions::default(), Language::De);
+        assert_eq!(
+            rows,
+            vec![
+                vec!["04.03.2024", "08:00", "04.03.2024", "12:15", "4,25", "Titra", "Review; \"final\""],
+                vec!["04.03.2024", "22:30", "05.03.2024", "01:00", "2,50", "", ""],
+            ]
+        );
+
+        let rows = round_trip(CsvOptions::default(), Language::En);
+        assert_eq!(rows[0][..5], ["2024-03-04", "8:00 am", "2024-03-04", "12:15 pm", "4.25"]);
+    }
+
+    #[test]
+    fn configured_formats() {
+        let options = CsvOptions {
+            delimiter: '\t',
+            decimal_separator: Some('.'),
+            date_format: Some("%Y/%m/%d".to_owned()),
+            time_format: Some("%H.%M".to_owned()),
+            header: true,
+        };
+        let rows = round_trip(options, Language::De);
+        assert_eq!(rows[1][..5], ["2024/03/04", "22.30", "2024/03/05", "01.00", "2.50"]);
+    }
+
+    #[test]
+    fn header_on_and_off() {
+        for header in [true, false] {
+            let exporter = CsvExporter::new(
+                CsvOptions {
+                    header,
+                    ..CsvOptions::default()
+                },
+                Formatter::new(Language::De, FormatSettings::default()),
+            );
+            let mut out = Vec::new();
+            exporter.write(&data(), &mut out).unwrap();
+            let mut reader = ::csv::ReaderBuilder::new().delimiter(b';').has_headers(header).from_reader(out.as_slice());
+            assert_eq!(reader.records().count(), 2);
+            if header {
+                assert_eq!(reader.headers().unwrap().iter().collect::<Vec<_>>(), HEADER.map(t));
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_formats() {
+        let exporter = |date_format: Option<&str>, time_format: Option<&str>| {
+            CsvExporter::new(
+                CsvOptions {
+                    date_format: date_format.map(str::to_owned),
+                    time_format: time_format.map(str::to_owned),
+                    ..CsvOptions::default()
+                },
+                Formatter::new(Language::De, FormatSettings::default()),
+            )
+        };
+        for (date_format, time_format) in [(Some("%Q"), None), (Some("%d.%m. %H"), None), (None, Some("%H:%"))] {
+            let err = exporter(date_format, time_format).write(&data(), Vec::new()).unwrap_err();
+            assert!(matches!(err, ExportError::InvalidFormat(_)), "{err:?}");
+        }
+        let err = CsvExporter::new(
+            CsvOptions {
+                delimiter: '§',
+                ..CsvOptions::default()
+            },
+            Formatter::new(Language::De, FormatSettings::default()),
+        )
+        .write(&data(), Vec::new())
+        .unwrap_err();
+        assert!(matches!(err, ExportError::Unknown(_)));
+    }
+}
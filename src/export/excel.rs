@@ -1,57 +1,304 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, io::Write};
 
-use chrono::Datelike;
-use rust_xlsxwriter::{Color, Format, Workbook, Worksheet};
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use rust_xlsxwriter::{Color, Format, FormatBorder, Workbook, Worksheet, XlsxError};
 
-use crate::{model::time_entry::TimeEntry, user::UserData};
+use crate::{absence::absence_on, compliance::Violations, format::{formatter, DateStyle, DurationStyle, Formatter, TimeStyle}, i18n::{t, Texts}, model::time_entry::TimeEntry, rules::DayDuration, statistics::{period_label, Statistics}, user::UserData};
 
-use super::Exporter;
+use super::{ExportData, ExportError, Exporter};
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub enum XlsxFormats {
     Header,
-    
+    TableHeader,
+    Date,
+    Time,
+    Duration,
+    Total,
+    TotalDuration,
+    Holiday,
+    Absence,
 }
 
+impl From<XlsxError> for ExportError {
+    fn from(value: XlsxError) -> Self {
+        Self::Unknown(value.to_string())
+    }
+}
+
+const DURATION_FORMAT: &str = "[h]:mm;-[h]:mm";
+const DECIMAL_FORMAT: &str = "0.00";
+
+const COLUMNS: [(Texts, f64); 10] = [
+    (Texts::Date, 12.0),
+    (Texts::Weekday, 12.0),
+    (Texts::Start, 8.0),
+    (Texts::End, 8.0),
+    (Texts::Duration, 8.0),
+    (Texts::Remark, 40.0),
+    (Texts::Actual, 8.0),
+    (Texts::Net, 8.0),
+    (Texts::Planned, 8.0),
+    (Texts::Difference, 10.0),
+];
 
 pub struct XlsxExporter{
-    formats: HashMap<XlsxFormats, Format>
+    formats: HashMap<XlsxFormats, Format>,
+    formatter: Formatter,
 }
 
 impl Default for XlsxExporter {
     fn default() -> Self {
-        Self::new()
+        Self::new(formatter())
     }
 }
 
 impl XlsxExporter {
-    pub fn new() -> Self {
-        Self {  
+    pub fn new(formatter: Formatter) -> Self {
+        let date_format = match formatter.date {
+            DateStyle::German => "dd.mm.yyyy",
+            DateStyle::Iso => "yyyy-mm-dd",
+        };
+        let time_format = match formatter.time {
+            TimeStyle::H24 => "hh:mm",
+            TimeStyle::H12 => "h:mm AM/PM",
+        };
+        let duration_format = match formatter.duration {
+            DurationStyle::HoursMinutes => DURATION_FORMAT,
+            DurationStyle::Decimal => DECIMAL_FORMAT,
+        };
+        Self {
             formats: HashMap::from([
-                (XlsxFormats::Header, Format::new().set_font_size(30.0).set_background_color(Color::Cyan))])
+                (XlsxFormats::Header, Format::new().set_font_size(30.0).set_background_color(Color::Cyan)),
+                (XlsxFormats::TableHeader, Format::new().set_bold().set_border_bottom(FormatBorder::Thin)),
+                (XlsxFormats::Date, Format::new().set_num_format(date_format)),
+                (XlsxFormats::Time, Format::new().set_num_format(time_format)),
+                (XlsxFormats::Duration, Format::new().set_num_format(duration_format)),
+                (XlsxFormats::Total, Format::new().set_bold().set_border_top(FormatBorder::Thin)),
+                (XlsxFormats::Holiday, Format::new().set_italic().set_font_color(Color::Green)),
+                (XlsxFormats::Absence, Format::new().set_italic().set_font_color(Color::Blue)),
+                (XlsxFormats::TotalDuration, Format::new().set_bold().set_border_top(FormatBorder::Thin).set_num_format(duration_format)),
+            ]),
+            formatter,
+        }
+    }
+
+    fn format(&self, format: XlsxFormats) -> &Format {
+        self.formats.get(&format).unwrap()
+    }
+
+    /// Excel stores durations as fractions of a day, decimal hours are written as plain numbers.
+    fn write_duration(&self, sheet: &mut Worksheet, row: u32, col: u16, duration: TimeDelta, format: XlsxFormats) -> Result<(), XlsxError> {
+        let hours = duration.num_minutes() as f64 / 60.0;
+        let value = match self.formatter.duration {
+            DurationStyle::HoursMinutes => hours / 24.0,
+            DurationStyle::Decimal => hours,
+        };
+        sheet.write_number_with_format(row, col, value, self.format(format))?;
+        Ok(())
+    }
+
+    fn generate_timesheet(&self, sheet: &mut Worksheet, data: &ExportData) -> Result<(), XlsxError> {
+        let mut row = generate_header(&self.formats, sheet, &data.user_data, data.range.0)?;
+        for (col, (title, width)) in COLUMNS.iter().enumerate() {
+            sheet.write_with_format(row, col as u16, t(*title), self.format(XlsxFormats::TableHeader))?;
+            sheet.set_column_width(col as u16, *width)?;
         }
+        row += 1;
+
+        let mut total = DayDuration::zero();
+        let mut total_planned = TimeDelta::zero();
+        for date in data.range.0.iter_days().take_while(|d| *d <= data.range.1) {
+            let entries: Vec<&TimeEntry> = data.entries.iter().filter(|e| e.1.date == date).collect();
+            let duration = data.durations.get(&date).copied().unwrap_or(DayDuration::zero());
+            let planned = data.planned_hours.get(&date).copied().unwrap_or(TimeDelta::zero());
+            total = [total, duration].into_iter().sum();
+            total_planned += planned;
+
+            let first_row = row;
+            if let Some((note, format)) = day_note(data, date) {
+                self.write_day(sheet, row, date)?;
+                sheet.write_with_format(row, 5, note, self.format(format))?;
+                row += 1;
+            } else if entries.is_empty() {
+                self.write_day(sheet, row, date)?;
+                row += 1;
+            }
+            for (_, entry) in entries {
+                self.write_day(sheet, row, date)?;
+                sheet.write_datetime_with_format(row, 2, entry.start, self.format(XlsxFormats::Time))?;
+                sheet.write_datetime_with_format(row, 3, entry.end, self.format(XlsxFormats::Time))?;
+                self.write_duration(sheet, row, 4, entry.duration(), XlsxFormats::Duration)?;
+                sheet.write(row, 5, &entry.remark)?;
+                row += 1;
+            }
+
+            // daily values go into the first row of the day
+            self.write_duration(sheet, first_row, 6, duration.raw, XlsxFormats::Duration)?;
+            self.write_duration(sheet, first_row, 7, duration.adjusted, XlsxFormats::Duration)?;
+            self.write_duration(sheet, first_row, 8, planned, XlsxFormats::Duration)?;
+            self.write_duration(sheet, first_row, 9, duration.adjusted - planned, XlsxFormats::Duration)?;
+        }
+
+        sheet.write_with_format(row, 0, t(Texts::Total), self.format(XlsxFormats::Total))?;
+        for col in 1..6 {
+            sheet.write_blank(row, col, self.format(XlsxFormats::Total))?;
+        }
+        self.write_duration(sheet, row, 6, total.raw, XlsxFormats::TotalDuration)?;
+        self.write_duration(sheet, row, 7, total.adjusted, XlsxFormats::TotalDuration)?;
+        self.write_duration(sheet, row, 8, total_planned, XlsxFormats::TotalDuration)?;
+        self.write_duration(sheet, row, 9, total.adjusted - total_planned, XlsxFormats::TotalDuration)?;
+        row += 2;
+        sheet.write(row, 0, t(Texts::Overtime))?;
+        self.write_duration(sheet, row, 1, total.adjusted - total_planned, XlsxFormats::Duration)?;
+        let balance = &data.balance;
+        if let (Some(carry_over), Some(closing)) = (balance.carry_over, balance.closing) {
+            row += 1;
+            sheet.write(row, 0, t(Texts::CarryOver))?;
+            self.write_duration(sheet, row, 1, carry_over, XlsxFormats::Duration)?;
+            if balance.adjustments != TimeDelta::zero() {
+                row += 1;
+                sheet.write(row, 0, t(Texts::Adjustments))?;
+                self.write_duration(sheet, row, 1, balance.adjustments, XlsxFormats::Duration)?;
+            }
+            row += 1;
+            sheet.write_with_format(row, 0, t(Texts::FlextimeBalance), self.format(XlsxFormats::Total))?;
+            self.write_duration(sheet, row, 1, closing, XlsxFormats::TotalDuration)?;
+        }
+        Ok(())
+    }
+
+    fn generate_statistics(&self, sheet: &mut Worksheet, statistics: &Statistics) -> Result<(), XlsxError> {
+        sheet.write_with_format(0, 0, t(Texts::Statistics), self.format(XlsxFormats::Header))?;
+        sheet.set_row_height(0, 40.0)?;
+        sheet.set_column_width(0, 30.0)?;
+        sheet.set_column_width(1, 12.0)?;
+        sheet.write(2, 0, t(Texts::WorkedDays))?;
+        sheet.write(2, 1, statistics.worked_days as u32)?;
+        let durations = [
+            (Texts::Net, Some(statistics.actual)),
+            (Texts::Planned, Some(statistics.planned)),
+            (Texts::Difference, Some(statistics.actual - statistics.planned)),
+            (Texts::AverageDay, statistics.average_day),
+        ];
+        for (row, (label, duration)) in (3..).zip(durations) {
+            sheet.write(row, 0, t(label))?;
+            if let Some(duration) = duration {
+                self.write_duration(sheet, row, 1, duration, XlsxFormats::Duration)?;
+            }
+        }
+        let times = [
+            (Texts::AverageStart, statistics.average_start),
+            (Texts::AverageEnd, statistics.average_end),
+        ];
+        for (row, (label, time)) in (7..).zip(times) {
+            sheet.write(row, 0, t(label))?;
+            if let Some(time) = time {
+                sheet.write_datetime_with_format(row, 1, time, self.format(XlsxFormats::Time))?;
+            }
+        }
+        sheet.write(9, 0, t(Texts::LongestStreak))?;
+        if let Some(streak) = statistics.longest_streak {
+            sheet.write(9, 1, streak.days() as u32)?;
+            sheet.write_datetime_with_format(9, 2, streak.start, self.format(XlsxFormats::Date))?;
+            sheet.write_datetime_with_format(9, 3, streak.end, self.format(XlsxFormats::Date))?;
+        }
+
+        let header = [statistics.granularity.name(), t(Texts::Net), t(Texts::Planned), t(Texts::Difference)];
+        for (col, title) in (0..).zip(header) {
+            sheet.write_with_format(11, col, title, self.format(XlsxFormats::TableHeader))?;
+        }
+        for (row, bucket) in (12..).zip(&statistics.buckets) {
+            sheet.write(row, 0, period_label(statistics.granularity, bucket.start))?;
+            self.write_duration(sheet, row, 1, bucket.actual, XlsxFormats::Duration)?;
+            self.write_duration(sheet, row, 2, bucket.planned, XlsxFormats::Duration)?;
+            self.write_duration(sheet, row, 3, bucket.actual - bucket.planned, XlsxFormats::Duration)?;
+        }
+        Ok(())
+    }
+
+    fn write_day(&self, sheet: &mut Worksheet, row: u32, date: NaiveDate) -> Result<(), XlsxError> {
+        sheet.write_datetime_with_format(row, 0, date, self.format(XlsxFormats::Date))?;
+        sheet.write(row, 1, self.formatter.weekday(date.weekday()))?;
+        Ok(())
     }
 }
 
 impl Exporter for XlsxExporter {
-    fn export(&self, data: Vec<TimeEntry>, user_data: UserData) -> Result<(), super::ExportError> {
-        
+    fn extension(&self) -> &'static str {
+        "xlsx"
+    }
+
+    fn export(&self, data: &ExportData, out: &mut dyn Write) -> Result<(), ExportError> {
+
         let mut wb = Workbook::new();
         let sheet = wb.add_worksheet();
-        if data.len() == 0 {
-            return Ok(());
+        let month = format!("{}-{}", data.range.0.year(), data.range.0.month());
+        sheet.set_name(month)?;
+        self.generate_timesheet(sheet, data)?;
+
+        let sheet = wb.add_worksheet();
+        sheet.set_name(t(Texts::Statistics))?;
+        self.generate_statistics(sheet, &data.statistics)?;
+
+        if !data.violations.is_empty() {
+            let sheet = wb.add_worksheet();
+            sheet.set_name(t(Texts::Violations))?;
+            generate_violations(&self.formats, sheet, &data.violations)?;
         }
-        let month = format!("{}-{}", data[0].1.date.year(), data[0].1.date.month());
-        sheet.set_name(month.clone()).unwrap();
-        generate_header(&self.formats, sheet, &user_data);
 
+        out.write_all(&wb.save_to_buffer()?)
+            .map_err(|err| ExportError::Unknown(err.to_string()))
+    }
+}
 
-        wb.save(format!("./{} {}.xlsx", user_data.name.clone(), month )).expect("Failed to save");
-        Ok(())
+/// Holiday and absence of a day, in the remark column above the entries.
+fn day_note(data: &ExportData, date: NaiveDate) -> Option<(String, XlsxFormats)> {
+    let holiday = data.holidays.get(&date).cloned();
+    let Some(absence) = absence_on(&data.absences, date) else {
+        return holiday.map(|h| (h, XlsxFormats::Holiday));
+    };
+    let mut name = absence.kind.name().to_owned();
+    if absence.half_day {
+        name.push_str(" ½");
+    }
+    if !absence.remark.is_empty() {
+        name = format!("{name}: {}", absence.remark);
     }
+    let note = match holiday {
+        Some(h) => format!("{h}, {name}"),
+        None => name,
+    };
+    Some((note, XlsxFormats::Absence))
 }
 
-fn generate_header(formats: &HashMap<XlsxFormats, Format>, sheet: &mut Worksheet, user_data: &UserData) {
-    sheet.write_with_format(0, 0, user_data.name.clone(), formats.get(&XlsxFormats::Header).unwrap()).unwrap();
-    sheet.set_column_width(0, 32.0).unwrap();
-}
\ No newline at end of file
+/// Writes the title and address block, returns the first free row.
+fn generate_header(formats: &HashMap<XlsxFormats, Format>, sheet: &mut Worksheet, user_data: &UserData, month: NaiveDate) -> Result<u32, XlsxError> {
+    sheet.write_with_format(0, 0, format!("{} {}", t(Texts::Timesheet), month.format("%m/%Y")), formats.get(&XlsxFormats::Header).unwrap())?;
+    sheet.set_row_height(0, 40.0)?;
+    let rows = [
+        (Texts::Name, user_data.name.clone()),
+        (Texts::EmployeeNumber, user_data.employee_number.clone()),
+        (Texts::Employer, user_data.employer.clone()),
+        (Texts::Street, user_data.street.clone()),
+        (Texts::PostalCodeCity, format!("{} {}", user_data.citycode, user_data.city).trim().to_owned()),
+        (Texts::Email, user_data.email.clone()),
+    ];
+    for (row, (label, value)) in (2..).zip(rows) {
+        sheet.write(row, 0, t(label))?;
+        sheet.write(row, 1, value)?;
+    }
+    Ok(9)
+}
+
+fn generate_violations(formats: &HashMap<XlsxFormats, Format>, sheet: &mut Worksheet, violations: &Violations) -> Result<(), XlsxError> {
+    sheet.write_with_format(0, 0, t(Texts::ViolationsTitle), formats.get(&XlsxFormats::Header).unwrap())?;
+    sheet.set_column_width(0, 16.0)?;
+    sheet.set_column_width(1, 80.0)?;
+    let rows = violations.iter().flat_map(|(date, v)| v.iter().map(move |v| (date, v)));
+    for (row, (date, violation)) in (2..).zip(rows) {
+        sheet.write_datetime_with_format(row, 0, *date, formats.get(&XlsxFormats::Date).unwrap())?;
+        sheet.write(row, 1, violation.to_string())?;
+    }
+    Ok(())
+}
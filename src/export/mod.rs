@@ -1,18 +1,94 @@
+use std::{collections::{BTreeMap, HashMap}, fs::File, io::{BufWriter, Write}, path::{Path, PathBuf}};
+
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{model::time_entry::TimeEntry, user::UserData};
+use crate::{absence::Absence, balance::Balance, compliance::Violations, i18n::{t, Texts}, model::{date_range::DateRange, project::Project, time_entry::TimeEntry}, rules::DayDuration, statistics::Statistics, storage::error::DataStorageError, user::UserData};
 
+pub mod csv;
 pub mod excel;
 
 
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum ExportError {
-    #[error("Could not export data: {0}")]
-    Unknown(String)
+    #[error("{}: {}", t(Texts::ExportFailed), .0)]
+    Unknown(String),
+    #[error("{}: {path}: {message}", t(Texts::ExportFailed))]
+    Io { path: PathBuf, message: String },
+    #[error("{}: {}", t(Texts::ExportFailed), .0)]
+    Storage(#[from] DataStorageError),
+    #[error("{}: {}", t(Texts::InvalidFormat), .0)]
+    InvalidFormat(String),
+}
+
+/// Where exports go if the user does not pick a file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct ExportSettings {
+    pub directory: PathBuf,
+    /// Supports `{name}`, `{year}`, `{month}` and `{ext}`.
+    pub filename_template: String,
 }
 
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            directory: crate::config::default_export_dir(),
+            filename_template: "{name}_{year}-{month}.{ext}".to_owned(),
+        }
+    }
+}
+
+impl ExportSettings {
+    pub fn file_name(&self, user_data: &UserData, range: DateRange, extension: &str) -> String {
+        self.filename_template
+            .replace("{name}", &user_data.name)
+            .replace("{year}", &range.0.year().to_string())
+            .replace("{month}", &format!("{:02}", range.0.month()))
+            .replace("{ext}", extension)
+    }
 
+    pub fn default_path(&self, user_data: &UserData, range: DateRange, extension: &str) -> PathBuf {
+        self.directory.join(self.file_name(user_data, range, extension))
+    }
+}
+
+/// Everything an exporter needs to write a report for one date range.
+pub struct ExportData {
+    pub range: DateRange,
+    pub entries: Vec<TimeEntry>,
+    pub user_data: UserData,
+    pub violations: Violations,
+    /// Raw and adjusted durations per day according to the configured rules.
+    pub durations: BTreeMap<NaiveDate, DayDuration>,
+    pub planned_hours: HashMap<NaiveDate, TimeDelta>,
+    /// Public holidays and company days off with their names.
+    pub holidays: BTreeMap<NaiveDate, String>,
+    pub absences: Vec<Absence>,
+    /// Totals of the range and the flextime balance at its end.
+    pub balance: Balance,
+    /// Key figures of the range, summed up per week.
+    pub statistics: Statistics,
+    pub projects: Vec<Project>,
+}
 
 pub trait Exporter {
-    fn export(&self, data: Vec<TimeEntry>, user_data: UserData) -> Result<(), ExportError>;
-}
\ No newline at end of file
+    /// File extension of the written format, without the dot.
+    fn extension(&self) -> &'static str;
+    fn export(&self, data: &ExportData, out: &mut dyn Write) -> Result<(), ExportError>;
+}
+
+/// Exports into the given file, creating missing parent directories.
+pub fn export_to_file(exporter: &dyn Exporter, data: &ExportData, path: &Path) -> Result<(), ExportError> {
+    let io_error = |err: std::io::Error| ExportError::Io {
+        path: path.to_owned(),
+        message: err.to_string(),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let mut out = BufWriter::new(File::create(path).map_err(io_error)?);
+    exporter.export(data, &mut out)?;
+    out.flush().map_err(io_error)
+}
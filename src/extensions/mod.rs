@@ -1 +1,2 @@
 pub mod naive_date_ext;
+pub mod time_delta_ext;
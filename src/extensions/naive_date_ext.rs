@@ -1,6 +1,6 @@
 use crate::model::date_range::DateRange;
 
-use chrono::Datelike;
+use chrono::{Datelike, NaiveDate, TimeDelta};
 
 
 pub trait NaiveDateExt {
@@ -8,6 +8,11 @@ pub trait NaiveDateExt {
     fn days_in_year(&self) -> u32;
     fn is_leap_year(&self) -> bool;
     fn as_month_range(&self) -> DateRange;
+    /// Monday to Sunday of the ISO week.
+    fn as_week_range(&self) -> DateRange;
+    /// The same day in another month, clamped to the last day of a shorter month.
+    /// `None` if the year is out of range.
+    fn with_year_month(&self, year: i32, month: u32) -> Option<NaiveDate>;
 }
 
 impl NaiveDateExt for chrono::NaiveDate {
@@ -43,4 +48,33 @@ impl NaiveDateExt for chrono::NaiveDate {
     fn as_month_range(&self) -> DateRange {
         (self.with_day(1).unwrap(), self.with_day(self.days_in_month()).unwrap())
     }
-}
\ No newline at end of file
+
+    fn as_week_range(&self) -> DateRange {
+        let monday = *self - TimeDelta::days(self.weekday().num_days_from_monday() as i64);
+        (monday, monday + TimeDelta::days(6))
+    }
+
+    fn with_year_month(&self, year: i32, month: u32) -> Option<NaiveDate> {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        first.with_day(self.day().min(first.days_in_month()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn with_year_month_clamps_to_last_day() {
+        assert_eq!(date(2025, 3, 31).with_year_month(2025, 2), Some(date(2025, 2, 28)));
+        assert_eq!(date(2025, 3, 31).with_year_month(2025, 4), Some(date(2025, 4, 30)));
+        assert_eq!(date(2024, 2, 29).with_year_month(2025, 2), Some(date(2025, 2, 28)));
+        assert_eq!(date(2024, 2, 29).with_year_month(2028, 2), Some(date(2028, 2, 29)));
+        assert_eq!(date(2025, 1, 15).with_year_month(2026, 7), Some(date(2026, 7, 15)));
+        assert_eq!(date(2025, 1, 15).with_year_month(i32::MAX, 1), None);
+    }
+}
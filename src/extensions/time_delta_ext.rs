@@ -0,0 +1,13 @@
+use chrono::TimeDelta;
+
+pub trait TimeDeltaExt {
+    fn format_hh_mm(&self) -> String;
+}
+
+impl TimeDeltaExt for TimeDelta {
+    fn format_hh_mm(&self) -> String {
+        let sign = if *self < TimeDelta::zero() { "-" } else { "" };
+        let minutes = self.num_minutes().abs();
+        format!("{sign}{:0>2}:{:0>2}", minutes / 60, minutes % 60)
+    }
+}
@@ -0,0 +1,291 @@
+use std::sync::RwLock;
+
+use chrono::{Datelike, NaiveDate, NaiveTime, TimeDelta, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    extensions::time_delta_ext::TimeDeltaExt,
+    i18n::{self, Language},
+    model::error::ApplicationError,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateStyle {
+    /// DD.MM.YYYY
+    German,
+    /// YYYY-MM-DD
+    Iso,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeStyle {
+    H24,
+    H12,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DurationStyle {
+    #[default]
+    HoursMinutes,
+    Decimal,
+}
+
+/// User choices, unset styles follow the selected language.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct FormatSettings {
+    pub date: Option<DateStyle>,
+    pub time: Option<TimeStyle>,
+    pub duration: DurationStyle,
+}
+
+static SETTINGS: RwLock<FormatSettings> = RwLock::new(FormatSettings {
+    date: None,
+    time: None,
+    duration: DurationStyle::HoursMinutes,
+});
+
+pub fn set_settings(settings: FormatSettings) {
+    *SETTINGS.write().unwrap() = settings;
+}
+
+/// The formatter for the current language and settings.
+pub fn formatter() -> Formatter {
+    Formatter::new(i18n::language(), *SETTINGS.read().unwrap())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Formatter {
+    pub language: Language,
+    pub date: DateStyle,
+    pub time: TimeStyle,
+    pub duration: DurationStyle,
+}
+
+impl Formatter {
+    pub fn new(language: Language, settings: FormatSettings) -> Self {
+        let (date, time) = match language {
+            Language::De => (DateStyle::German, TimeStyle::H24),
+            Language::En => (DateStyle::Iso, TimeStyle::H12),
+        };
+        Self {
+            language,
+            date: settings.date.unwrap_or(date),
+            time: settings.time.unwrap_or(time),
+            duration: settings.duration,
+        }
+    }
+
+    pub fn date_pattern(&self) -> &'static str {
+        match self.date {
+            DateStyle::German => "%d.%m.%Y",
+            DateStyle::Iso => "%Y-%m-%d",
+        }
+    }
+
+    pub fn time_pattern(&self) -> &'static str {
+        match self.time {
+            TimeStyle::H24 => "%H:%M",
+            TimeStyle::H12 => "%-I:%M %P",
+        }
+    }
+
+    pub fn weekday(&self, weekday: Weekday) -> &'static str {
+        i18n::weekday_name(weekday)
+    }
+
+    pub fn date(&self, date: NaiveDate) -> String {
+        date.format(self.date_pattern()).to_string()
+    }
+
+    /// Date with the abbreviated weekday in front.
+    pub fn date_with_weekday(&self, date: NaiveDate) -> String {
+        let weekday: String = self.weekday(date.weekday()).chars().take(2).collect();
+        format!("{weekday} {}", self.date(date))
+    }
+
+    pub fn time(&self, time: NaiveTime) -> String {
+        time.format(self.time_pattern()).to_string()
+    }
+
+    pub fn duration(&self, duration: TimeDelta) -> String {
+        match self.duration {
+            DurationStyle::HoursMinutes => duration.format_hh_mm(),
+            DurationStyle::Decimal => self.decimal_hours(duration),
+        }
+    }
+
+    pub fn decimal_separator(&self) -> char {
+        match self.language {
+            Language::De => ',',
+            Language::En => '.',
+        }
+    }
+
+    /// Hours with two decimals and the decimal separator of the language.
+    pub fn decimal_hours(&self, duration: TimeDelta) -> String {
+        let hours = format!("{:.2}", duration.num_minutes() as f64 / 60.0);
+        hours.replace('.', &self.decimal_separator().to_string())
+    }
+
+    /// A duration with an optional leading `-` or `+`.
+    pub fn parse_signed_duration(&self, input: &str) -> Result<TimeDelta, ApplicationError> {
+        let input = input.trim();
+        match input.strip_prefix('-') {
+            Some(rest) => self.parse_duration(rest).map(|d| -d),
+            None => self.parse_duration(input.strip_prefix('+').unwrap_or(input)),
+        }
+        .map_err(|_| ApplicationError::InvalidTime(input.to_owned()))
+    }
+
+    /// Days with at most one decimal, e.g. for vacation.
+    pub fn days(&self, days: f64) -> String {
+        let text = if days.fract() == 0.0 {
+            format!("{days:.0}")
+        } else {
+            format!("{days:.1}")
+        };
+        text.replace('.', &self.decimal_separator().to_string())
+    }
+
+    /// A non-negative number of days, both `.` and `,` are accepted as decimal separator.
+    pub fn parse_days(&self, input: &str) -> Result<f64, ApplicationError> {
+        input
+            .trim()
+            .replace(',', ".")
+            .parse::<f64>()
+            .ok()
+            .filter(|days| days.is_finite() && *days >= 0.0)
+            .ok_or_else(|| ApplicationError::InvalidNumber(input.to_owned()))
+    }
+
+    /// Accepts `9`, `930`, `0930`, `9:30`, `9.5`, `9,5` and an optional `am`/`pm` suffix.
+    pub fn parse_time(&self, input: &str) -> Result<NaiveTime, ApplicationError> {
+        parse_time(input).ok_or_else(|| ApplicationError::InvalidTime(input.to_owned()))
+    }
+
+    /// Durations use the same syntax as times, but without `am`/`pm`.
+    pub fn parse_duration(&self, input: &str) -> Result<TimeDelta, ApplicationError> {
+        parse_hours_minutes(input.trim())
+            .filter(|(_, minutes)| *minutes < 60)
+            .map(|(hours, minutes)| TimeDelta::minutes(hours as i64 * 60 + minutes as i64))
+            .ok_or_else(|| ApplicationError::InvalidTime(input.to_owned()))
+    }
+}
+
+fn parse_hours_minutes(input: &str) -> Option<(u32, u32)> {
+    if let Some((hours, minutes)) = input.split_once(':') {
+        return Some((hours.trim().parse().ok()?, minutes.trim().parse().ok()?));
+    }
+    if let Some((hours, fraction)) = input.split_once(['.', ',']) {
+        let hours: u32 = if hours.is_empty() { 0 } else { hours.parse().ok()? };
+        let fraction: f64 = format!("0.{fraction}").parse().ok()?;
+        return Some((hours, (fraction * 60.0).round() as u32));
+    }
+    if input.is_empty() || !input.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    match input.len() {
+        1 | 2 => Some((input.parse().ok()?, 0)),
+        3 | 4 => {
+            let split = input.len() - 2;
+            Some((input[..split].parse().ok()?, input[split..].parse().ok()?))
+        }
+        _ => None,
+    }
+}
+
+fn parse_time(input: &str) -> Option<NaiveTime> {
+    let input = input.trim().to_lowercase();
+    let (input, pm) = if let Some(rest) = input.strip_suffix("pm").or(input.strip_suffix('p')) {
+        (rest.trim(), Some(true))
+    } else if let Some(rest) = input.strip_suffix("am").or(input.strip_suffix('a')) {
+        (rest.trim(), Some(false))
+    } else {
+        (input.as_str(), None)
+    };
+    let (mut hours, minutes) = parse_hours_minutes(input)?;
+    match pm {
+        Some(_) if hours == 0 || hours > 12 => return None,
+        Some(true) if hours < 12 => hours += 12,
+        Some(false) if hours == 12 => hours = 0,
+        _ => {}
+    }
+    NaiveTime::from_hms_opt(hours, minutes, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatter() -> Formatter {
+        Formatter::new(Language::De, FormatSettings::default())
+    }
+
+    #[test]
+    fn parses_times() {
+        let cases = [
+            ("9", Some((9, 0))),
+            ("09", Some((9, 0))),
+            ("930", Some((9, 30))),
+            ("0930", Some((9, 30))),
+            ("9:30", Some((9, 30))),
+            (" 9:30 ", Some((9, 30))),
+            ("9.5", Some((9, 30))),
+            ("9,75", Some((9, 45))),
+            ("9:30 am", Some((9, 30))),
+            ("9:30 pm", Some((21, 30))),
+            ("9:30PM", Some((21, 30))),
+            ("9p", Some((21, 0))),
+            ("12am", Some((0, 0))),
+            ("12 pm", Some((12, 0))),
+            ("23:59", Some((23, 59))),
+            ("0", Some((0, 0))),
+            ("", None),
+            ("   ", None),
+            ("25:00", None),
+            ("24:00", None),
+            ("9:60", None),
+            ("2400", None),
+            ("13 pm", None),
+            ("0 am", None),
+            ("12345", None),
+            ("9h", None),
+            ("abc", None),
+            ("-9", None),
+        ];
+        for (input, expected) in cases {
+            let expected = expected.map(|(h, m)| NaiveTime::from_hms_opt(h, m, 0).unwrap());
+            assert_eq!(formatter().parse_time(input).ok(), expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn parses_durations() {
+        let cases = [
+            ("8", Some(8 * 60)),
+            ("730", Some(7 * 60 + 30)),
+            ("7:30", Some(7 * 60 + 30)),
+            ("7.5", Some(7 * 60 + 30)),
+            ("0,25", Some(15)),
+            (",5", Some(30)),
+            ("25:00", Some(25 * 60)),
+            ("0:00", Some(0)),
+            ("", None),
+            ("7:60", None),
+            ("-1", None),
+            ("7:30 pm", None),
+            ("12345", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(formatter().parse_duration(input).ok(), expected.map(TimeDelta::minutes), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn parses_signed_durations() {
+        assert_eq!(formatter().parse_signed_duration("-1:30"), Ok(TimeDelta::minutes(-90)));
+        assert_eq!(formatter().parse_signed_duration("+2"), Ok(TimeDelta::hours(2)));
+        assert!(formatter().parse_signed_duration("--2").is_err());
+    }
+}
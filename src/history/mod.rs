@@ -0,0 +1,220 @@
+use chrono::{NaiveDate, TimeDelta};
+use log::warn;
+
+use crate::{
+    model::time_entry::{TimeEntry, TimeEntryData, TimeEntryId},
+    storage::{error::DataStorageError, PlannedHoursStorage, TimeStorage},
+};
+
+/// Number of steps that can be undone.
+const LIMIT: usize = 100;
+
+/// A change of the stored data that can be reverted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    AddEntry(TimeEntryData),
+    UpdateEntry(TimeEntryId, TimeEntryData),
+    RemoveEntry(TimeEntryId),
+    /// Inserts a removed entry again, keeping its id.
+    RestoreEntry(TimeEntry),
+    /// Overrides the planned hours of a day, `None` resets it to the schedule.
+    SetPlannedHours(NaiveDate, Option<TimeDelta>),
+    /// Several commands undone and redone as one step.
+    Batch(Vec<Command>),
+}
+
+impl Command {
+    /// Applies the command and returns the command reverting it.
+    pub fn apply(
+        self,
+        time: &mut dyn TimeStorage,
+        hours: &mut dyn PlannedHoursStorage,
+    ) -> Result<Command, DataStorageError> {
+        Ok(match self {
+            Command::AddEntry(data) => Command::RemoveEntry(time.add_entry(data)?),
+            Command::UpdateEntry(id, data) => Command::UpdateEntry(id, time.update_entry(id, data)?),
+            Command::RemoveEntry(id) => Command::RestoreEntry((id, time.remove_entry(id)?)),
+            Command::RestoreEntry(entry) => {
+                let id = entry.0;
+                time.restore_entry(entry)?;
+                Command::RemoveEntry(id)
+            }
+            Command::SetPlannedHours(date, Some(duration)) => Command::SetPlannedHours(date, hours.set(date, duration)?),
+            Command::SetPlannedHours(date, None) => Command::SetPlannedHours(date, hours.reset(date)?),
+            Command::Batch(commands) => {
+                let mut inverse = Vec::with_capacity(commands.len());
+                for command in commands {
+                    match command.apply(time, hours) {
+                        Ok(command) => inverse.push(command),
+                        Err(err) => {
+                            // nothing would undo the applied part of a failed batch
+                            for command in inverse.into_iter().rev() {
+                                if let Err(err) = command.apply(time, hours) {
+                                    warn!("Failed to revert batch: {err}");
+                                }
+                            }
+                            return Err(err);
+                        }
+                    }
+                }
+                inverse.reverse();
+                Command::Batch(inverse)
+            }
+        })
+    }
+
+    /// Whether the command changes the entry with the given id.
+    pub fn references(&self, entry_id: TimeEntryId) -> bool {
+        match self {
+            Command::UpdateEntry(id, _) | Command::RemoveEntry(id) | Command::RestoreEntry((id, _)) => *id == entry_id,
+            Command::AddEntry(_) | Command::SetPlannedHours(..) => false,
+            Command::Batch(commands) => commands.iter().any(|c| c.references(entry_id)),
+        }
+    }
+}
+
+/// Undo and redo stacks, both holding the commands that revert a step.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+}
+
+impl History {
+    /// Records the inverse of a newly applied command, which discards the redo stack.
+    pub fn push(&mut self, inverse: Command) {
+        self.redo.clear();
+        push_limited(&mut self.undo, inverse);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Command> {
+        self.undo.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Command> {
+        self.redo.pop()
+    }
+
+    /// Records the command reverting an undone step.
+    pub fn push_redo(&mut self, command: Command) {
+        push_limited(&mut self.redo, command);
+    }
+
+    /// Records the command reverting a redone step without touching the redo stack.
+    pub fn push_undo(&mut self, command: Command) {
+        push_limited(&mut self.undo, command);
+    }
+
+    /// Drops the steps changing entries that were deleted permanently.
+    pub fn forget(&mut self, entry_ids: &[TimeEntryId]) {
+        for stack in [&mut self.undo, &mut self.redo] {
+            stack.retain(|c| !entry_ids.iter().any(|id| c.references(*id)));
+        }
+    }
+}
+
+fn push_limited(stack: &mut Vec<Command>, command: Command) {
+    if stack.len() == LIMIT {
+        stack.remove(0);
+    }
+    stack.push(command);
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveTime;
+
+    use crate::storage::sqlite::SqliteStorage;
+
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 4).unwrap()
+    }
+
+    fn entry(start: u32, end: u32, remark: &str) -> TimeEntryData {
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        TimeEntryData::new(date(), time(start), time(end), remark.to_owned(), None).unwrap()
+    }
+
+    fn entries(time: &SqliteStorage) -> Vec<TimeEntry> {
+        time.get_in_range((date(), date())).unwrap()
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_data() {
+        let (mut time, mut hours) = (SqliteStorage::in_memory().unwrap(), SqliteStorage::in_memory().unwrap());
+        let undo = Command::AddEntry(entry(8, 12, "a")).apply(&mut time, &mut hours).unwrap();
+        let id = entries(&time)[0].0;
+        assert_eq!(undo, Command::RemoveEntry(id));
+
+        let undo_update = Command::UpdateEntry(id, entry(9, 12, "b")).apply(&mut time, &mut hours).unwrap();
+        let redo_update = undo_update.apply(&mut time, &mut hours).unwrap();
+        assert_eq!(entries(&time), vec![(id, entry(8, 12, "a"))]);
+
+        let redo = undo.apply(&mut time, &mut hours).unwrap();
+        assert!(entries(&time).is_empty());
+        redo.apply(&mut time, &mut hours).unwrap();
+        redo_update.apply(&mut time, &mut hours).unwrap();
+        assert_eq!(entries(&time), vec![(id, entry(9, 12, "b"))]);
+    }
+
+    #[test]
+    fn failed_batch_is_reverted() {
+        let (mut time, mut hours) = (SqliteStorage::in_memory().unwrap(), SqliteStorage::in_memory().unwrap());
+        let id = time.add_entry(entry(8, 12, "a")).unwrap();
+        let planned = hours.get(date(), None).unwrap();
+
+        let batch = Command::Batch(vec![
+            Command::UpdateEntry(id, entry(9, 12, "b")),
+            Command::AddEntry(entry(13, 14, "c")),
+            Command::SetPlannedHours(date(), Some(TimeDelta::hours(2))),
+            Command::RemoveEntry(id + 100),
+        ]);
+        assert!(batch.apply(&mut time, &mut hours).is_err());
+
+        assert_eq!(entries(&time), vec![(id, entry(8, 12, "a"))]);
+        assert_eq!(hours.get(date(), None).unwrap(), planned);
+    }
+
+    #[test]
+    fn history_keeps_the_last_steps() {
+        let mut history = History::default();
+        for id in 0..=LIMIT as i64 {
+            history.push(Command::RemoveEntry(id));
+        }
+        history.push_redo(Command::RemoveEntry(-1));
+        history.push(Command::RemoveEntry(-2));
+        assert!(!history.can_redo());
+        let mut undone = 0;
+        while history.pop_undo().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, LIMIT);
+    }
+
+    #[test]
+    fn forget_drops_steps_of_purged_entries() {
+        let mut history = History::default();
+        history.push(Command::RestoreEntry((1, entry(8, 12, "a"))));
+        history.push(Command::Batch(vec![Command::RemoveEntry(2), Command::UpdateEntry(3, entry(9, 10, "b"))]));
+        history.push(Command::RemoveEntry(4));
+        let redo = history.pop_undo().unwrap();
+        history.push_redo(redo);
+
+        history.forget(&[1, 3]);
+
+        assert!(history.can_redo());
+        assert!(!history.can_undo());
+        history.forget(&[4]);
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, TimeDelta, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::{t, Texts},
+    model::date_range::DateRange,
+    storage::{error::DataStorageError, PlannedHoursStorage},
+};
+
+/// The German federal states, each has its own set of public holidays.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum State {
+    BadenWuerttemberg,
+    Bavaria,
+    Berlin,
+    Brandenburg,
+    Bremen,
+    Hamburg,
+    Hesse,
+    MecklenburgVorpommern,
+    LowerSaxony,
+    NorthRhineWestphalia,
+    RhinelandPalatinate,
+    Saarland,
+    Saxony,
+    SaxonyAnhalt,
+    SchleswigHolstein,
+    Thuringia,
+}
+
+impl State {
+    pub const ALL: [State; 16] = [
+        State::BadenWuerttemberg,
+        State::Bavaria,
+        State::Berlin,
+        State::Brandenburg,
+        State::Bremen,
+        State::Hamburg,
+        State::Hesse,
+        State::MecklenburgVorpommern,
+        State::LowerSaxony,
+        State::NorthRhineWestphalia,
+        State::RhinelandPalatinate,
+        State::Saarland,
+        State::Saxony,
+        State::SaxonyAnhalt,
+        State::SchleswigHolstein,
+        State::Thuringia,
+    ];
+
+    /// The official German name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::BadenWuerttemberg => "Baden-Württemberg",
+            State::Bavaria => "Bayern",
+            State::Berlin => "Berlin",
+            State::Brandenburg => "Brandenburg",
+            State::Bremen => "Bremen",
+            State::Hamburg => "Hamburg",
+            State::Hesse => "Hessen",
+            State::MecklenburgVorpommern => "Mecklenburg-Vorpommern",
+            State::LowerSaxony => "Niedersachsen",
+            State::NorthRhineWestphalia => "Nordrhein-Westfalen",
+            State::RhinelandPalatinate => "Rheinland-Pfalz",
+            State::Saarland => "Saarland",
+            State::Saxony => "Sachsen",
+            State::SaxonyAnhalt => "Sachsen-Anhalt",
+            State::SchleswigHolstein => "Schleswig-Holstein",
+            State::Thuringia => "Thüringen",
+        }
+    }
+}
+
+pub type DayOffId = i64;
+
+/// A company specific day off, e.g. Christmas Eve or a bridge day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DayOffData {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+impl DayOffData {
+    pub fn new(date: NaiveDate, name: String) -> Self {
+        Self { date, name }
+    }
+}
+
+pub type DayOff = (DayOffId, DayOffData);
+
+/// Easter Sunday in the Gregorian calendar (anonymous algorithm by Meeus, Jones and Butcher).
+pub fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap()
+}
+
+/// Public holidays of a year, `None` as state gives the nationwide ones only.
+///
+/// Holidays that only apply to parts of a state, like Assumption Day in
+/// Bavarian communities or Corpus Christi in parts of Saxony, are not included.
+pub fn public_holidays(year: i32, state: Option<State>) -> Vec<(NaiveDate, Texts)> {
+    use State::*;
+    let date = |month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    let easter = easter_sunday(year);
+    let in_state = |states: &[State]| state.is_some_and(|s| states.contains(&s));
+
+    let mut res = vec![
+        (date(1, 1), Texts::NewYear),
+        (easter - TimeDelta::days(2), Texts::GoodFriday),
+        (easter + TimeDelta::days(1), Texts::EasterMonday),
+        (date(5, 1), Texts::LabourDay),
+        (easter + TimeDelta::days(39), Texts::AscensionDay),
+        (easter + TimeDelta::days(50), Texts::WhitMonday),
+        (date(12, 25), Texts::ChristmasDay),
+        (date(12, 26), Texts::BoxingDay),
+    ];
+    if year >= 1990 {
+        res.push((date(10, 3), Texts::GermanUnityDay));
+    }
+    if in_state(&[BadenWuerttemberg, Bavaria, SaxonyAnhalt]) {
+        res.push((date(1, 6), Texts::Epiphany));
+    }
+    if (in_state(&[Berlin]) && year >= 2019) || (in_state(&[MecklenburgVorpommern]) && year >= 2023) {
+        res.push((date(3, 8), Texts::WomensDay));
+    }
+    if in_state(&[Berlin]) && (year == 2020 || year == 2025) {
+        res.push((date(5, 8), Texts::LiberationDay));
+    }
+    if in_state(&[Brandenburg]) {
+        res.push((easter, Texts::EasterSunday));
+        res.push((easter + TimeDelta::days(49), Texts::WhitSunday));
+    }
+    if in_state(&[BadenWuerttemberg, Bavaria, Hesse, NorthRhineWestphalia, RhinelandPalatinate, Saarland]) {
+        res.push((easter + TimeDelta::days(60), Texts::CorpusChristi));
+    }
+    if in_state(&[Saarland]) {
+        res.push((date(8, 15), Texts::AssumptionDay));
+    }
+    if in_state(&[Thuringia]) && year >= 2019 {
+        res.push((date(9, 20), Texts::WorldChildrensDay));
+    }
+    // the 500th anniversary in 2017 was a holiday everywhere
+    if year == 2017
+        || in_state(&[Brandenburg, MecklenburgVorpommern, Saxony, SaxonyAnhalt, Thuringia])
+        || (in_state(&[Bremen, Hamburg, LowerSaxony, SchleswigHolstein]) && year >= 2018)
+    {
+        res.push((date(10, 31), Texts::ReformationDay));
+    }
+    if in_state(&[BadenWuerttemberg, Bavaria, NorthRhineWestphalia, RhinelandPalatinate, Saarland]) {
+        res.push((date(11, 1), Texts::AllSaintsDay));
+    }
+    if in_state(&[Saxony]) {
+        // the Wednesday before November 23rd
+        let last = date(11, 22);
+        let offset = (last.weekday().num_days_from_monday() + 7 - Weekday::Wed.num_days_from_monday()) % 7;
+        res.push((last - TimeDelta::days(offset as i64), Texts::RepentanceDay));
+    }
+    res.sort_by_key(|h| h.0);
+    res
+}
+
+/// Public holidays and company days off in the range with their names.
+/// A company day off on a public holiday keeps the name of the holiday.
+pub fn holidays_in(range: DateRange, state: Option<State>, days_off: &[DayOff]) -> BTreeMap<NaiveDate, String> {
+    let mut res: BTreeMap<NaiveDate, String> = days_off
+        .iter()
+        .filter(|d| d.1.date >= range.0 && d.1.date <= range.1)
+        .map(|d| (d.1.date, d.1.name.clone()))
+        .collect();
+    for year in range.0.year()..=range.1.year() {
+        let holidays = public_holidays(year, state)
+            .into_iter()
+            .filter(|h| h.0 >= range.0 && h.0 <= range.1)
+            .map(|(date, name)| (date, t(name).to_owned()));
+        res.extend(holidays);
+    }
+    res
+}
+
+/// Loads the company days off from storage and combines them with the public holidays.
+pub fn load(hours: &dyn PlannedHoursStorage, range: DateRange, state: Option<State>) -> Result<BTreeMap<NaiveDate, String>, DataStorageError> {
+    Ok(holidays_in(range, state, &hours.get_days_off(range)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn holiday(day: NaiveDate, state: Option<State>) -> Option<Texts> {
+        public_holidays(day.year(), state).into_iter().find(|h| h.0 == day).map(|h| h.1)
+    }
+
+    #[test]
+    fn easter_sunday_of_known_years() {
+        for expected in [
+            date(1818, 3, 22),
+            date(2000, 4, 23),
+            date(2019, 4, 21),
+            date(2024, 3, 31),
+            date(2025, 4, 20),
+            date(2038, 4, 25),
+            date(2285, 3, 22),
+        ] {
+            assert_eq!(easter_sunday(expected.year()), expected);
+        }
+    }
+
+    #[test]
+    fn movable_holidays_follow_easter() {
+        let holidays = public_holidays(2024, None);
+        assert!(holidays.contains(&(date(2024, 3, 29), Texts::GoodFriday)));
+        assert!(holidays.contains(&(date(2024, 4, 1), Texts::EasterMonday)));
+        assert!(holidays.contains(&(date(2024, 5, 9), Texts::AscensionDay)));
+        assert!(holidays.contains(&(date(2024, 5, 20), Texts::WhitMonday)));
+        assert_eq!(holiday(date(2024, 5, 30), Some(State::Bavaria)), Some(Texts::CorpusChristi));
+        assert_eq!(holiday(date(2024, 5, 30), None), None);
+    }
+
+    #[test]
+    fn repentance_day_only_in_saxony() {
+        for day in [date(2023, 11, 22), date(2024, 11, 20), date(2025, 11, 19)] {
+            assert_eq!(holiday(day, Some(State::Saxony)), Some(Texts::RepentanceDay));
+            assert_eq!(day.weekday(), Weekday::Wed);
+            for state in State::ALL.into_iter().filter(|s| *s != State::Saxony) {
+                assert_eq!(holiday(day, Some(state)), None, "{state:?}");
+            }
+            assert_eq!(holiday(day, None), None);
+        }
+    }
+
+    #[test]
+    fn reformation_day_2017_nationwide() {
+        assert_eq!(holiday(date(2017, 10, 31), None), Some(Texts::ReformationDay));
+        for state in State::ALL {
+            assert_eq!(holiday(date(2017, 10, 31), Some(state)), Some(Texts::ReformationDay), "{state:?}");
+        }
+        assert_eq!(holiday(date(2016, 10, 31), None), None);
+        assert_eq!(holiday(date(2018, 10, 31), Some(State::Bavaria)), None);
+        assert_eq!(holiday(date(2018, 10, 31), Some(State::Hamburg)), Some(Texts::ReformationDay));
+    }
+
+    #[test]
+    fn liberation_day_in_berlin_once() {
+        for year in [2020, 2025] {
+            assert_eq!(holiday(date(year, 5, 8), Some(State::Berlin)), Some(Texts::LiberationDay));
+            assert_eq!(holiday(date(year, 5, 8), Some(State::Brandenburg)), None);
+        }
+        for year in [2019, 2021, 2024, 2026] {
+            assert_eq!(holiday(date(year, 5, 8), Some(State::Berlin)), None);
+        }
+    }
+
+    #[test]
+    fn womens_day() {
+        assert_eq!(holiday(date(2018, 3, 8), Some(State::Berlin)), None);
+        assert_eq!(holiday(date(2019, 3, 8), Some(State::Berlin)), Some(Texts::WomensDay));
+        assert_eq!(holiday(date(2022, 3, 8), Some(State::MecklenburgVorpommern)), None);
+        assert_eq!(holiday(date(2023, 3, 8), Some(State::MecklenburgVorpommern)), Some(Texts::WomensDay));
+        assert_eq!(holiday(date(2023, 3, 8), Some(State::Hamburg)), None);
+        assert_eq!(holiday(date(2023, 3, 8), None), None);
+    }
+
+    #[test]
+    fn company_days_off_keep_holiday_names() {
+        let days_off = [
+            (1, DayOffData::new(date(2024, 12, 24), "Heiligabend".to_owned())),
+            (2, DayOffData::new(date(2024, 12, 25), "Betriebsruhe".to_owned())),
+        ];
+        let holidays = holidays_in((date(2024, 12, 1), date(2024, 12, 31)), None, &days_off);
+        assert_eq!(holidays.get(&date(2024, 12, 24)).map(String::as_str), Some("Heiligabend"));
+        assert_eq!(holidays.get(&date(2024, 12, 25)).map(String::as_str), Some(t(Texts::ChristmasDay)));
+        assert_eq!(holidays.len(), 3);
+    }
+}
@@ -1,20 +1,257 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, RwLock},
+};
 
-use serde::Deserialize;
+use log::warn;
+use serde::{Deserialize, Serialize};
 
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub enum Language {
-    De
+    #[default]
+    De,
+    En,
 }
 
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Deserialize)]
-pub enum Texts {
+impl Language {
+    pub const ALL: [Language; 2] = [Language::De, Language::En];
+
+    /// The name of the language in itself.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::De => "Deutsch",
+            Language::En => "English",
+        }
+    }
+}
+
+/// Declares the `Texts` enum together with the list of all its keys.
+macro_rules! texts {
+    ($($key:ident),* $(,)?) => {
+        #[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Deserialize)]
+        pub enum Texts {
+            $($key),*
+        }
+
+        impl Texts {
+            pub const ALL: &'static [Texts] = &[$(Texts::$key),*];
+        }
+    };
+}
+
+texts! {
     Start,
     End,
     Remark,
     Export,
+    Project,
+    Projects,
+    NoProject,
+    AllProjects,
+    Clients,
+    Date,
+    Day,
+    Weekday,
+    EndDate,
+    NextDay,
+    Duration,
+    Actual,
+    Net,
+    Planned,
+    Difference,
+    Total,
+    Overtime,
+    BreakDeducted,
+    Save,
+    Cancel,
+    Discard,
+    Edit,
+    Archived,
+    ShowArchived,
+    NewClient,
+    NewProject,
+    Profile,
+    NewProfile,
+    ActiveProfile,
+    NoProfile,
+    Name,
+    Street,
+    PostalCode,
+    City,
+    PostalCodeCity,
+    EmployeeNumber,
+    Employer,
+    Email,
+    Settings,
+    Language,
+    Theme,
+    ThemeSystem,
+    ThemeLight,
+    ThemeDark,
+    DefaultWorkingHours,
+    InvalidWorkingHours,
+    ExportDirectory,
+    FileName,
+    DataDirectory,
+    ExportFormat,
+    ChooseDestination,
+    ExportedTo,
+    ExportAborted,
+    ExportFailed,
+    StartTimer,
+    StopTimer,
+    DiscardTimer,
+    RunningSince,
+    Timesheet,
+    Violations,
+    ViolationsTitle,
+    MissingBreak,
+    MaxDailyWorkExceeded,
+    InsufficientRest,
+    InvalidRange,
+    SetWeekdayHours,
+    ResetToSchedule,
+    WorkSchedules,
+    ValidFrom,
+    WeeklyHours,
+    NewSchedule,
+    FederalState,
+    NationwideOnly,
+    DaysOff,
+    NewYear,
+    Epiphany,
+    WomensDay,
+    GoodFriday,
+    EasterSunday,
+    EasterMonday,
+    LabourDay,
+    LiberationDay,
+    AscensionDay,
+    WhitSunday,
+    WhitMonday,
+    CorpusChristi,
+    AssumptionDay,
+    WorldChildrensDay,
+    GermanUnityDay,
+    ReformationDay,
+    AllSaintsDay,
+    RepentanceDay,
+    ChristmasDay,
+    BoxingDay,
+    Absences,
+    Vacation,
+    SickLeave,
+    OvertimeCompensation,
+    Kind,
+    From,
+    To,
+    HalfDay,
+    VacationEntitlement,
+    VacationTaken,
+    VacationRemaining,
+    Days,
+    CreditedAbsences,
+    Credited,
+    InvalidNumber,
+    FlextimeAccount,
+    FlextimeBalance,
+    CarryOver,
+    Adjustments,
+    Correction,
+    Payout,
+    BalanceStart,
+    OpeningBalance,
+    Amount,
+    Month,
+    Week,
+    CalendarWeek,
+    YearOverview,
+    LoadingAborted,
+    Statistics,
+    WorkedDays,
+    AverageDay,
+    AverageStart,
+    AverageEnd,
+    LongestStreak,
+    StartEndDistribution,
+    Undo,
+    Redo,
+    Trash,
+    Restore,
+    DeletePermanently,
+    EmptyTrash,
+    DeletedAt,
+    RetentionDays,
+    ChangeLog,
+    ChangeAdded,
+    ChangeUpdated,
+    ChangeRemoved,
+    ChangeRestored,
+    ChangePurged,
+    Number,
+    Entry,
+    ChangedAt,
+    Action,
+    Before,
+    After,
+    PreviousHash,
+    Hash,
+    LogIntact,
+    LogModified,
+    OverlapsWith,
+    Overlapping,
+    OverlapWarn,
+    OverlapBlock,
+    OverlappingEntries,
+    ShowGaps,
+    ImplicitBreak,
+    InvalidFormat,
+    InvalidTime,
+    DateFormat,
+    TimeFormat,
+    DurationFormat,
+    Automatic,
+    DecimalHours,
+    StillInEdit,
+    January,
+    February,
+    March,
+    April,
+    May,
+    June,
+    July,
+    August,
+    September,
+    October,
+    November,
+    December,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+/// Missing keys fall back to this language.
+pub const FALLBACK: Language = Language::De;
+
+static TRANSLATIONS: LazyLock<Translations> = LazyLock::new(Translations::new);
+static LANGUAGE: RwLock<Language> = RwLock::new(FALLBACK);
+
+pub fn set_language(language: Language) {
+    *LANGUAGE.write().unwrap() = language;
+}
+
+pub fn language() -> Language {
+    *LANGUAGE.read().unwrap()
+}
 
+/// The text in the current language.
+pub fn t(text: Texts) -> &'static str {
+    TRANSLATIONS.lookup(language(), text)
 }
 
 pub struct Translations {
@@ -24,11 +261,83 @@ pub struct Translations {
 impl Translations {
     pub fn new() -> Self {
         let lang_de = include_str!("./de.json");
-        let map_lang_de = serde_json::from_str(&lang_de).unwrap();
+        let lang_en = include_str!("./en.json");
+        let map_lang_de = serde_json::from_str(lang_de).unwrap();
+        let map_lang_en = serde_json::from_str(lang_en).unwrap();
         Self {
             translations: HashMap::from([
-                (Language::De, map_lang_de)
+                (Language::De, map_lang_de),
+                (Language::En, map_lang_en),
             ])
         }
     }
-}
\ No newline at end of file
+
+    pub fn get(&self, language: Language, text: Texts) -> Option<&str> {
+        self.translations.get(&language)?.get(&text).map(String::as_str)
+    }
+
+    /// Falls back to the default language and finally to an empty string.
+    pub fn lookup(&self, language: Language, text: Texts) -> &str {
+        self.get(language, text)
+            .or_else(|| self.get(FALLBACK, text))
+            .unwrap_or_else(|| {
+                warn!("Missing translation for {text:?}");
+                ""
+            })
+    }
+}
+
+impl Default for Translations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn month_name(month: u32) -> Option<&'static str> {
+    let text = match month {
+        1 => Texts::January,
+        2 => Texts::February,
+        3 => Texts::March,
+        4 => Texts::April,
+        5 => Texts::May,
+        6 => Texts::June,
+        7 => Texts::July,
+        8 => Texts::August,
+        9 => Texts::September,
+        10 => Texts::October,
+        11 => Texts::November,
+        12 => Texts::December,
+        _ => return None,
+    };
+    Some(t(text))
+}
+
+pub fn weekday_name(weekday: chrono::Weekday) -> &'static str {
+    t(match weekday {
+        chrono::Weekday::Mon => Texts::Monday,
+        chrono::Weekday::Tue => Texts::Tuesday,
+        chrono::Weekday::Wed => Texts::Wednesday,
+        chrono::Weekday::Thu => Texts::Thursday,
+        chrono::Weekday::Fri => Texts::Friday,
+        chrono::Weekday::Sat => Texts::Saturday,
+        chrono::Weekday::Sun => Texts::Sunday,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_text_is_translated() {
+        let translations = Translations::new();
+        for language in Language::ALL {
+            let missing: Vec<Texts> = Texts::ALL
+                .iter()
+                .copied()
+                .filter(|text| translations.get(language, *text).is_none_or(str::is_empty))
+                .collect();
+            assert!(missing.is_empty(), "{language:?} is missing {missing:?}");
+        }
+    }
+}
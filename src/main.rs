@@ -9,9 +9,20 @@ pub mod user;
 pub mod i18n;
 pub mod extensions;
 pub mod model;
+pub mod compliance;
+pub mod rules;
+pub mod config;
+pub mod format;
+pub mod holidays;
+pub mod absence;
+pub mod balance;
+pub mod statistics;
+pub mod history;
+pub mod audit;
+pub mod overlap;
 
 use dotenv::dotenv;
-use egui::{IconData, ThemePreference};
+use egui::IconData;
 use titra::*;
 use egui::FontFamily::Proportional;
 use egui::FontId;
@@ -22,6 +33,15 @@ fn main() {
     dotenv().ok();
     env_logger::init();
 
+    let overrides = match config::Overrides::from_env_and_args(std::env::args().skip(1)) {
+        Ok(overrides) => overrides,
+        Err(err) => {
+            eprintln!("{err}\n{}", config::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let config = config::load(&overrides);
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1024.0, 768.0]).with_title("Titra").with_icon(IconData::default()),
  
@@ -43,12 +63,16 @@ fn main() {
         "Titra",
         options,
         Box::new(|cc| {
-            cc.egui_ctx.set_theme(ThemePreference::Dark);
             cc.egui_ctx.all_styles_mut(move |style| style.text_styles = text_styles.clone());
-            Ok(Box::new(Titra::new(TitraConfig {
-                root_dir: ".".into(),
-                storage_impl: storage::StorageImplementation::Sqlite,
-            })))
+            match config {
+                Ok(config) => {
+                    cc.egui_ctx.set_theme(config.theme.preference());
+                    i18n::set_language(config.language);
+                    format::set_settings(config.format);
+                    Ok(Box::new(Titra::new(config)))
+                }
+                Err(err) => Ok(Box::new(Titra::failed(err.to_string()))),
+            }
         }),
     )
     .unwrap();
@@ -1,7 +1,7 @@
 use chrono::ParseError;
 use thiserror::Error;
 
-use crate::{export::ExportError, storage::error::DataStorageError};
+use crate::{config::ConfigError, export::ExportError, i18n::{t, Texts}, storage::error::DataStorageError};
 
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum ApplicationError {
@@ -10,13 +10,21 @@ pub enum ApplicationError {
     #[error("{0}")]
     Export(ExportError),
     #[error("{0}")]
+    Config(ConfigError),
+    #[error("{0}")]
     ChronoParseError(ParseError),
     #[error("{0}")]
     ChronoeTimezoneError(String),
-    #[error("Ungülitige Start- und Endzeit")]
+    #[error("{}: {}", t(Texts::InvalidTime), .0)]
+    InvalidTime(String),
+    #[error("{}: {}", t(Texts::InvalidNumber), .0)]
+    InvalidNumber(String),
+    #[error("{}", t(Texts::InvalidRange))]
     InvalidRange,
-    #[error("Still in edit")]
+    #[error("{}", t(Texts::StillInEdit))]
     InEdit,
+    #[error("{}: {}", t(Texts::OverlapsWith), .0)]
+    Overlap(String),
 }
 
 
@@ -25,6 +33,11 @@ impl From<ParseError> for ApplicationError {
         ApplicationError::ChronoParseError(value)
     }
 }
+impl From<ConfigError> for ApplicationError {
+    fn from(value: ConfigError) -> Self {
+        ApplicationError::Config(value)
+    }
+}
 impl From<DataStorageError> for ApplicationError {
     fn from(value: DataStorageError) -> Self {
         ApplicationError::Storage(value)
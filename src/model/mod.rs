@@ -1,3 +1,5 @@
 pub mod time_entry;
 pub mod date_range;
 pub mod error;
+pub mod project;
+pub mod schedule;
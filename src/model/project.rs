@@ -0,0 +1,53 @@
+use egui::Color32;
+
+pub type ClientId = i64;
+pub type ProjectId = i64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientData {
+    pub name: String,
+    pub color: Color32,
+    pub archived: bool,
+}
+
+impl ClientData {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            color: Color32::GRAY,
+            archived: false,
+        }
+    }
+}
+
+pub type Client = (ClientId, ClientData);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectData {
+    pub name: String,
+    pub client_id: Option<ClientId>,
+    pub color: Color32,
+    pub archived: bool,
+}
+
+impl ProjectData {
+    pub fn new(name: String, client_id: Option<ClientId>) -> Self {
+        Self {
+            name,
+            client_id,
+            color: Color32::LIGHT_BLUE,
+            archived: false,
+        }
+    }
+}
+
+pub type Project = (ProjectId, ProjectData);
+
+/// Colors are stored as `0xRRGGBB` integers.
+pub fn color_to_rgb(color: Color32) -> u32 {
+    (color.r() as u32) << 16 | (color.g() as u32) << 8 | color.b() as u32
+}
+
+pub fn color_from_rgb(value: u32) -> Color32 {
+    Color32::from_rgb((value >> 16) as u8, (value >> 8) as u8, value as u8)
+}
@@ -0,0 +1,43 @@
+use chrono::{Datelike, NaiveDate, TimeDelta};
+
+pub type ScheduleId = i64;
+
+/// Planned hours per weekday, valid from `valid_from` until the next schedule starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleData {
+    pub valid_from: NaiveDate,
+    /// Monday to Sunday.
+    pub hours: [TimeDelta; 7],
+}
+
+impl ScheduleData {
+    pub fn new(valid_from: NaiveDate, hours: [TimeDelta; 7]) -> Self {
+        Self { valid_from, hours }
+    }
+
+    /// The same hours for Monday to Friday, weekends off.
+    pub fn weekdays(valid_from: NaiveDate, hours: TimeDelta) -> Self {
+        let mut all = [TimeDelta::zero(); 7];
+        all[..5].fill(hours);
+        Self::new(valid_from, all)
+    }
+
+    pub fn hours_on(&self, date: NaiveDate) -> TimeDelta {
+        self.hours[date.weekday().num_days_from_monday() as usize]
+    }
+
+    pub fn weekly_total(&self) -> TimeDelta {
+        self.hours.iter().sum()
+    }
+}
+
+pub type Schedule = (ScheduleId, ScheduleData);
+
+/// The schedule in effect at `date`, i.e. the latest one that started on or before it.
+pub fn schedule_at(schedules: &[Schedule], date: NaiveDate) -> Option<&ScheduleData> {
+    schedules
+        .iter()
+        .map(|s| &s.1)
+        .filter(|s| s.valid_from <= date)
+        .max_by_key(|s| s.valid_from)
+}
@@ -1,32 +1,185 @@
-use chrono::{NaiveDate, NaiveTime};
+use chrono::{Days, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::error::DataStorageError;
+
+use super::{error::ApplicationError, project::ProjectId};
 
 pub type TimeEntryId = i64;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// A worked time span. `end_date` is either `date` or the following day for
+/// entries that cross midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TimeEntryData {
     pub start: NaiveTime,
     pub end: NaiveTime,
     pub date: NaiveDate,
-    pub remark: String
+    pub end_date: NaiveDate,
+    pub remark: String,
+    pub project_id: Option<ProjectId>,
 }
 impl TimeEntryData {
+    /// Creates an entry on `date`, an `end` at or before `start` is taken to
+    /// lie on the next day.
+    pub fn new(
+        date: NaiveDate,
+        start: NaiveTime,
+        end: NaiveTime,
+        remark: String,
+        project_id: Option<ProjectId>,
+    ) -> Result<Self, ApplicationError> {
+        if start == end {
+            return Err(ApplicationError::InvalidRange);
+        }
+        let end_date = if end < start {
+            date.checked_add_days(Days::new(1))
+                .ok_or(ApplicationError::InvalidRange)?
+        } else {
+            date
+        };
+        Ok(Self {
+            start,
+            end,
+            date,
+            end_date,
+            remark,
+            project_id,
+        })
+    }
+
     pub fn with_start(&self, val: NaiveTime) -> TimeEntryData {
         Self {
-            date: self.date,
-            end: self.end,
-            remark: self.remark.clone(),
-            start: val
+            start: val,
+            ..self.clone()
         }
     }
 
     pub fn with_end(&self, val: NaiveTime) -> TimeEntryData {
         Self {
-            date: self.date,
             end: val,
-            remark: self.remark.clone(),
-            start: self.start
+            ..self.clone()
+        }
+    }
+
+    pub fn start_datetime(&self) -> NaiveDateTime {
+        self.date.and_time(self.start)
+    }
+
+    pub fn end_datetime(&self) -> NaiveDateTime {
+        self.end_date.and_time(self.end)
+    }
+
+    pub fn duration(&self) -> TimeDelta {
+        self.end_datetime() - self.start_datetime()
+    }
+
+    pub fn crosses_midnight(&self) -> bool {
+        self.end_date > self.date
+    }
+
+    /// Whether both entries cover a common span of time, touching ends don't count.
+    pub fn overlaps(&self, other: &TimeEntryData) -> bool {
+        self.start_datetime() < other.end_datetime() && other.start_datetime() < self.end_datetime()
+    }
+
+    /// The worked time per calendar day, splitting entries at midnight.
+    pub fn split_at_midnight(&self) -> Vec<(NaiveDate, TimeDelta)> {
+        let mut res = vec![];
+        let mut current = self.start_datetime();
+        let end = self.end_datetime();
+        while current < end {
+            let next_midnight = current.date().succ_opt().unwrap().and_time(NaiveTime::MIN);
+            let part_end = next_midnight.min(end);
+            res.push((current.date(), part_end - current));
+            current = part_end;
+        }
+        res
+    }
+
+    /// The part of this entry that falls on `date`.
+    pub fn duration_on(&self, date: NaiveDate) -> TimeDelta {
+        self.split_at_midnight()
+            .into_iter()
+            .filter(|(d, _)| *d == date)
+            .map(|(_, d)| d)
+            .sum()
+    }
+}
+
+pub type TimeEntry = (TimeEntryId, TimeEntryData);
+
+/// An entry whose end is not known yet, i.e. a running timer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenTimeEntry {
+    pub start: NaiveDateTime,
+    pub remark: String,
+    pub project_id: Option<ProjectId>,
+}
+
+impl OpenTimeEntry {
+    pub fn new(start: NaiveDateTime, remark: String, project_id: Option<ProjectId>) -> Self {
+        Self {
+            start,
+            remark,
+            project_id,
         }
     }
+
+    pub fn elapsed(&self, now: NaiveDateTime) -> TimeDelta {
+        now - self.start
+    }
+
+    /// An entry can cross midnight once, so a timer running for a day or longer can't be closed.
+    pub fn close(&self, end: NaiveDateTime) -> Result<TimeEntryData, DataStorageError> {
+        if end <= self.start {
+            return Err(DataStorageError::StopBeforeStart);
+        }
+        if end - self.start >= TimeDelta::days(1) {
+            return Err(DataStorageError::TimerTooLong);
+        }
+        Ok(TimeEntryData {
+            start: self.start.time(),
+            end: end.time(),
+            date: self.start.date(),
+            end_date: end.date(),
+            remark: self.remark.clone(),
+            project_id: self.project_id,
+        })
+    }
 }
 
-pub type TimeEntry = (TimeEntryId, TimeEntryData);
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn closes_a_timer_crossing_midnight() {
+        let open = OpenTimeEntry::new(at(4, 22, 0), "".to_owned(), None);
+
+        let entry = open.close(at(5, 2, 0)).unwrap();
+
+        assert_eq!(entry, TimeEntryData::new(at(4, 0, 0).date(), at(4, 22, 0).time(), at(5, 2, 0).time(), "".to_owned(), None).unwrap());
+        assert_eq!(entry.duration(), TimeDelta::hours(4));
+    }
+
+    #[test]
+    fn rejects_stops_at_or_before_the_start() {
+        let open = OpenTimeEntry::new(at(4, 8, 0), "".to_owned(), None);
+
+        assert_eq!(open.close(at(4, 8, 0)), Err(DataStorageError::StopBeforeStart));
+        assert_eq!(open.close(at(4, 7, 59)), Err(DataStorageError::StopBeforeStart));
+    }
+
+    #[test]
+    fn rejects_timers_running_a_day() {
+        let open = OpenTimeEntry::new(at(4, 8, 0), "".to_owned(), None);
+
+        assert!(open.close(at(5, 7, 59)).is_ok());
+        assert_eq!(open.close(at(5, 8, 0)), Err(DataStorageError::TimerTooLong));
+        assert_eq!(open.close(at(6, 9, 0)), Err(DataStorageError::TimerTooLong));
+    }
+}
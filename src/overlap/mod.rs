@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    format::formatter,
+    i18n::{t, Texts},
+    model::{
+        error::ApplicationError,
+        time_entry::{TimeEntry, TimeEntryData, TimeEntryId},
+    },
+};
+
+/// What happens when an entry overlaps another one.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OverlapHandling {
+    /// The entry is saved and marked.
+    #[default]
+    Warn,
+    /// The entry is not saved.
+    Block,
+}
+
+impl OverlapHandling {
+    pub const ALL: [OverlapHandling; 2] = [OverlapHandling::Warn, OverlapHandling::Block];
+
+    pub fn name(&self) -> &'static str {
+        t(match self {
+            OverlapHandling::Warn => Texts::OverlapWarn,
+            OverlapHandling::Block => Texts::OverlapBlock,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct OverlapSettings {
+    pub handling: OverlapHandling,
+    /// Show the time between the entries of a day as breaks.
+    pub show_gaps: bool,
+}
+
+impl OverlapSettings {
+    /// Fails if the data overlaps one of the entries and overlaps are blocked.
+    pub fn check(&self, overlapping: &[TimeEntry]) -> Result<(), ApplicationError> {
+        match self.handling {
+            OverlapHandling::Block if !overlapping.is_empty() => Err(ApplicationError::Overlap(describe(overlapping))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The entries the data overlaps, `ignore` skips the edited entry itself.
+pub fn find(data: &TimeEntryData, entries: &[TimeEntry], ignore: Option<TimeEntryId>) -> Vec<TimeEntry> {
+    entries
+        .iter()
+        .filter(|(id, other)| Some(*id) != ignore && data.overlaps(other))
+        .cloned()
+        .collect()
+}
+
+/// Ids of all entries that overlap at least one other entry.
+pub fn overlapping_ids(entries: &[TimeEntry]) -> HashSet<TimeEntryId> {
+    let mut res = HashSet::new();
+    for (i, (id, data)) in entries.iter().enumerate() {
+        for (other_id, other) in &entries[i + 1..] {
+            if data.overlaps(other) {
+                res.insert(*id);
+                res.insert(*other_id);
+            }
+        }
+    }
+    res
+}
+
+/// The spans between the entries in chronological order, overlapping entries leave no gap.
+pub fn gaps(entries: &[TimeEntry]) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let mut spans: Vec<(NaiveDateTime, NaiveDateTime)> =
+        entries.iter().map(|(_, e)| (e.start_datetime(), e.end_datetime())).collect();
+    spans.sort();
+    let mut res = vec![];
+    let mut end: Option<NaiveDateTime> = None;
+    for (start, span_end) in spans {
+        if let Some(end) = end.filter(|end| *end < start) {
+            res.push((end, start));
+        }
+        end = Some(end.map_or(span_end, |end| end.max(span_end)));
+    }
+    res
+}
+
+/// Start and end of the entries, e.g. for messages.
+pub fn describe(entries: &[TimeEntry]) -> String {
+    let formatter = formatter();
+    entries
+        .iter()
+        .map(|(_, e)| format!("{} – {}", formatter.time(e.start), formatter.time(e.end)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn at(h: u32, m: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 4).unwrap().and_time(time(h, m))
+    }
+
+    /// Start and end of an entry written as `(hour, minute)`.
+    type Span = ((u32, u32), (u32, u32));
+
+    /// Entries on the same day.
+    fn entries(spans: &[Span]) -> Vec<TimeEntry> {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        spans
+            .iter()
+            .zip(1..)
+            .map(|(((sh, sm), (eh, em)), id)| {
+                (id, TimeEntryData::new(date, time(*sh, *sm), time(*eh, *em), "".to_owned(), None).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn touching_entries_do_not_overlap() {
+        let entries = entries(&[((8, 0), (12, 0)), ((12, 0), (16, 0))]);
+        assert!(overlapping_ids(&entries).is_empty());
+        assert!(find(&entries[1].1, &entries, Some(entries[1].0)).is_empty());
+        assert!(gaps(&entries).is_empty());
+    }
+
+    #[test]
+    fn nested_entries_overlap_and_leave_no_gap() {
+        let entries = entries(&[((8, 0), (16, 0)), ((10, 0), (11, 0)), ((17, 0), (18, 0))]);
+        assert_eq!(overlapping_ids(&entries), HashSet::from([1, 2]));
+        assert_eq!(find(&entries[1].1, &entries, Some(entries[1].0)), vec![entries[0].clone()]);
+        assert_eq!(gaps(&entries), vec![(at(16, 0), at(17, 0))]);
+    }
+
+    #[test]
+    fn gaps_are_sorted_by_time() {
+        let entries = entries(&[((13, 0), (16, 0)), ((8, 0), (12, 0)), ((16, 30), (17, 0))]);
+        assert_eq!(gaps(&entries), vec![(at(12, 0), at(13, 0)), (at(16, 0), at(16, 30))]);
+    }
+
+    #[test]
+    fn an_empty_day_has_no_overlaps_or_gaps() {
+        assert!(overlapping_ids(&[]).is_empty());
+        assert!(gaps(&[]).is_empty());
+        assert!(entries(&[((8, 0), (12, 0))]).iter().all(|(_, e)| find(e, &[], None).is_empty()));
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use serde::{Deserialize, Serialize};
+
+use crate::{compliance::ComplianceRules, model::time_entry::TimeEntry};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    Up,
+    Down,
+    Nearest,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingScope {
+    Entry,
+    Day,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rounding {
+    /// Increment in minutes, e.g. 5, 15 or 30.
+    pub increment: i64,
+    pub mode: RoundingMode,
+    pub scope: RoundingScope,
+}
+
+impl Rounding {
+    pub fn apply(&self, delta: TimeDelta) -> TimeDelta {
+        let increment = self.increment.max(1) * 60;
+        let seconds = delta.num_seconds();
+        let rest = seconds.rem_euclid(increment);
+        let down = seconds - rest;
+        let rounded = match self.mode {
+            RoundingMode::Down => down,
+            RoundingMode::Up if rest == 0 => down,
+            RoundingMode::Up => down + increment,
+            RoundingMode::Nearest if rest * 2 >= increment => down + increment,
+            RoundingMode::Nearest => down,
+        };
+        TimeDelta::seconds(rounded)
+    }
+}
+
+/// Turns raw entries into countable durations.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct TimeRules {
+    /// Deduct the statutory break if the recorded gaps are too short.
+    pub deduct_breaks: bool,
+    pub rounding: Option<Rounding>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DayDuration {
+    pub raw: TimeDelta,
+    pub deducted_break: TimeDelta,
+    /// Time of absences that count as worked, included in `adjusted`.
+    pub credited: TimeDelta,
+    pub adjusted: TimeDelta,
+}
+
+impl DayDuration {
+    pub fn zero() -> Self {
+        Self {
+            raw: TimeDelta::zero(),
+            deducted_break: TimeDelta::zero(),
+            credited: TimeDelta::zero(),
+            adjusted: TimeDelta::zero(),
+        }
+    }
+
+    pub fn credit(&mut self, duration: TimeDelta) {
+        self.credited += duration;
+        self.adjusted += duration;
+    }
+}
+
+impl std::iter::Sum for DayDuration {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, d| Self {
+            raw: acc.raw + d.raw,
+            deducted_break: acc.deducted_break + d.deducted_break,
+            credited: acc.credited + d.credited,
+            adjusted: acc.adjusted + d.adjusted,
+        })
+    }
+}
+
+impl TimeRules {
+    /// The smallest deduction so that the remaining work time is covered by
+    /// the taken break plus the deduction.
+    fn break_deduction(&self, compliance: &ComplianceRules, worked: TimeDelta, taken: TimeDelta) -> TimeDelta {
+        (0..=compliance.second_break.max(compliance.first_break))
+            .map(TimeDelta::minutes)
+            .find(|deduction| compliance.required_break(worked - *deduction) <= taken + *deduction)
+            .unwrap_or(TimeDelta::zero())
+            .min(worked)
+    }
+
+    fn day(&self, compliance: &ComplianceRules, mut parts: Vec<(NaiveDateTime, NaiveDateTime)>) -> DayDuration {
+        parts.sort();
+        let raw: TimeDelta = parts.iter().map(|(s, e)| *e - *s).sum();
+        let mut adjusted = match self.rounding {
+            Some(r) if r.scope == RoundingScope::Entry => parts.iter().map(|(s, e)| r.apply(*e - *s)).sum(),
+            _ => raw,
+        };
+        let deducted_break = if self.deduct_breaks {
+            self.break_deduction(compliance, raw, compliance.taken_break(&parts))
+        } else {
+            TimeDelta::zero()
+        };
+        adjusted -= deducted_break;
+        if let Some(r) = self.rounding.filter(|r| r.scope == RoundingScope::Day) {
+            adjusted = r.apply(adjusted);
+        }
+        DayDuration {
+            raw,
+            deducted_break,
+            credited: TimeDelta::zero(),
+            adjusted: adjusted.max(TimeDelta::zero()),
+        }
+    }
+
+    /// Raw and adjusted durations per calendar day, entries crossing midnight are split.
+    pub fn evaluate(&self, compliance: &ComplianceRules, entries: &[TimeEntry]) -> BTreeMap<NaiveDate, DayDuration> {
+        let mut days: BTreeMap<NaiveDate, Vec<(NaiveDateTime, NaiveDateTime)>> = BTreeMap::new();
+        for (_, e) in entries {
+            let mut start = e.start_datetime();
+            for (date, duration) in e.split_at_midnight() {
+                days.entry(date).or_default().push((start, start + duration));
+                start += duration;
+            }
+        }
+        days.into_iter()
+            .map(|(date, parts)| (date, self.day(compliance, parts)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rounding(mode: RoundingMode) -> Rounding {
+        Rounding {
+            increment: 15,
+            mode,
+            scope: RoundingScope::Entry,
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 4).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn deduction(parts: Vec<(NaiveDateTime, NaiveDateTime)>) -> DayDuration {
+        let rules = TimeRules {
+            deduct_breaks: true,
+            rounding: None,
+        };
+        rules.day(&ComplianceRules::default(), parts)
+    }
+
+    #[test]
+    fn rounds_to_the_increment() {
+        let cases = [
+            // (mode, seconds, expected minutes)
+            (RoundingMode::Up, 30 * 60, 30),
+            (RoundingMode::Up, 30 * 60 + 1, 45),
+            (RoundingMode::Up, 0, 0),
+            (RoundingMode::Down, 45 * 60, 45),
+            (RoundingMode::Down, 45 * 60 - 1, 30),
+            (RoundingMode::Nearest, 30 * 60, 30),
+            (RoundingMode::Nearest, 37 * 60 + 29, 30),
+            (RoundingMode::Nearest, 37 * 60 + 30, 45),
+            (RoundingMode::Nearest, 45 * 60 - 1, 45),
+        ];
+        for (mode, seconds, expected) in cases {
+            assert_eq!(
+                rounding(mode).apply(TimeDelta::seconds(seconds)),
+                TimeDelta::minutes(expected),
+                "{mode:?} {seconds}s"
+            );
+        }
+    }
+
+    #[test]
+    fn deducts_the_missing_break() {
+        let day = deduction(vec![(at(8, 0), at(16, 0))]);
+        assert_eq!(day.deducted_break, TimeDelta::minutes(30));
+        assert_eq!(day.adjusted, TimeDelta::minutes(7 * 60 + 30));
+
+        // only as much as needed to stay below the threshold
+        let day = deduction(vec![(at(8, 0), at(14, 5))]);
+        assert_eq!(day.deducted_break, TimeDelta::minutes(5));
+        assert_eq!(day.adjusted, TimeDelta::hours(6));
+    }
+
+    #[test]
+    fn recorded_breaks_are_not_deducted_again() {
+        let day = deduction(vec![(at(8, 0), at(12, 0)), (at(12, 30), at(16, 30))]);
+        assert_eq!(day.deducted_break, TimeDelta::zero());
+        assert_eq!(day.adjusted, TimeDelta::hours(8));
+
+        let day = deduction(vec![(at(8, 0), at(12, 0)), (at(12, 15), at(15, 15))]);
+        assert_eq!(day.deducted_break, TimeDelta::minutes(15));
+        assert_eq!(day.adjusted, TimeDelta::minutes(6 * 60 + 45));
+    }
+
+    #[test]
+    fn day_rounding_applies_after_the_deduction() {
+        let rules = TimeRules {
+            deduct_breaks: true,
+            rounding: Some(Rounding {
+                scope: RoundingScope::Day,
+                ..rounding(RoundingMode::Down)
+            }),
+        };
+        let day = rules.day(&ComplianceRules::default(), vec![(at(8, 0), at(16, 7))]);
+        assert_eq!(day.raw, TimeDelta::minutes(8 * 60 + 7));
+        assert_eq!(day.adjusted, TimeDelta::minutes(7 * 60 + 30));
+    }
+}
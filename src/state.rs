@@ -4,6 +4,6 @@ use crate::{views::overview::Overview, Services};
 
 pub enum AppState {
     Init,
-    Loaded(Overview, Services),
+    Loaded(Box<Overview>, Box<Services>),
     Failed(String)
 }
\ No newline at end of file
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+
+use crate::{
+    balance::Days,
+    extensions::naive_date_ext::NaiveDateExt,
+    format::formatter,
+    i18n::{month_name, t, Texts},
+    model::date_range::DateRange,
+};
+
+/// Length of the periods hours are summed up over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Granularity {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl Granularity {
+    pub const ALL: [Granularity; 3] = [Granularity::Day, Granularity::Week, Granularity::Month];
+
+    pub fn name(&self) -> &'static str {
+        t(match self {
+            Granularity::Day => Texts::Day,
+            Granularity::Week => Texts::Week,
+            Granularity::Month => Texts::Month,
+        })
+    }
+
+    /// The first day of the period containing the date.
+    pub fn period_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Granularity::Day => date,
+            Granularity::Week => date.as_week_range().0,
+            Granularity::Month => date.as_month_range().0,
+        }
+    }
+}
+
+/// Actual and planned time of one period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bucket {
+    pub start: NaiveDate,
+    pub actual: TimeDelta,
+    pub planned: TimeDelta,
+}
+
+/// Consecutive days with recorded work.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Streak {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Streak {
+    pub fn days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+}
+
+/// Key figures of a date range, independent of how they are shown.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Statistics {
+    pub range: DateRange,
+    pub granularity: Granularity,
+    pub buckets: Vec<Bucket>,
+    /// Number of days whose work started in the given hour.
+    pub start_hours: [u32; 24],
+    /// Number of days whose work ended in the given hour.
+    pub end_hours: [u32; 24],
+    pub worked_days: usize,
+    pub actual: TimeDelta,
+    pub planned: TimeDelta,
+    /// Net time per day with recorded work.
+    pub average_day: Option<TimeDelta>,
+    pub average_start: Option<NaiveTime>,
+    pub average_end: Option<NaiveTime>,
+    pub longest_streak: Option<Streak>,
+}
+
+fn average_time(times: &[NaiveTime]) -> Option<NaiveTime> {
+    if times.is_empty() {
+        return None;
+    }
+    let seconds: u64 = times.iter().map(|t| t.num_seconds_from_midnight() as u64).sum();
+    NaiveTime::from_num_seconds_from_midnight_opt((seconds / times.len() as u64) as u32, 0)
+}
+
+/// Computes the statistics of the range from the loaded days.
+/// Start and end times are taken from the first and last entry starting on a day.
+pub fn compute(days: &Days, range: DateRange, granularity: Granularity) -> Statistics {
+    let mut buckets: BTreeMap<NaiveDate, Bucket> = BTreeMap::new();
+    for date in range.0.iter_days().take_while(|d| *d <= range.1) {
+        let start = granularity.period_start(date);
+        let bucket = buckets.entry(start).or_insert(Bucket {
+            start,
+            actual: TimeDelta::zero(),
+            planned: TimeDelta::zero(),
+        });
+        bucket.actual += days.durations.get(&date).map(|d| d.adjusted).unwrap_or(TimeDelta::zero());
+        bucket.planned += days.planned_hours.get(&date).copied().unwrap_or(TimeDelta::zero());
+    }
+
+    let mut first_last: BTreeMap<NaiveDate, (NaiveDateTime, NaiveDateTime)> = BTreeMap::new();
+    for (_, entry) in days.entries.iter().filter(|e| e.1.date >= range.0 && e.1.date <= range.1) {
+        let (start, end) = (entry.start_datetime(), entry.end_datetime());
+        let times = first_last.entry(entry.date).or_insert((start, end));
+        times.0 = times.0.min(start);
+        times.1 = times.1.max(end);
+    }
+    let starts: Vec<NaiveTime> = first_last.values().map(|t| t.0.time()).collect();
+    let ends: Vec<NaiveTime> = first_last.values().map(|t| t.1.time()).collect();
+    let mut start_hours = [0; 24];
+    let mut end_hours = [0; 24];
+    for start in &starts {
+        start_hours[start.hour() as usize] += 1;
+    }
+    for end in &ends {
+        end_hours[end.hour() as usize] += 1;
+    }
+
+    let worked: Vec<(NaiveDate, TimeDelta)> = days
+        .durations
+        .iter()
+        .filter(|(date, d)| **date >= range.0 && **date <= range.1 && d.raw > TimeDelta::zero())
+        .map(|(date, d)| (*date, d.adjusted))
+        .collect();
+    let worked_total: TimeDelta = worked.iter().map(|w| w.1).sum();
+    let average_day = (!worked.is_empty()).then(|| TimeDelta::seconds(worked_total.num_seconds() / worked.len() as i64));
+
+    let mut longest_streak: Option<Streak> = None;
+    let mut current: Option<Streak> = None;
+    for (date, _) in &worked {
+        let streak = match current {
+            Some(streak) if streak.end.succ_opt() == Some(*date) => Streak { end: *date, ..streak },
+            _ => Streak {
+                start: *date,
+                end: *date,
+            },
+        };
+        if longest_streak.is_none_or(|l| streak.days() > l.days()) {
+            longest_streak = Some(streak);
+        }
+        current = Some(streak);
+    }
+
+    let buckets: Vec<Bucket> = buckets.into_values().collect();
+    Statistics {
+        range,
+        granularity,
+        actual: buckets.iter().map(|b| b.actual).sum(),
+        planned: buckets.iter().map(|b| b.planned).sum(),
+        buckets,
+        start_hours,
+        end_hours,
+        worked_days: worked.len(),
+        average_day,
+        average_start: average_time(&starts),
+        average_end: average_time(&ends),
+        longest_streak,
+    }
+}
+
+/// Short label of a period, e.g. for chart axes.
+pub fn period_label(granularity: Granularity, start: NaiveDate) -> String {
+    match granularity {
+        Granularity::Day => formatter().date(start),
+        Granularity::Week => format!("{} {}", t(Texts::CalendarWeek), start.iso_week().week()),
+        Granularity::Month => format!("{} {}", month_name(start.month()).unwrap(), start.year()),
+    }
+}
@@ -1,9 +1,18 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::{BTreeMap, HashMap}, sync::Arc};
 
-use chrono::{DateTime, Local, NaiveDate, TimeDelta};
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
 use egui::mutex::RwLock;
 
-use crate::model::{date_range::DateRange, time_entry::{TimeEntryData, TimeEntryId}};
+use crate::{
+    audit::Change,
+    holidays::{DayOff, DayOffData, DayOffId, State},
+    model::{
+        date_range::DateRange,
+        project::{Client, ClientData, ClientId, Project, ProjectData, ProjectId},
+        schedule::{Schedule, ScheduleData, ScheduleId},
+        time_entry::{OpenTimeEntry, TimeEntryData, TimeEntryId},
+    },
+};
 
 use super::{DataStorageError, PlannedHoursStorage, TimeEntry, TimeStorage};
 
@@ -68,11 +77,40 @@ impl<S: TimeStorage + Clone + Send + 'static> TimeStorage for CachedStorage<S, V
     fn remove_entry(
         &mut self,
         entry_id: super::TimeEntryId,
-    ) -> Result<(), super::DataStorageError> {
+    ) -> Result<super::TimeEntryData, super::DataStorageError> {
         self.last_query.invalidate();
         self.imp.remove_entry(entry_id)
     }
 
+    fn restore_entry(&mut self, entry: TimeEntry) -> Result<(), DataStorageError> {
+        self.last_query.invalidate();
+        self.imp.restore_entry(entry)
+    }
+
+    fn get_overlapping(&self, data: &TimeEntryData, ignore: Option<TimeEntryId>) -> Result<Vec<TimeEntry>, DataStorageError> {
+        self.imp.get_overlapping(data, ignore)
+    }
+
+    fn get_deleted(&self) -> Result<Vec<(TimeEntry, NaiveDateTime)>, DataStorageError> {
+        self.imp.get_deleted()
+    }
+
+    fn purge_entry(&mut self, entry_id: TimeEntryId) -> Result<(), DataStorageError> {
+        self.imp.purge_entry(entry_id)
+    }
+
+    fn purge_deleted_before(&mut self, before: NaiveDateTime) -> Result<Vec<TimeEntryId>, DataStorageError> {
+        self.imp.purge_deleted_before(before)
+    }
+
+    fn get_changes(&self, entry_id: TimeEntryId) -> Result<Vec<Change>, DataStorageError> {
+        self.imp.get_changes(entry_id)
+    }
+
+    fn get_change_log(&self) -> Result<Vec<Change>, DataStorageError> {
+        self.imp.get_change_log()
+    }
+
     fn get_in_range(
         &self,
         range: DateRange
@@ -84,6 +122,14 @@ impl<S: TimeStorage + Clone + Send + 'static> TimeStorage for CachedStorage<S, V
         self.do_query(range)
     }
 
+    fn get_in_range_for_project(&self, range: DateRange, project_id: ProjectId) -> Result<Vec<TimeEntry>, DataStorageError> {
+        self.imp.get_in_range_for_project(range, project_id)
+    }
+
+    fn get_daily_totals(&self, range: DateRange) -> Result<BTreeMap<NaiveDate, TimeDelta>, DataStorageError> {
+        self.imp.get_daily_totals(range)
+    }
+
     fn dyn_clone(&self) -> Box<dyn TimeStorage + Send> {
         Box::new(Self{
             imp: self.imp.clone(),
@@ -91,10 +137,60 @@ impl<S: TimeStorage + Clone + Send + 'static> TimeStorage for CachedStorage<S, V
         })
     }
     
-    fn update_entry(&mut self, entry_id: TimeEntryId, data: TimeEntryData) -> Result<(), DataStorageError> {
-        self.imp.update_entry(entry_id, data)?;
+    fn update_entry(&mut self, entry_id: TimeEntryId, data: TimeEntryData) -> Result<TimeEntryData, DataStorageError> {
+        let previous = self.imp.update_entry(entry_id, data)?;
+        self.last_query.invalidate();
+        Ok(previous)
+    }
+
+    fn add_project(&mut self, project: ProjectData) -> Result<ProjectId, DataStorageError> {
+        self.imp.add_project(project)
+    }
+
+    fn update_project(&mut self, project_id: ProjectId, data: ProjectData) -> Result<(), DataStorageError> {
+        self.imp.update_project(project_id, data)
+    }
+
+    fn remove_project(&mut self, project_id: ProjectId) -> Result<(), DataStorageError> {
+        self.last_query.invalidate();
+        self.imp.remove_project(project_id)
+    }
+
+    fn get_projects(&self) -> Result<Vec<Project>, DataStorageError> {
+        self.imp.get_projects()
+    }
+
+    fn add_client(&mut self, client: ClientData) -> Result<ClientId, DataStorageError> {
+        self.imp.add_client(client)
+    }
+
+    fn update_client(&mut self, client_id: ClientId, data: ClientData) -> Result<(), DataStorageError> {
+        self.imp.update_client(client_id, data)
+    }
+
+    fn remove_client(&mut self, client_id: ClientId) -> Result<(), DataStorageError> {
+        self.imp.remove_client(client_id)
+    }
+
+    fn get_clients(&self) -> Result<Vec<Client>, DataStorageError> {
+        self.imp.get_clients()
+    }
+
+    fn start_timer(&mut self, entry: OpenTimeEntry) -> Result<(), DataStorageError> {
+        self.imp.start_timer(entry)
+    }
+
+    fn get_running_timer(&self) -> Result<Option<OpenTimeEntry>, DataStorageError> {
+        self.imp.get_running_timer()
+    }
+
+    fn stop_timer(&mut self, end: NaiveDateTime) -> Result<TimeEntryId, DataStorageError> {
         self.last_query.invalidate();
-        Ok(())
+        self.imp.stop_timer(end)
+    }
+
+    fn discard_timer(&mut self) -> Result<(), DataStorageError> {
+        self.imp.discard_timer()
     }
 }
 
@@ -116,7 +212,10 @@ impl<S: TimeStorage> CachedStorage<S, Vec<TimeEntry>> {
     }
 }
 
-impl<S: PlannedHoursStorage> CachedStorage<S, HashMap<NaiveDate,TimeDelta>> {
+/// The planned hours are cached along with the state whose holidays they leave out.
+type PlannedHours = (Option<State>, HashMap<NaiveDate, TimeDelta>);
+
+impl<S: PlannedHoursStorage> CachedStorage<S, PlannedHours> {
     pub fn new_hours(imp: S) -> Self {
         Self {
             imp,
@@ -126,31 +225,70 @@ impl<S: PlannedHoursStorage> CachedStorage<S, HashMap<NaiveDate,TimeDelta>> {
 
     pub fn do_query(
         &self,
-        range: DateRange
+        range: DateRange,
+        state: Option<State>,
     ) -> Result<HashMap<NaiveDate,super::TimeDelta>, super::DataStorageError> {
-        let res = self.imp.get_range(range)?;
-        self.last_query.set_result(range ,Ok(res.clone()));
+        let res = self.imp.get_range(range, state)?;
+        self.last_query.set_result(range ,Ok((state, res.clone())));
         Ok(res)
     }
 }
 
 
-impl<S: PlannedHoursStorage + Clone + Send + 'static> PlannedHoursStorage for CachedStorage<S, HashMap<NaiveDate,TimeDelta>> {
-    fn set(&mut self, date: chrono::NaiveDate, duration: TimeDelta) -> Result<(), DataStorageError> {
+impl<S: PlannedHoursStorage + Clone + Send + 'static> PlannedHoursStorage for CachedStorage<S, PlannedHours> {
+    fn set(&mut self, date: chrono::NaiveDate, duration: TimeDelta) -> Result<Option<TimeDelta>, DataStorageError> {
         self.last_query.invalidate();
         self.imp.set(date, duration)
     }
 
-    fn get(&self, date: chrono::NaiveDate) -> Result<TimeDelta, DataStorageError> {
-        self.imp.get(date)
+    fn reset(&mut self, date: chrono::NaiveDate) -> Result<Option<TimeDelta>, DataStorageError> {
+        self.last_query.invalidate();
+        self.imp.reset(date)
     }
 
-    fn get_range(&self, range: DateRange) -> Result<HashMap<NaiveDate,TimeDelta>, DataStorageError> {
-        let cached = self.last_query.get_cached(range);
-        if let Some(val) = cached {
-            return val;
+    fn get(&self, date: chrono::NaiveDate, state: Option<State>) -> Result<TimeDelta, DataStorageError> {
+        self.imp.get(date, state)
+    }
+
+    fn get_range(&self, range: DateRange, state: Option<State>) -> Result<HashMap<NaiveDate,TimeDelta>, DataStorageError> {
+        match self.last_query.get_cached(range) {
+            Some(Ok((cached_state, res))) if cached_state == state => Ok(res),
+            Some(Err(err)) => Err(err),
+            _ => self.do_query(range, state),
         }
-        self.do_query(range)
+    }
+
+    fn add_schedule(&mut self, schedule: ScheduleData) -> Result<ScheduleId, DataStorageError> {
+        self.last_query.invalidate();
+        self.imp.add_schedule(schedule)
+    }
+
+    fn update_schedule(&mut self, schedule_id: ScheduleId, data: ScheduleData) -> Result<(), DataStorageError> {
+        self.last_query.invalidate();
+        self.imp.update_schedule(schedule_id, data)
+    }
+
+    fn remove_schedule(&mut self, schedule_id: ScheduleId) -> Result<(), DataStorageError> {
+        self.last_query.invalidate();
+        self.imp.remove_schedule(schedule_id)
+    }
+
+    fn get_schedules(&self) -> Result<Vec<Schedule>, DataStorageError> {
+        self.imp.get_schedules()
+    }
+
+    fn add_day_off(&mut self, day_off: DayOffData) -> Result<DayOffId, DataStorageError> {
+        self.last_query.invalidate();
+        self.imp.add_day_off(day_off)
+    }
+
+    fn remove_day_off(&mut self, day_off_id: DayOffId) -> Result<(), DataStorageError> {
+        self.last_query.invalidate();
+        self.imp.remove_day_off(day_off_id)
+    }
+
+    fn get_days_off(&self, range: DateRange) -> Result<Vec<DayOff>, DataStorageError> {
+        self.imp.get_days_off(range)
     }
 
     fn dyn_clone(&self) -> Box<dyn PlannedHoursStorage + Send> {
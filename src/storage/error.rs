@@ -5,5 +5,15 @@ pub enum DataStorageError {
     #[error("Unknown error occured: {0}")]
     Unknown(String),
     #[error("Not found")]
-    NotFound
+    NotFound,
+    #[error("Migration {version} failed: {message}")]
+    Migration { version: u32, message: String },
+    #[error("Database schema version {found} is newer than the supported version {supported}")]
+    SchemaTooNew { found: u32, supported: u32 },
+    #[error("The entry overlaps existing entries")]
+    Overlap,
+    #[error("The timer can only be stopped after its start")]
+    StopBeforeStart,
+    #[error("The timer ran for more than a day, discard it and add the times as entries")]
+    TimerTooLong,
 }
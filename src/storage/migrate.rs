@@ -1,37 +1,353 @@
-use log::debug;
-use rusqlite::{Connection, Error};
-
-pub fn migrate_db(connection: &mut Connection) {
-    debug!("Create tables");
-    let res = connection.execute(
-        "CREATE TABLE times (
-                                id      INTEGER PRIMARY KEY,
-                                start   TEXT NOT NULL,
-                                end     TEXT NOT NULL,
-                                date    TEXT NOT NULL,
-                                remark  TEXT
-                                )",
-        (),
-    );
-    if let Err(err) = res {
-        debug!("Error: {}", err.to_string());
-    }
-
-    let res = connection.execute("CREATE TABLE user_data (
-                                                                                    id          INTEGER PRIMARY KEY,
-                                                                                    name        TEXT NOT NULL,
-                                                                                    street      TEXT,
-                                                                                    citycode    TEXT
-                                                                                    )", ());
-    if let Err(err) = res {
-        debug!("Error: {}", err.to_string());
-    }
-
-    let res = connection.execute("CREATE TABLE planned_hours (
-        date        TEXT PRIMARY KEY,
-        hours       TEXT NOT NULL
-        )", ());
-if let Err(err) = res {
-debug!("Error: {}", err.to_string());
+use log::{debug, info};
+use rusqlite::Connection;
+
+use super::error::DataStorageError;
+
+/// A single, numbered schema change.
+///
+/// Migrations are applied in ascending `version` order and each one runs inside
+/// its own transaction together with the bump of `PRAGMA user_version`, so a
+/// database is never left half migrated.
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+/// All migrations known to this binary. Never edit or reorder an entry that
+/// has been released, always append a new one with the next version.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        // `IF NOT EXISTS` adopts databases created before versioning was introduced.
+        sql: "CREATE TABLE IF NOT EXISTS times (
+                id      INTEGER PRIMARY KEY,
+                start   TEXT NOT NULL,
+                end     TEXT NOT NULL,
+                date    TEXT NOT NULL,
+                remark  TEXT
+            );
+            CREATE TABLE IF NOT EXISTS user_data (
+                id          INTEGER PRIMARY KEY,
+                name        TEXT NOT NULL,
+                street      TEXT,
+                citycode    TEXT
+            );
+            CREATE TABLE IF NOT EXISTS planned_hours (
+                date        TEXT PRIMARY KEY,
+                hours       TEXT NOT NULL
+            );",
+    },
+    Migration {
+        version: 2,
+        description: "projects and clients",
+        sql: "CREATE TABLE clients (
+                id          INTEGER PRIMARY KEY,
+                name        TEXT NOT NULL,
+                color       INTEGER NOT NULL,
+                archived    INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE projects (
+                id          INTEGER PRIMARY KEY,
+                client_id   INTEGER REFERENCES clients(id),
+                name        TEXT NOT NULL,
+                color       INTEGER NOT NULL,
+                archived    INTEGER NOT NULL DEFAULT 0
+            );
+            ALTER TABLE times ADD COLUMN project_id INTEGER REFERENCES projects(id);",
+    },
+    Migration {
+        version: 3,
+        description: "running timer",
+        sql: "CREATE TABLE running_timer (
+                id          INTEGER PRIMARY KEY CHECK (id = 1),
+                start       TEXT NOT NULL,
+                remark      TEXT,
+                project_id  INTEGER REFERENCES projects(id)
+            );",
+    },
+    Migration {
+        version: 4,
+        description: "end date for entries crossing midnight",
+        sql: "ALTER TABLE times ADD COLUMN end_date TEXT;
+            UPDATE times SET end_date = date;",
+    },
+    Migration {
+        version: 5,
+        description: "extended user profiles",
+        sql: "ALTER TABLE user_data ADD COLUMN city TEXT;
+            ALTER TABLE user_data ADD COLUMN employee_number TEXT;
+            ALTER TABLE user_data ADD COLUMN employer TEXT;
+            ALTER TABLE user_data ADD COLUMN email TEXT;",
+    },
+    Migration {
+        version: 6,
+        description: "weekly work schedules",
+        sql: "CREATE TABLE schedules (
+                id          INTEGER PRIMARY KEY,
+                valid_from  TEXT NOT NULL UNIQUE,
+                monday      INTEGER NOT NULL,
+                tuesday     INTEGER NOT NULL,
+                wednesday   INTEGER NOT NULL,
+                thursday    INTEGER NOT NULL,
+                friday      INTEGER NOT NULL,
+                saturday    INTEGER NOT NULL,
+                sunday      INTEGER NOT NULL
+            );",
+    },
+    Migration {
+        version: 7,
+        description: "company days off",
+        sql: "CREATE TABLE days_off (
+                id          INTEGER PRIMARY KEY,
+                date        TEXT NOT NULL UNIQUE,
+                name        TEXT NOT NULL
+            );",
+    },
+    Migration {
+        version: 8,
+        description: "absences and vacation entitlements",
+        sql: "CREATE TABLE absences (
+                id          INTEGER PRIMARY KEY,
+                kind        TEXT NOT NULL,
+                start       TEXT NOT NULL,
+                end         TEXT NOT NULL,
+                half_day    INTEGER NOT NULL DEFAULT 0,
+                remark      TEXT
+            );
+            CREATE TABLE vacation_entitlements (
+                year        INTEGER PRIMARY KEY,
+                days        REAL NOT NULL
+            );",
+    },
+    Migration {
+        version: 9,
+        description: "flextime balance adjustments",
+        sql: "CREATE TABLE balance_adjustments (
+                id          INTEGER PRIMARY KEY,
+                date        TEXT NOT NULL,
+                kind        TEXT NOT NULL,
+                amount      INTEGER NOT NULL,
+                remark      TEXT
+            );",
+    },
+    Migration {
+        version: 10,
+        description: "soft deleted time entries",
+        sql: "ALTER TABLE times ADD COLUMN deleted_at TEXT;",
+    },
+    Migration {
+        version: 11,
+        description: "append-only change log of time entries",
+        sql: "CREATE TABLE entry_changes (
+                id              INTEGER PRIMARY KEY,
+                entry_id        INTEGER NOT NULL,
+                changed_at      TEXT NOT NULL,
+                action          TEXT NOT NULL,
+                old_value       TEXT,
+                new_value       TEXT,
+                previous_hash   TEXT NOT NULL,
+                hash            TEXT NOT NULL
+            );
+            CREATE INDEX entry_changes_entry ON entry_changes (entry_id);
+            CREATE TRIGGER entry_changes_no_update BEFORE UPDATE ON entry_changes
+            BEGIN
+                SELECT RAISE(ABORT, 'the change log is append-only');
+            END;
+            CREATE TRIGGER entry_changes_no_delete BEFORE DELETE ON entry_changes
+            BEGIN
+                SELECT RAISE(ABORT, 'the change log is append-only');
+            END;",
+    },
+    Migration {
+        version: 12,
+        description: "never reuse ids of purged time entries",
+        // the sequence starts after every id in the change log, purged ones included
+        sql: "CREATE TABLE times_new (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                start       TEXT NOT NULL,
+                end         TEXT NOT NULL,
+                date        TEXT NOT NULL,
+                remark      TEXT,
+                project_id  INTEGER REFERENCES projects(id),
+                end_date    TEXT,
+                deleted_at  TEXT
+            );
+            INSERT INTO times_new (id, start, end, date, remark, project_id, end_date, deleted_at)
+                SELECT id, start, end, date, remark, project_id, end_date, deleted_at FROM times;
+            DROP TABLE times;
+            ALTER TABLE times_new RENAME TO times;
+            DELETE FROM sqlite_sequence WHERE name = 'times';
+            INSERT INTO sqlite_sequence (name, seq)
+                SELECT 'times', max(coalesce((SELECT max(id) FROM times), 0), coalesce((SELECT max(entry_id) FROM entry_changes), 0));",
+    },
+    Migration {
+        version: 13,
+        description: "a database per profile",
+        // the data recorded so far stays with the first profile
+        sql: "ALTER TABLE user_data ADD COLUMN data_file TEXT;
+            UPDATE user_data SET data_file = 'profile-' || lower(hex(randomblob(8))) || '.sqlite'
+                WHERE id <> (SELECT min(id) FROM user_data);",
+    },
+];
+
+pub fn schema_version(connection: &Connection) -> Result<u32, DataStorageError> {
+    Ok(connection.pragma_query_value(None, "user_version", |r| r.get(0))?)
 }
+
+pub fn migrate_db(connection: &mut Connection) -> Result<(), DataStorageError> {
+    run(connection, MIGRATIONS)
+}
+
+fn run(connection: &mut Connection, migrations: &[Migration]) -> Result<(), DataStorageError> {
+    let current = schema_version(connection)?;
+    let supported = migrations.last().map(|m| m.version).unwrap_or(0);
+    debug!("Schema version {current}, supported {supported}");
+    if current > supported {
+        return Err(DataStorageError::SchemaTooNew {
+            found: current,
+            supported,
+        });
+    }
+
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        info!(
+            "Applying migration {}: {}",
+            migration.version, migration.description
+        );
+        apply(connection, migration).map_err(|err| DataStorageError::Migration {
+            version: migration.version,
+            message: err.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+fn apply(connection: &mut Connection, migration: &Migration) -> Result<(), rusqlite::Error> {
+    let tx = connection.transaction()?;
+    tx.execute_batch(migration.sql)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latest_version() -> u32 {
+        MIGRATIONS.last().unwrap().version
+    }
+
+    fn memory() -> Connection {
+        Connection::open_in_memory().unwrap()
+    }
+
+    #[test]
+    fn adopts_legacy_database() {
+        let mut connection = memory();
+        connection
+            .execute_batch(
+                "CREATE TABLE times (
+                    id      INTEGER PRIMARY KEY,
+                    start   TEXT NOT NULL,
+                    end     TEXT NOT NULL,
+                    date    TEXT NOT NULL,
+                    remark  TEXT
+                );
+                CREATE TABLE user_data (
+                    id          INTEGER PRIMARY KEY,
+                    name        TEXT NOT NULL,
+                    street      TEXT,
+                    citycode    TEXT
+                );
+                CREATE TABLE planned_hours (
+                    date        TEXT PRIMARY KEY,
+                    hours       TEXT NOT NULL
+                );
+                INSERT INTO times (start, end, date, remark) VALUES ('08:00:00', '12:00:00', '2024-03-04', 'legacy');",
+            )
+            .unwrap();
+        assert_eq!(schema_version(&connection).unwrap(), 0);
+
+        migrate_db(&mut connection).unwrap();
+
+        assert_eq!(schema_version(&connection).unwrap(), latest_version());
+        let (remark, end_date): (String, String) = connection
+            .query_row("SELECT remark, end_date FROM times", (), |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap();
+        assert_eq!(remark, "legacy");
+        assert_eq!(end_date, "2024-03-04");
+    }
+
+    #[test]
+    fn entry_ids_continue_after_the_change_log() {
+        let mut connection = memory();
+        run(&mut connection, &MIGRATIONS[..11]).unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO times (id, start, end, date, end_date) VALUES (3, '08:00:00', '12:00:00', '2024-03-04', '2024-03-04');
+                INSERT INTO entry_changes (entry_id, changed_at, action, previous_hash, hash) VALUES (7, '2024-03-04 12:00:00', 'purge', '', '');",
+            )
+            .unwrap();
+
+        migrate_db(&mut connection).unwrap();
+
+        connection
+            .execute("INSERT INTO times (start, end, date, end_date) VALUES ('13:00:00', '14:00:00', '2024-03-04', '2024-03-04')", ())
+            .unwrap();
+        assert_eq!(connection.last_insert_rowid(), 8);
+    }
+
+    #[test]
+    fn migrating_twice_is_a_no_op() {
+        let mut connection = memory();
+        migrate_db(&mut connection).unwrap();
+        migrate_db(&mut connection).unwrap();
+        assert_eq!(schema_version(&connection).unwrap(), latest_version());
+    }
+
+    #[test]
+    fn rejects_newer_schema() {
+        let mut connection = memory();
+        connection.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+
+        let err = migrate_db(&mut connection).unwrap_err();
+
+        assert!(matches!(
+            err,
+            DataStorageError::SchemaTooNew { found, supported } if found == latest_version() + 1 && supported == latest_version()
+        ));
+    }
+
+    #[test]
+    fn failing_step_rolls_back() {
+        let migrations = [
+            Migration {
+                version: 1,
+                description: "works",
+                sql: "CREATE TABLE first (id INTEGER PRIMARY KEY);",
+            },
+            Migration {
+                version: 2,
+                description: "fails halfway",
+                sql: "CREATE TABLE second (id INTEGER PRIMARY KEY);
+                    INSERT INTO missing VALUES (1);",
+            },
+        ];
+        let mut connection = memory();
+
+        let err = run(&mut connection, &migrations).unwrap_err();
+
+        assert!(matches!(err, DataStorageError::Migration { version: 2, .. }));
+        assert_eq!(schema_version(&connection).unwrap(), 1);
+        let tables: Vec<String> = connection
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name")
+            .unwrap()
+            .query_map((), |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(tables, vec!["first".to_owned()]);
+    }
 }
@@ -1,11 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeDelta};
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
 use error::DataStorageError;
 use serde::{Deserialize, Serialize};
-use thiserror::Error;
 
-use crate::model::{date_range::DateRange, time_entry::{TimeEntry, TimeEntryData, TimeEntryId}};
+use crate::{
+    absence::{Absence, AbsenceData, AbsenceId},
+    audit::Change,
+    balance::{Adjustment, AdjustmentData, AdjustmentId},
+    holidays::{DayOff, DayOffData, DayOffId, State},
+    model::{
+        date_range::DateRange,
+        project::{Client, ClientData, ClientId, Project, ProjectData, ProjectId},
+        schedule::{Schedule, ScheduleData, ScheduleId},
+        time_entry::{OpenTimeEntry, TimeEntry, TimeEntryData, TimeEntryId},
+    },
+    user::{UserData, UserId, UserProfile},
+};
 
 
 
@@ -20,25 +31,142 @@ pub enum StorageImplementation {
     Sqlite,
 }
 
+/// How long removed time entries stay in the trash.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct TrashSettings {
+    /// Entries are purged automatically after this many days, 0 keeps them forever.
+    pub retention_days: u32,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self { retention_days: 30 }
+    }
+}
+
+impl TrashSettings {
+    /// Entries removed before the returned time are due to be purged.
+    pub fn purge_before(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self.retention_days {
+            0 => None,
+            days => Some(now - TimeDelta::days(days as i64)),
+        }
+    }
+}
+
 
 
 
 pub trait TimeStorage {
     fn add_entry(&mut self, entry: TimeEntryData) -> Result<TimeEntryId, DataStorageError>;
-    fn remove_entry(&mut self, entry_id: TimeEntryId) -> Result<(), DataStorageError>;
-    fn update_entry(&mut self, entry_id: TimeEntryId, data: TimeEntryData) -> Result<(), DataStorageError>; 
+    /// Moves the entry to the trash and returns its data, so the removal can be reverted with `restore_entry`.
+    fn remove_entry(&mut self, entry_id: TimeEntryId) -> Result<TimeEntryData, DataStorageError>;
+    /// Brings back an entry in the trash under its old id, purged entries are not found.
+    fn restore_entry(&mut self, entry: TimeEntry) -> Result<(), DataStorageError>;
+    /// Entries in the trash with the time they were removed, the latest first.
+    fn get_deleted(&self) -> Result<Vec<(TimeEntry, NaiveDateTime)>, DataStorageError>;
+    /// Deletes an entry in the trash permanently.
+    fn purge_entry(&mut self, entry_id: TimeEntryId) -> Result<(), DataStorageError>;
+    /// Deletes the entries removed before the given time permanently and returns their ids.
+    fn purge_deleted_before(&mut self, before: NaiveDateTime) -> Result<Vec<TimeEntryId>, DataStorageError>;
+    /// Recorded changes of an entry, the oldest first.
+    fn get_changes(&self, entry_id: TimeEntryId) -> Result<Vec<Change>, DataStorageError>;
+    /// The complete change log in the order it was written.
+    fn get_change_log(&self) -> Result<Vec<Change>, DataStorageError>;
+    /// Returns the data before the update.
+    fn update_entry(&mut self, entry_id: TimeEntryId, data: TimeEntryData) -> Result<TimeEntryData, DataStorageError>;
     fn get_in_range(&self, range: DateRange) -> Result<Vec<TimeEntry>, DataStorageError>;
+    fn get_in_range_for_project(&self, range: DateRange, project_id: ProjectId) -> Result<Vec<TimeEntry>, DataStorageError>;
+    /// Entries overlapping the data, `ignore` skips the entry itself when it is updated.
+    fn get_overlapping(&self, data: &TimeEntryData, ignore: Option<TimeEntryId>) -> Result<Vec<TimeEntry>, DataStorageError>;
+    /// Raw worked time per day, aggregated by the storage. Entries crossing midnight are split.
+    fn get_daily_totals(&self, range: DateRange) -> Result<BTreeMap<NaiveDate, TimeDelta>, DataStorageError>;
+    fn add_project(&mut self, project: ProjectData) -> Result<ProjectId, DataStorageError>;
+    fn update_project(&mut self, project_id: ProjectId, data: ProjectData) -> Result<(), DataStorageError>;
+    fn remove_project(&mut self, project_id: ProjectId) -> Result<(), DataStorageError>;
+    fn get_projects(&self) -> Result<Vec<Project>, DataStorageError>;
+    fn add_client(&mut self, client: ClientData) -> Result<ClientId, DataStorageError>;
+    fn update_client(&mut self, client_id: ClientId, data: ClientData) -> Result<(), DataStorageError>;
+    fn remove_client(&mut self, client_id: ClientId) -> Result<(), DataStorageError>;
+    fn get_clients(&self) -> Result<Vec<Client>, DataStorageError>;
+    fn start_timer(&mut self, entry: OpenTimeEntry) -> Result<(), DataStorageError>;
+    fn get_running_timer(&self) -> Result<Option<OpenTimeEntry>, DataStorageError>;
+    fn stop_timer(&mut self, end: NaiveDateTime) -> Result<TimeEntryId, DataStorageError>;
+    /// Drops the running timer without recording an entry.
+    fn discard_timer(&mut self) -> Result<(), DataStorageError>;
     fn dyn_clone(&self) -> Box<dyn TimeStorage + Send>;
 }
 
 
+/// Planned hours come from the weekly schedules, a value set for a single day overrides them.
 pub trait PlannedHoursStorage {
-    fn set(&mut self, date: NaiveDate, duration: TimeDelta) -> Result<(), DataStorageError>;
-    fn get(&self, date: NaiveDate) -> Result<TimeDelta, DataStorageError>;
-    fn get_range(&self, range: DateRange) -> Result<HashMap<NaiveDate,TimeDelta>, DataStorageError>;
+    /// Overrides the planned hours of a day and returns the previous override.
+    fn set(&mut self, date: NaiveDate, duration: TimeDelta) -> Result<Option<TimeDelta>, DataStorageError>;
+    /// Removes the override of a day, so the schedule applies again. Returns the removed override.
+    fn reset(&mut self, date: NaiveDate) -> Result<Option<TimeDelta>, DataStorageError>;
+    fn get(&self, date: NaiveDate, state: Option<State>) -> Result<TimeDelta, DataStorageError>;
+    /// Days before the first schedule without an override are missing in the result. Nothing is
+    /// planned on the public holidays of the state and on company days off.
+    fn get_range(&self, range: DateRange, state: Option<State>) -> Result<HashMap<NaiveDate,TimeDelta>, DataStorageError>;
+    fn add_schedule(&mut self, schedule: ScheduleData) -> Result<ScheduleId, DataStorageError>;
+    fn update_schedule(&mut self, schedule_id: ScheduleId, data: ScheduleData) -> Result<(), DataStorageError>;
+    fn remove_schedule(&mut self, schedule_id: ScheduleId) -> Result<(), DataStorageError>;
+    /// All schedules, ordered by their start.
+    fn get_schedules(&self) -> Result<Vec<Schedule>, DataStorageError>;
+    fn add_day_off(&mut self, day_off: DayOffData) -> Result<DayOffId, DataStorageError>;
+    fn remove_day_off(&mut self, day_off_id: DayOffId) -> Result<(), DataStorageError>;
+    fn get_days_off(&self, range: DateRange) -> Result<Vec<DayOff>, DataStorageError>;
     fn dyn_clone(&self) -> Box<dyn PlannedHoursStorage + Send>;
 }
 
+pub trait UserDataStorage {
+    fn add_user(&mut self, data: UserData) -> Result<UserId, DataStorageError>;
+    fn update_user(&mut self, user_id: UserId, data: UserData) -> Result<(), DataStorageError>;
+    fn remove_user(&mut self, user_id: UserId) -> Result<(), DataStorageError>;
+    fn get_users(&self) -> Result<Vec<UserProfile>, DataStorageError>;
+    /// The database file holding the recorded data of the profile, relative to the data
+    /// directory. `None` means the main database.
+    fn get_data_file(&self, user_id: UserId) -> Result<Option<String>, DataStorageError>;
+    fn dyn_clone(&self) -> Box<dyn UserDataStorage + Send>;
+}
+
+pub trait AbsenceStorage {
+    fn add_absence(&mut self, absence: AbsenceData) -> Result<AbsenceId, DataStorageError>;
+    fn remove_absence(&mut self, absence_id: AbsenceId) -> Result<(), DataStorageError>;
+    /// Absences overlapping the range, ordered by their start.
+    fn get_absences(&self, range: DateRange) -> Result<Vec<Absence>, DataStorageError>;
+    fn set_vacation_entitlement(&mut self, year: i32, days: f64) -> Result<(), DataStorageError>;
+    /// The entitlement stored for the year, if any.
+    fn get_vacation_entitlement(&self, year: i32) -> Result<Option<f64>, DataStorageError>;
+    fn dyn_clone(&self) -> Box<dyn AbsenceStorage + Send>;
+}
+
+pub trait BalanceStorage {
+    fn add_adjustment(&mut self, adjustment: AdjustmentData) -> Result<AdjustmentId, DataStorageError>;
+    fn remove_adjustment(&mut self, adjustment_id: AdjustmentId) -> Result<(), DataStorageError>;
+    fn get_adjustments(&self, range: DateRange) -> Result<Vec<Adjustment>, DataStorageError>;
+    fn dyn_clone(&self) -> Box<dyn BalanceStorage + Send>;
+}
+
+impl Clone for Box<dyn BalanceStorage + Send> {
+    fn clone(&self) -> Self {
+        self.dyn_clone()
+    }
+}
+
+impl Clone for Box<dyn AbsenceStorage + Send> {
+    fn clone(&self) -> Self {
+        self.dyn_clone()
+    }
+}
+
+impl Clone for Box<dyn UserDataStorage + Send> {
+    fn clone(&self) -> Self {
+        self.dyn_clone()
+    }
+}
+
 impl Clone for Box<dyn PlannedHoursStorage + Send> {
     fn clone(&self) -> Self {
         self.dyn_clone()
@@ -1,45 +1,243 @@
-use crate::model::{date_range::DateRange, time_entry::{TimeEntry, TimeEntryData, TimeEntryId}};
+use crate::{
+    absence::{Absence, AbsenceData, AbsenceId},
+    balance::{Adjustment, AdjustmentData, AdjustmentId},
+    holidays::{DayOff, DayOffData, DayOffId, State},
+    model::{
+        date_range::DateRange,
+        project::{Client, ClientData, ClientId, Project, ProjectData, ProjectId},
+        schedule::{Schedule, ScheduleData, ScheduleId},
+        time_entry::{OpenTimeEntry, TimeEntry, TimeEntryData, TimeEntryId},
+    },
+    user::{UserData, UserId, UserProfile},
+};
 
-use super::{error::DataStorageError, PlannedHoursStorage, TimeStorage};
+use super::{error::DataStorageError, AbsenceStorage, BalanceStorage, PlannedHoursStorage, TimeStorage, UserDataStorage};
 
+/// Stores nothing and finds nothing, used while the real storage is loaded.
 pub struct NullService;
 
 impl PlannedHoursStorage for NullService {
-    fn set(&mut self, date: chrono::NaiveDate, duration: chrono::TimeDelta) -> Result<(), DataStorageError> {
-        todo!()
+    fn set(&mut self, _date: chrono::NaiveDate, _duration: chrono::TimeDelta) -> Result<Option<chrono::TimeDelta>, DataStorageError> {
+        Ok(Default::default())
     }
 
-    fn get(&self, date: chrono::NaiveDate) -> Result<chrono::TimeDelta, DataStorageError> {
-        todo!()
+    fn reset(&mut self, _date: chrono::NaiveDate) -> Result<Option<chrono::TimeDelta>, DataStorageError> {
+        Ok(Default::default())
     }
 
-    fn get_range(&self, range: DateRange) -> Result<std::collections::HashMap<chrono::NaiveDate,chrono::TimeDelta>, DataStorageError> {
-        todo!()
+    fn get(&self, _date: chrono::NaiveDate, _state: Option<State>) -> Result<chrono::TimeDelta, DataStorageError> {
+        Ok(chrono::TimeDelta::zero())
+    }
+
+    fn get_range(&self, _range: DateRange, _state: Option<State>) -> Result<std::collections::HashMap<chrono::NaiveDate,chrono::TimeDelta>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn add_schedule(&mut self, _schedule: ScheduleData) -> Result<ScheduleId, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn update_schedule(&mut self, _schedule_id: ScheduleId, _data: ScheduleData) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn remove_schedule(&mut self, _schedule_id: ScheduleId) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn get_schedules(&self) -> Result<Vec<Schedule>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn add_day_off(&mut self, _day_off: DayOffData) -> Result<DayOffId, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn remove_day_off(&mut self, _day_off_id: DayOffId) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn get_days_off(&self, _range: DateRange) -> Result<Vec<DayOff>, DataStorageError> {
+        Ok(Default::default())
     }
 
     fn dyn_clone(&self) -> Box<dyn PlannedHoursStorage + Send> {
-        todo!()
+        Box::new(NullService)
     }
 }
 
 impl TimeStorage for NullService {
-    fn add_entry(&mut self, entry: TimeEntryData) -> Result<TimeEntryId, DataStorageError> {
-        todo!()
+    fn add_entry(&mut self, _entry: TimeEntryData) -> Result<TimeEntryId, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn remove_entry(&mut self, _entry_id: TimeEntryId) -> Result<TimeEntryData, DataStorageError> {
+        Err(DataStorageError::NotFound)
+    }
+
+    fn restore_entry(&mut self, _entry: crate::model::time_entry::TimeEntry) -> Result<(), DataStorageError> {
+        Err(DataStorageError::NotFound)
+    }
+
+    fn get_deleted(&self) -> Result<Vec<(crate::model::time_entry::TimeEntry, chrono::NaiveDateTime)>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn purge_entry(&mut self, _entry_id: TimeEntryId) -> Result<(), DataStorageError> {
+        Err(DataStorageError::NotFound)
     }
 
-    fn remove_entry(&mut self, entry_id: TimeEntryId) -> Result<(), DataStorageError> {
-        todo!()
+    fn purge_deleted_before(&mut self, _before: chrono::NaiveDateTime) -> Result<Vec<TimeEntryId>, DataStorageError> {
+        Ok(Default::default())
     }
 
-    fn update_entry(&mut self, entry_id: TimeEntryId, data: TimeEntryData) -> Result<(), DataStorageError> {
-        todo!()
+    fn get_changes(&self, _entry_id: TimeEntryId) -> Result<Vec<crate::audit::Change>, DataStorageError> {
+        Ok(Default::default())
     }
 
-    fn get_in_range(&self, range: DateRange) -> Result<Vec<TimeEntry>, DataStorageError> {
-        todo!()
+    fn get_change_log(&self) -> Result<Vec<crate::audit::Change>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn get_overlapping(&self, _data: &TimeEntryData, _ignore: Option<TimeEntryId>) -> Result<Vec<crate::model::time_entry::TimeEntry>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn update_entry(&mut self, _entry_id: TimeEntryId, _data: TimeEntryData) -> Result<TimeEntryData, DataStorageError> {
+        Err(DataStorageError::NotFound)
+    }
+
+    fn get_daily_totals(&self, _range: DateRange) -> Result<std::collections::BTreeMap<chrono::NaiveDate, chrono::TimeDelta>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn get_in_range(&self, _range: DateRange) -> Result<Vec<TimeEntry>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn get_in_range_for_project(&self, _range: DateRange, _project_id: ProjectId) -> Result<Vec<TimeEntry>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn add_project(&mut self, _project: ProjectData) -> Result<ProjectId, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn update_project(&mut self, _project_id: ProjectId, _data: ProjectData) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn remove_project(&mut self, _project_id: ProjectId) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn get_projects(&self) -> Result<Vec<Project>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn add_client(&mut self, _client: ClientData) -> Result<ClientId, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn update_client(&mut self, _client_id: ClientId, _data: ClientData) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn remove_client(&mut self, _client_id: ClientId) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn get_clients(&self) -> Result<Vec<Client>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn start_timer(&mut self, _entry: OpenTimeEntry) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn get_running_timer(&self) -> Result<Option<OpenTimeEntry>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn discard_timer(&mut self) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn stop_timer(&mut self, _end: chrono::NaiveDateTime) -> Result<TimeEntryId, DataStorageError> {
+        Err(DataStorageError::NotFound)
     }
 
     fn dyn_clone(&self) -> Box<dyn TimeStorage + Send> {
-        todo!()
+        Box::new(NullService)
+    }
+}
+
+impl UserDataStorage for NullService {
+    fn add_user(&mut self, _data: UserData) -> Result<UserId, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn update_user(&mut self, _user_id: UserId, _data: UserData) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn remove_user(&mut self, _user_id: UserId) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn get_users(&self) -> Result<Vec<UserProfile>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn get_data_file(&self, _user_id: UserId) -> Result<Option<String>, DataStorageError> {
+        Err(DataStorageError::NotFound)
+    }
+
+    fn dyn_clone(&self) -> Box<dyn UserDataStorage + Send> {
+        Box::new(NullService)
+    }
+}
+
+impl AbsenceStorage for NullService {
+    fn add_absence(&mut self, _absence: AbsenceData) -> Result<AbsenceId, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn remove_absence(&mut self, _absence_id: AbsenceId) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn get_absences(&self, _range: DateRange) -> Result<Vec<Absence>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn set_vacation_entitlement(&mut self, _year: i32, _days: f64) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn get_vacation_entitlement(&self, _year: i32) -> Result<Option<f64>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn dyn_clone(&self) -> Box<dyn AbsenceStorage + Send> {
+        Box::new(NullService)
+    }
+}
+
+impl BalanceStorage for NullService {
+    fn add_adjustment(&mut self, _adjustment: AdjustmentData) -> Result<AdjustmentId, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn remove_adjustment(&mut self, _adjustment_id: AdjustmentId) -> Result<(), DataStorageError> {
+        Ok(())
+    }
+
+    fn get_adjustments(&self, _range: DateRange) -> Result<Vec<Adjustment>, DataStorageError> {
+        Ok(Default::default())
+    }
+
+    fn dyn_clone(&self) -> Box<dyn BalanceStorage + Send> {
+        Box::new(NullService)
     }
 }
@@ -1,13 +1,26 @@
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::{BTreeMap, HashMap}, path::PathBuf};
 
-use chrono::{Local, NaiveDate, TimeDelta};
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeDelta};
 use fallible_iterator::FallibleIterator;
 use log::debug;
-use rusqlite::{Connection, Statement, ToSql};
+use rusqlite::{types::Type, Connection, OptionalExtension, Row, ToSql};
 
-use crate::{model::date_range::DateRange, storage::migrate::migrate_db};
+use crate::{
+    absence::{Absence, AbsenceData, AbsenceId, AbsenceKind},
+    audit::{compute_hash, encode, Change, ChangeKind, GENESIS_HASH},
+    balance::{Adjustment, AdjustmentData, AdjustmentId, AdjustmentKind},
+    holidays::{holidays_in, DayOff, DayOffData, DayOffId, State},
+    model::{
+        date_range::DateRange,
+        project::{color_from_rgb, color_to_rgb, Client, ClientData, ClientId, Project, ProjectData, ProjectId},
+        schedule::{schedule_at, Schedule, ScheduleData, ScheduleId},
+        time_entry::{OpenTimeEntry, TimeEntryId},
+    },
+    storage::migrate::migrate_db,
+    user::{UserData, UserId, UserProfile},
+};
 
-use super::{DataStorageError, PlannedHoursStorage, TimeEntry, TimeEntryData, TimeStorage};
+use super::{AbsenceStorage, BalanceStorage, DataStorageError, PlannedHoursStorage, TimeEntry, TimeEntryData, TimeStorage, UserDataStorage};
 
 impl From<rusqlite::Error> for DataStorageError {
     fn from(value: rusqlite::Error) -> Self {
@@ -16,74 +29,431 @@ impl From<rusqlite::Error> for DataStorageError {
 }
 
 pub struct SqliteStorage {
-    root_dir: PathBuf,
+    path: PathBuf,
     connection: rusqlite::Connection,
 }
 
 impl SqliteStorage {
+    /// The main database in the data directory, it holds the profiles.
     pub fn new(root_dir: PathBuf) -> Result<Self, DataStorageError> {
         debug!("Create sqlite storage");
-        let path = std::path::absolute(root_dir.clone().join("db.sqlite")).expect("get path");
+        Self::open(std::path::absolute(root_dir.join("db.sqlite")).expect("get path"))
+    }
+
+    /// Opens or creates the database file and brings its schema up to date.
+    pub fn open(path: PathBuf) -> Result<Self, DataStorageError> {
         debug!("Path: {:?}", path);
-        let mut connection = Connection::open(path)?;
-        migrate_db(&mut connection);
+        let mut connection = Connection::open(&path)?;
+        migrate_db(&mut connection)?;
 
         Ok(Self {
-            root_dir,
+            path,
             connection,
         })
     }
+
+    /// A database that lives as long as the storage, clones open a new empty one.
+    #[cfg(test)]
+    pub fn in_memory() -> Result<Self, DataStorageError> {
+        Self::open(PathBuf::from(":memory:"))
+    }
+
+    fn get_override(&self, date: NaiveDate) -> Result<Option<TimeDelta>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached("Select CAST(hours AS INTEGER) from planned_hours where date = ?1")?;
+        let seconds: Option<i64> = statement.query_row([date], |r| r.get(0)).optional()?;
+        Ok(seconds.map(TimeDelta::seconds))
+    }
+}
+
+/// An entry that is in the trash or not, depending on `in_trash`.
+fn entry_by_id(connection: &Connection, entry_id: TimeEntryId, in_trash: bool) -> Result<TimeEntryData, DataStorageError> {
+    let sql = match in_trash {
+        false => "SELECT id, start, end, date, remark, project_id, end_date from times where id = ?1 and deleted_at is null",
+        true => "SELECT id, start, end, date, remark, project_id, end_date from times where id = ?1 and deleted_at is not null",
+    };
+    let mut statement = connection.prepare_cached(sql)?;
+    let entry = statement.query_row([entry_id], map_time_entry).optional()?;
+    entry.map(|e| e.1).ok_or(DataStorageError::NotFound)
+}
+
+/// Appends a record to the change log, chained to the last one. Runs in the transaction of the change.
+fn record_change(
+    connection: &Connection,
+    entry_id: TimeEntryId,
+    kind: ChangeKind,
+    old: Option<&TimeEntryData>,
+    new: Option<&TimeEntryData>,
+) -> Result<(), DataStorageError> {
+    let previous_hash: String = connection
+        .prepare_cached("SELECT hash from entry_changes order by id desc limit 1")?
+        .query_row([], |r| r.get(0))
+        .optional()?
+        .unwrap_or_else(|| GENESIS_HASH.to_owned());
+    let changed_at = Local::now().naive_local();
+    let (old, new) = (encode(old), encode(new));
+    let hash = compute_hash(&previous_hash, entry_id, changed_at, kind, old.as_deref(), new.as_deref());
+    let mut statement = connection.prepare_cached(
+        "insert into entry_changes (entry_id, changed_at, action, old_value, new_value, previous_hash, hash) values (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    statement.execute((entry_id, changed_at, kind.as_str(), old, new, previous_hash, hash))?;
+    Ok(())
+}
+
+fn map_change(r: &Row) -> Result<Change, rusqlite::Error> {
+    let action: String = r.get(3)?;
+    Ok(Change {
+        id: r.get(0)?,
+        entry_id: r.get(1)?,
+        changed_at: r.get(2)?,
+        kind: ChangeKind::parse(&action).ok_or(rusqlite::Error::InvalidColumnType(3, action, Type::Text))?,
+        old_value: r.get(4)?,
+        new_value: r.get(5)?,
+        previous_hash: r.get(6)?,
+        hash: r.get(7)?,
+    })
+}
+
+fn map_time_entry(e: &Row) -> Result<TimeEntry, rusqlite::Error> {
+    Ok((
+        e.get(0)?,
+        TimeEntryData {
+            end: e.get(2)?,
+            start: e.get(1)?,
+            date: e.get(3)?,
+            remark: e.get(4)?,
+            project_id: e.get(5)?,
+            end_date: e.get(6)?,
+        },
+    ))
 }
 
 impl Clone for SqliteStorage {
     fn clone(&self) -> Self {
-        Self::new(self.root_dir.clone()).unwrap()
+        Self::open(self.path.clone()).unwrap()
     }
 }
 
 impl TimeStorage for SqliteStorage {
     fn add_entry(&mut self, entry: TimeEntryData) -> Result<super::TimeEntryId, DataStorageError> {
         debug!("Inserting: {:?}", entry);
-        let mut statment = self
-            .connection
-            .prepare("insert into times (start, end, date, remark) values (?1, ?2, ?3, ?4)")?;
-        let res = statment.insert((
-            &entry.start.to_sql().unwrap(),
-            &entry.end.to_sql().unwrap(),
-            &entry.date.to_sql().unwrap(),
-            &entry.remark,
-        ))?;
+        let tx = self.connection.transaction()?;
+        let res = tx
+            .prepare("insert into times (start, end, date, remark, project_id, end_date) values (?1, ?2, ?3, ?4, ?5, ?6)")?
+            .insert((
+                &entry.start.to_sql().unwrap(),
+                &entry.end.to_sql().unwrap(),
+                &entry.date.to_sql().unwrap(),
+                &entry.remark,
+                &entry.project_id,
+                &entry.end_date.to_sql().unwrap(),
+            ))?;
+        record_change(&tx, res, ChangeKind::Added, None, Some(&entry))?;
+        tx.commit()?;
 
         Ok(res)
     }
 
-    fn remove_entry(&mut self, entry_id: super::TimeEntryId) -> Result<(), DataStorageError> {
+    fn remove_entry(&mut self, entry_id: super::TimeEntryId) -> Result<TimeEntryData, DataStorageError> {
         debug!("Deleting entry: {}", entry_id);
-        let mut statement = self.connection.prepare_cached("Delete from times where id = ?1")?;
-        let _res = statement.execute([entry_id])?;
+        let tx = self.connection.transaction()?;
+        let removed = entry_by_id(&tx, entry_id, false)?;
+        tx.execute("UPDATE times set deleted_at = ?2 where id = ?1", (entry_id, Local::now().naive_local()))?;
+        record_change(&tx, entry_id, ChangeKind::Removed, Some(&removed), None)?;
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    fn restore_entry(&mut self, entry: TimeEntry) -> Result<(), DataStorageError> {
+        debug!("Restoring entry: {:?}", entry);
+        let (id, data) = entry;
+        let params = (
+            id,
+            data.start.to_sql()?,
+            data.end.to_sql()?,
+            data.date.to_sql()?,
+            &data.remark,
+            data.project_id,
+            data.end_date.to_sql()?,
+        );
+        let tx = self.connection.transaction()?;
+        let restored = tx.execute(
+            "UPDATE times set start = ?2, end = ?3, date = ?4, remark = ?5, project_id = ?6, end_date = ?7, deleted_at = NULL
+                where id = ?1 and deleted_at is not null",
+            params,
+        )?;
+        // purged entries stay deleted
+        if restored == 0 {
+            return Err(DataStorageError::NotFound);
+        }
+        record_change(&tx, id, ChangeKind::Restored, None, Some(&data))?;
+        tx.commit()?;
         Ok(())
     }
 
     fn get_in_range(&self, range: DateRange) -> Result<Vec<TimeEntry>, DataStorageError> {
         debug!("query data: {:?}", range);
-        let mut statement = self.connection.prepare_cached("SELECT id, start, end, date, remark from times where date(date) >= ?1
+        let mut statement = self.connection.prepare_cached("SELECT id, start, end, date, remark, project_id, end_date from times where deleted_at is null and date(end_date) >= ?1
                                                                                      and date(date) <= ?2 order by date asc, id asc")?;
         let res = statement.query((range.0.to_sql()?, range.1.to_sql()?))?;
 
-        let mapped = res.map(|e| {
+        let mapped = res.map(map_time_entry);
+        Ok(mapped.collect()?)
+    }
+
+    fn get_in_range_for_project(&self, range: DateRange, project_id: ProjectId) -> Result<Vec<TimeEntry>, DataStorageError> {
+        debug!("query data: {:?} for project {}", range, project_id);
+        let mut statement = self.connection.prepare_cached("SELECT id, start, end, date, remark, project_id, end_date from times where deleted_at is null and date(end_date) >= ?1
+                                                                                     and date(date) <= ?2 and project_id = ?3 order by date asc, id asc")?;
+        let res = statement.query((range.0.to_sql()?, range.1.to_sql()?, project_id))?;
+
+        let mapped = res.map(map_time_entry);
+        Ok(mapped.collect()?)
+    }
+
+    fn get_overlapping(&self, data: &TimeEntryData, ignore: Option<TimeEntryId>) -> Result<Vec<TimeEntry>, DataStorageError> {
+        debug!("query entries overlapping {:?}", data);
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, start, end, date, remark, project_id, end_date from times where deleted_at is null
+                and julianday(date || ' ' || start) < julianday(?2) and julianday(end_date || ' ' || end) > julianday(?1)
+                and (?3 is null or id != ?3) order by date asc, start asc",
+        )?;
+        let res = statement.query((data.start_datetime(), data.end_datetime(), ignore))?;
+        Ok(res.map(map_time_entry).collect()?)
+    }
+
+    fn get_deleted(&self) -> Result<Vec<(TimeEntry, NaiveDateTime)>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, start, end, date, remark, project_id, end_date, deleted_at from times where deleted_at is not null order by deleted_at desc",
+        )?;
+        let res = statement.query([])?;
+        Ok(res.map(|r| Ok((map_time_entry(r)?, r.get(7)?))).collect()?)
+    }
+
+    fn purge_entry(&mut self, entry_id: TimeEntryId) -> Result<(), DataStorageError> {
+        debug!("Purging entry: {}", entry_id);
+        let tx = self.connection.transaction()?;
+        let purged = entry_by_id(&tx, entry_id, true)?;
+        tx.execute("DELETE from times where id = ?1", [entry_id])?;
+        record_change(&tx, entry_id, ChangeKind::Purged, Some(&purged), None)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn purge_deleted_before(&mut self, before: NaiveDateTime) -> Result<Vec<TimeEntryId>, DataStorageError> {
+        debug!("Purging entries deleted before {before}");
+        let tx = self.connection.transaction()?;
+        let purged: Vec<TimeEntry> = tx
+            .prepare("SELECT id, start, end, date, remark, project_id, end_date from times where deleted_at < ?1")?
+            .query([before])?
+            .map(map_time_entry)
+            .collect()?;
+        for (id, data) in &purged {
+            tx.execute("DELETE from times where id = ?1", [id])?;
+            record_change(&tx, *id, ChangeKind::Purged, Some(data), None)?;
+        }
+        tx.commit()?;
+        Ok(purged.into_iter().map(|e| e.0).collect())
+    }
+
+    fn get_changes(&self, entry_id: TimeEntryId) -> Result<Vec<Change>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, entry_id, changed_at, action, old_value, new_value, previous_hash, hash from entry_changes where entry_id = ?1 order by id",
+        )?;
+        let res = statement.query([entry_id])?;
+        Ok(res.map(map_change).collect()?)
+    }
+
+    fn get_change_log(&self) -> Result<Vec<Change>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, entry_id, changed_at, action, old_value, new_value, previous_hash, hash from entry_changes order by id",
+        )?;
+        let res = statement.query([])?;
+        Ok(res.map(map_change).collect()?)
+    }
+
+    fn get_daily_totals(&self, range: DateRange) -> Result<BTreeMap<NaiveDate, TimeDelta>, DataStorageError> {
+        debug!("query daily totals: {:?}", range);
+        // every entry is expanded into one part per calendar day it touches
+        let mut statement = self.connection.prepare_cached(
+            "WITH RECURSIVE parts(day, end_date, part_start, entry_end) AS (
+                SELECT date(date), date(end_date), julianday(date || ' ' || start), julianday(end_date || ' ' || end)
+                FROM times WHERE deleted_at is null and date(end_date) >= ?1 and date(date) <= ?2
+                UNION ALL
+                SELECT date(day, '+1 day'), end_date, julianday(day, '+1 day'), entry_end
+                FROM parts WHERE day < end_date
+            )
+            SELECT day, CAST(round(SUM(min(entry_end, julianday(day, '+1 day')) - part_start) * 86400) AS INTEGER)
+            FROM parts WHERE day >= ?1 and day <= ?2 GROUP BY day ORDER BY day",
+        )?;
+        let res = statement.query((range.0.to_sql()?, range.1.to_sql()?))?;
+        let mapped = res.map(|row| Ok((row.get(0)?, TimeDelta::seconds(row.get(1)?))));
+        Ok(mapped.collect()?)
+    }
+
+    fn add_project(&mut self, project: ProjectData) -> Result<ProjectId, DataStorageError> {
+        debug!("Inserting project: {:?}", project);
+        let mut statement = self
+            .connection
+            .prepare_cached("insert into projects (client_id, name, color, archived) values (?1, ?2, ?3, ?4)")?;
+        let res = statement.insert((
+            project.client_id,
+            project.name,
+            color_to_rgb(project.color),
+            project.archived,
+        ))?;
+        Ok(res)
+    }
+
+    fn update_project(&mut self, project_id: ProjectId, data: ProjectData) -> Result<(), DataStorageError> {
+        debug!("update project: {project_id}");
+        let mut statement = self.connection.prepare_cached(
+            "UPDATE projects set client_id = ?1, name = ?2, color = ?3, archived = ?4 where id = ?5",
+        )?;
+        statement.execute((
+            data.client_id,
+            data.name,
+            color_to_rgb(data.color),
+            data.archived,
+            project_id,
+        ))?;
+        Ok(())
+    }
+
+    fn remove_project(&mut self, project_id: ProjectId) -> Result<(), DataStorageError> {
+        debug!("Deleting project: {}", project_id);
+        let tx = self.connection.transaction()?;
+        // entries in the trash lose the project as well
+        let affected: Vec<TimeEntry> = tx
+            .prepare("SELECT id, start, end, date, remark, project_id, end_date from times where project_id = ?1")?
+            .query([project_id])?
+            .map(map_time_entry)
+            .collect()?;
+        tx.execute("UPDATE times set project_id = NULL where project_id = ?1", [project_id])?;
+        for (id, old) in &affected {
+            let new = TimeEntryData {
+                project_id: None,
+                ..old.clone()
+            };
+            record_change(&tx, *id, ChangeKind::Updated, Some(old), Some(&new))?;
+        }
+        tx.execute("DELETE from projects where id = ?1", [project_id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_projects(&self) -> Result<Vec<Project>, DataStorageError> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT id, client_id, name, color, archived from projects order by name asc")?;
+        let res = statement.query(())?;
+        let mapped = res.map(|r| {
+            Ok((
+                r.get(0)?,
+                ProjectData {
+                    client_id: r.get(1)?,
+                    name: r.get(2)?,
+                    color: color_from_rgb(r.get(3)?),
+                    archived: r.get(4)?,
+                },
+            ))
+        });
+        Ok(mapped.collect()?)
+    }
+
+    fn add_client(&mut self, client: ClientData) -> Result<ClientId, DataStorageError> {
+        debug!("Inserting client: {:?}", client);
+        let mut statement = self
+            .connection
+            .prepare_cached("insert into clients (name, color, archived) values (?1, ?2, ?3)")?;
+        let res = statement.insert((client.name, color_to_rgb(client.color), client.archived))?;
+        Ok(res)
+    }
+
+    fn update_client(&mut self, client_id: ClientId, data: ClientData) -> Result<(), DataStorageError> {
+        debug!("update client: {client_id}");
+        let mut statement = self
+            .connection
+            .prepare_cached("UPDATE clients set name = ?1, color = ?2, archived = ?3 where id = ?4")?;
+        statement.execute((data.name, color_to_rgb(data.color), data.archived, client_id))?;
+        Ok(())
+    }
+
+    fn remove_client(&mut self, client_id: ClientId) -> Result<(), DataStorageError> {
+        debug!("Deleting client: {}", client_id);
+        let tx = self.connection.transaction()?;
+        tx.execute("UPDATE projects set client_id = NULL where client_id = ?1", [client_id])?;
+        tx.execute("DELETE from clients where id = ?1", [client_id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_clients(&self) -> Result<Vec<Client>, DataStorageError> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT id, name, color, archived from clients order by name asc")?;
+        let res = statement.query(())?;
+        let mapped = res.map(|r| {
             Ok((
-                e.get(0)?,
-                TimeEntryData {
-                    end: e.get(2)?,
-                    start: e.get(1)?,
-                    date: e.get(3)?,
-                    remark: e.get(4)?,
+                r.get(0)?,
+                ClientData {
+                    name: r.get(1)?,
+                    color: color_from_rgb(r.get(2)?),
+                    archived: r.get(3)?,
                 },
             ))
         });
         Ok(mapped.collect()?)
     }
 
+    fn start_timer(&mut self, entry: OpenTimeEntry) -> Result<(), DataStorageError> {
+        debug!("Starting timer: {:?}", entry);
+        let mut statement = self.connection.prepare_cached(
+            "insert into running_timer (id, start, remark, project_id) values (1, ?1, ?2, ?3)",
+        )?;
+        statement.execute((entry.start, entry.remark, entry.project_id))?;
+        Ok(())
+    }
+
+    fn get_running_timer(&self) -> Result<Option<OpenTimeEntry>, DataStorageError> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT start, remark, project_id from running_timer where id = 1")?;
+        let res = statement
+            .query_row((), |r| {
+                Ok(OpenTimeEntry {
+                    start: r.get(0)?,
+                    remark: r.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    project_id: r.get(2)?,
+                })
+            })
+            .optional()?;
+        Ok(res)
+    }
+
+    fn discard_timer(&mut self) -> Result<(), DataStorageError> {
+        debug!("Discarding timer");
+        self.connection.execute("DELETE from running_timer where id = 1", ())?;
+        Ok(())
+    }
+
+    fn stop_timer(&mut self, end: NaiveDateTime) -> Result<TimeEntryId, DataStorageError> {
+        debug!("Stopping timer at {end}");
+        let Some(open) = self.get_running_timer()? else {
+            return Err(DataStorageError::NotFound);
+        };
+        let entry = open.close(end)?;
+        let tx = self.connection.transaction()?;
+        tx.execute(
+            "insert into times (start, end, date, remark, project_id, end_date) values (?1, ?2, ?3, ?4, ?5, ?6)",
+            (entry.start, entry.end, entry.date, &entry.remark, entry.project_id, entry.end_date),
+        )?;
+        let id = tx.last_insert_rowid();
+        record_change(&tx, id, ChangeKind::Added, None, Some(&entry))?;
+        tx.execute("DELETE from running_timer where id = 1", ())?;
+        tx.commit()?;
+        Ok(id)
+    }
+
     fn dyn_clone(&self) -> Box<dyn TimeStorage + Send> {
         Box::new(self.clone())
     }
@@ -92,51 +462,510 @@ impl TimeStorage for SqliteStorage {
         &mut self,
         entry_id: super::TimeEntryId,
         data: TimeEntryData,
-    ) -> Result<(), DataStorageError> {
+    ) -> Result<TimeEntryData, DataStorageError> {
         debug!("update entry: {entry_id}");
-
-        let mut statement = self.connection.prepare_cached(
-            "UPDATE times set start = ?1, end = ?2, date = ?3, remark = ?4 where id = ?5",
+        let tx = self.connection.transaction()?;
+        let previous = entry_by_id(&tx, entry_id, false)?;
+        tx.execute(
+            "UPDATE times set start = ?1, end = ?2, date = ?3, remark = ?4, project_id = ?5, end_date = ?6 where id = ?7",
+            (
+                data.start.to_sql()?,
+                data.end.to_sql()?,
+                data.date.to_sql()?,
+                &data.remark,
+                data.project_id,
+                data.end_date.to_sql()?,
+                entry_id,
+            ),
         )?;
-        statement.execute((
-            data.start.to_sql()?,
-            data.end.to_sql()?,
-            data.date.to_sql()?,
-            data.remark,
-            entry_id,
-        ))?;
+        record_change(&tx, entry_id, ChangeKind::Updated, Some(&previous), Some(&data))?;
+        tx.commit()?;
 
-        Ok(())
+        Ok(previous)
+    }
+}
+
+fn map_schedule(r: &Row) -> Result<Schedule, rusqlite::Error> {
+    let mut hours = [TimeDelta::zero(); 7];
+    for (i, h) in hours.iter_mut().enumerate() {
+        *h = TimeDelta::seconds(r.get(i + 2)?);
     }
+    Ok((r.get(0)?, ScheduleData::new(r.get(1)?, hours)))
 }
 
 impl PlannedHoursStorage for SqliteStorage {
-    fn set(&mut self, date: chrono::NaiveDate, duration: chrono::TimeDelta) -> Result<(), DataStorageError> {
+    fn set(&mut self, date: chrono::NaiveDate, duration: chrono::TimeDelta) -> Result<Option<TimeDelta>, DataStorageError> {
+        let previous = self.get_override(date)?;
         let mut statement = self.connection.prepare_cached("Insert or replace into planned_hours (date, hours) values (?1, ?2)")?;
         statement.execute((date, duration.num_seconds()))?;
-        Ok(())
+        Ok(previous)
     }
 
-    fn get(&self, date: chrono::NaiveDate) -> Result<chrono::TimeDelta, DataStorageError> {
-        let mut statement  = self.connection.prepare_cached("Select hours from planned_hours where date = ?1")?;
-        let res = statement.query_row([date], |r| {
-            let seconds: i64 = r.get(0)?;
-            Ok(TimeDelta::seconds(seconds))
-        })?;
-        Ok(res)
+    fn reset(&mut self, date: NaiveDate) -> Result<Option<TimeDelta>, DataStorageError> {
+        let previous = self.get_override(date)?;
+        let mut statement = self.connection.prepare_cached("DELETE from planned_hours where date = ?1")?;
+        statement.execute([date])?;
+        Ok(previous)
     }
 
-    fn get_range(&self, range: DateRange) -> Result<HashMap<NaiveDate, chrono::TimeDelta>, DataStorageError> {
-        let mut statement = self.connection.prepare_cached("Seelct date, hours from planned_hours where date >= ?1 and date <= ?2")?;
-        let res = statement.query((range.0, range.1))?;
-        let res = res.map(|r| {
+    fn get(&self, date: chrono::NaiveDate, state: Option<State>) -> Result<chrono::TimeDelta, DataStorageError> {
+        let res = self.get_range((date, date), state)?;
+        Ok(res.get(&date).copied().unwrap_or(TimeDelta::zero()))
+    }
+
+    fn get_range(&self, range: DateRange, state: Option<State>) -> Result<HashMap<NaiveDate, chrono::TimeDelta>, DataStorageError> {
+        let schedules = self.get_schedules()?;
+        let mut res: HashMap<NaiveDate, TimeDelta> = range
+            .0
+            .iter_days()
+            .take_while(|d| *d <= range.1)
+            .filter_map(|d| schedule_at(&schedules, d).map(|s| (d, s.hours_on(d))))
+            .collect();
+
+        let mut statement = self.connection.prepare_cached("Select date, CAST(hours AS INTEGER) from planned_hours where date >= ?1 and date <= ?2")?;
+        let overrides = statement.query((range.0, range.1))?;
+        let overrides = overrides.map(|r| {
             let seconds = r.get(1)?;
             Ok((r.get(0)?, TimeDelta::seconds(seconds)))
         });
-        Ok(res.collect()?)
+        res.extend(overrides.collect::<Vec<(NaiveDate, TimeDelta)>>()?);
+        for date in holidays_in(range, state, &self.get_days_off(range)?).into_keys() {
+            res.insert(date, TimeDelta::zero());
+        }
+        Ok(res)
+    }
+
+    fn add_schedule(&mut self, schedule: ScheduleData) -> Result<ScheduleId, DataStorageError> {
+        debug!("Inserting schedule: {:?}", schedule);
+        let mut statement = self.connection.prepare_cached(
+            "insert into schedules (valid_from, monday, tuesday, wednesday, thursday, friday, saturday, sunday) values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        let h = schedule.hours.map(|h| h.num_seconds());
+        let id = statement.insert((schedule.valid_from, h[0], h[1], h[2], h[3], h[4], h[5], h[6]))?;
+        Ok(id)
+    }
+
+    fn update_schedule(&mut self, schedule_id: ScheduleId, data: ScheduleData) -> Result<(), DataStorageError> {
+        debug!("Updating schedule: {schedule_id}");
+        let mut statement = self.connection.prepare_cached(
+            "UPDATE schedules set valid_from = ?1, monday = ?2, tuesday = ?3, wednesday = ?4, thursday = ?5, friday = ?6, saturday = ?7, sunday = ?8 where id = ?9",
+        )?;
+        let h = data.hours.map(|h| h.num_seconds());
+        statement.execute((data.valid_from, h[0], h[1], h[2], h[3], h[4], h[5], h[6], schedule_id))?;
+        Ok(())
+    }
+
+    fn remove_schedule(&mut self, schedule_id: ScheduleId) -> Result<(), DataStorageError> {
+        debug!("Deleting schedule: {schedule_id}");
+        let mut statement = self.connection.prepare_cached("DELETE from schedules where id = ?1")?;
+        statement.execute([schedule_id])?;
+        Ok(())
+    }
+
+    fn get_schedules(&self) -> Result<Vec<Schedule>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, valid_from, monday, tuesday, wednesday, thursday, friday, saturday, sunday from schedules order by valid_from asc",
+        )?;
+        let res = statement.query(())?;
+        Ok(res.map(map_schedule).collect()?)
+    }
+
+    fn add_day_off(&mut self, day_off: DayOffData) -> Result<DayOffId, DataStorageError> {
+        debug!("Inserting day off: {:?}", day_off);
+        let mut statement = self.connection.prepare_cached("insert into days_off (date, name) values (?1, ?2)")?;
+        let id = statement.insert((day_off.date, day_off.name))?;
+        Ok(id)
+    }
+
+    fn remove_day_off(&mut self, day_off_id: DayOffId) -> Result<(), DataStorageError> {
+        debug!("Deleting day off: {day_off_id}");
+        let mut statement = self.connection.prepare_cached("DELETE from days_off where id = ?1")?;
+        statement.execute([day_off_id])?;
+        Ok(())
+    }
+
+    fn get_days_off(&self, range: DateRange) -> Result<Vec<DayOff>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, date, name from days_off where date >= ?1 and date <= ?2 order by date asc",
+        )?;
+        let res = statement.query((range.0, range.1))?;
+        let mapped = res.map(|r| Ok((r.get(0)?, DayOffData::new(r.get(1)?, r.get(2)?))));
+        Ok(mapped.collect()?)
     }
 
     fn dyn_clone(&self) -> Box<dyn PlannedHoursStorage + Send> {
         Box::new(self.clone())
     }
 }
+
+impl UserDataStorage for SqliteStorage {
+    fn add_user(&mut self, data: UserData) -> Result<UserId, DataStorageError> {
+        debug!("Inserting user: {:?}", data.name);
+        // the first profile keeps the data of the main database, later ones get their own file
+        let mut statement = self.connection.prepare_cached(
+            "insert into user_data (name, street, citycode, city, employee_number, employer, email, data_file)
+             values (?1, ?2, ?3, ?4, ?5, ?6, ?7, CASE WHEN EXISTS (SELECT 1 FROM user_data)
+                THEN 'profile-' || lower(hex(randomblob(8))) || '.sqlite' END)",
+        )?;
+        let id = statement.insert((
+            data.name,
+            data.street,
+            data.citycode,
+            data.city,
+            data.employee_number,
+            data.employer,
+            data.email,
+        ))?;
+        Ok(id)
+    }
+
+    fn update_user(&mut self, user_id: UserId, data: UserData) -> Result<(), DataStorageError> {
+        debug!("Updating user: {}", user_id);
+        let mut statement = self.connection.prepare_cached(
+            "UPDATE user_data set name = ?1, street = ?2, citycode = ?3, city = ?4, employee_number = ?5, employer = ?6, email = ?7 where id = ?8",
+        )?;
+        statement.execute((
+            data.name,
+            data.street,
+            data.citycode,
+            data.city,
+            data.employee_number,
+            data.employer,
+            data.email,
+            user_id,
+        ))?;
+        Ok(())
+    }
+
+    fn remove_user(&mut self, user_id: UserId) -> Result<(), DataStorageError> {
+        debug!("Deleting user: {}", user_id);
+        let mut statement = self.connection.prepare_cached("DELETE from user_data where id = ?1")?;
+        statement.execute([user_id])?;
+        Ok(())
+    }
+
+    fn get_data_file(&self, user_id: UserId) -> Result<Option<String>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached("SELECT data_file from user_data where id = ?1")?;
+        statement
+            .query_row([user_id], |r| r.get(0))
+            .optional()?
+            .ok_or(DataStorageError::NotFound)
+    }
+
+    fn get_users(&self) -> Result<Vec<UserProfile>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, name, street, citycode, city, employee_number, employer, email from user_data order by name asc",
+        )?;
+        let res = statement.query(())?;
+        let text = |r: &Row, i: usize| -> Result<String, rusqlite::Error> {
+            Ok(r.get::<_, Option<String>>(i)?.unwrap_or_default())
+        };
+        let mapped = res.map(|r| {
+            Ok((
+                r.get(0)?,
+                UserData {
+                    name: r.get(1)?,
+                    street: text(r, 2)?,
+                    citycode: text(r, 3)?,
+                    city: text(r, 4)?,
+                    employee_number: text(r, 5)?,
+                    employer: text(r, 6)?,
+                    email: text(r, 7)?,
+                },
+            ))
+        });
+        Ok(mapped.collect()?)
+    }
+
+    fn dyn_clone(&self) -> Box<dyn UserDataStorage + Send> {
+        Box::new(self.clone())
+    }
+}
+
+fn map_absence(r: &Row) -> Result<Absence, rusqlite::Error> {
+    let kind: String = r.get(1)?;
+    let kind = AbsenceKind::from_key(&kind).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, format!("unknown absence kind {kind}").into())
+    })?;
+    Ok((
+        r.get(0)?,
+        AbsenceData {
+            kind,
+            start: r.get(2)?,
+            end: r.get(3)?,
+            half_day: r.get(4)?,
+            remark: r.get::<_, Option<String>>(5)?.unwrap_or_default(),
+        },
+    ))
+}
+
+impl AbsenceStorage for SqliteStorage {
+    fn add_absence(&mut self, absence: AbsenceData) -> Result<AbsenceId, DataStorageError> {
+        debug!("Inserting absence: {:?}", absence);
+        let mut statement = self.connection.prepare_cached(
+            "insert into absences (kind, start, end, half_day, remark) values (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let id = statement.insert((absence.kind.key(), absence.start, absence.end, absence.half_day, absence.remark))?;
+        Ok(id)
+    }
+
+    fn remove_absence(&mut self, absence_id: AbsenceId) -> Result<(), DataStorageError> {
+        debug!("Deleting absence: {absence_id}");
+        let mut statement = self.connection.prepare_cached("DELETE from absences where id = ?1")?;
+        statement.execute([absence_id])?;
+        Ok(())
+    }
+
+    fn get_absences(&self, range: DateRange) -> Result<Vec<Absence>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, kind, start, end, half_day, remark from absences where start <= ?2 and end >= ?1 order by start asc",
+        )?;
+        let res = statement.query((range.0, range.1))?;
+        Ok(res.map(map_absence).collect()?)
+    }
+
+    fn set_vacation_entitlement(&mut self, year: i32, days: f64) -> Result<(), DataStorageError> {
+        let mut statement = self
+            .connection
+            .prepare_cached("Insert or replace into vacation_entitlements (year, days) values (?1, ?2)")?;
+        statement.execute((year, days))?;
+        Ok(())
+    }
+
+    fn get_vacation_entitlement(&self, year: i32) -> Result<Option<f64>, DataStorageError> {
+        let mut statement = self
+            .connection
+            .prepare_cached("Select days from vacation_entitlements where year = ?1")?;
+        Ok(statement.query_row([year], |r| r.get(0)).optional()?)
+    }
+
+    fn dyn_clone(&self) -> Box<dyn AbsenceStorage + Send> {
+        Box::new(self.clone())
+    }
+}
+
+fn map_adjustment(r: &Row) -> Result<Adjustment, rusqlite::Error> {
+    let kind: String = r.get(2)?;
+    let kind = AdjustmentKind::from_key(&kind).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, format!("unknown adjustment kind {kind}").into())
+    })?;
+    Ok((
+        r.get(0)?,
+        AdjustmentData {
+            date: r.get(1)?,
+            kind,
+            amount: TimeDelta::seconds(r.get(3)?),
+            remark: r.get::<_, Option<String>>(4)?.unwrap_or_default(),
+        },
+    ))
+}
+
+impl BalanceStorage for SqliteStorage {
+    fn add_adjustment(&mut self, adjustment: AdjustmentData) -> Result<AdjustmentId, DataStorageError> {
+        debug!("Inserting balance adjustment: {:?}", adjustment);
+        let mut statement = self.connection.prepare_cached(
+            "insert into balance_adjustments (date, kind, amount, remark) values (?1, ?2, ?3, ?4)",
+        )?;
+        let id = statement.insert((
+            adjustment.date,
+            adjustment.kind.key(),
+            adjustment.amount.num_seconds(),
+            adjustment.remark,
+        ))?;
+        Ok(id)
+    }
+
+    fn remove_adjustment(&mut self, adjustment_id: AdjustmentId) -> Result<(), DataStorageError> {
+        debug!("Deleting balance adjustment: {adjustment_id}");
+        let mut statement = self.connection.prepare_cached("DELETE from balance_adjustments where id = ?1")?;
+        statement.execute([adjustment_id])?;
+        Ok(())
+    }
+
+    fn get_adjustments(&self, range: DateRange) -> Result<Vec<Adjustment>, DataStorageError> {
+        let mut statement = self.connection.prepare_cached(
+            "SELECT id, date, kind, amount, remark from balance_adjustments where date >= ?1 and date <= ?2 order by date asc",
+        )?;
+        let res = statement.query((range.0, range.1))?;
+        Ok(res.map(map_adjustment).collect()?)
+    }
+
+    fn dyn_clone(&self) -> Box<dyn BalanceStorage + Send> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, NaiveTime};
+
+    use crate::audit;
+
+    use super::*;
+
+    fn entry(remark: &str) -> TimeEntryData {
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        TimeEntryData::new(NaiveDate::from_ymd_opt(2024, 3, 4).unwrap(), time(8), time(12), remark.to_owned(), None).unwrap()
+    }
+
+    #[test]
+    fn purged_ids_are_not_reused() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        storage.add_entry(entry("a")).unwrap();
+        let id = storage.add_entry(entry("b")).unwrap();
+        let removed = storage.remove_entry(id).unwrap();
+        storage.purge_entry(id).unwrap();
+
+        assert!(storage.add_entry(entry("c")).unwrap() > id);
+        assert_eq!(storage.restore_entry((id, removed)), Err(DataStorageError::NotFound));
+    }
+
+    #[test]
+    fn restores_entries_from_the_trash() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let id = storage.add_entry(entry("a")).unwrap();
+        let removed = storage.remove_entry(id).unwrap();
+        assert_eq!(storage.get_deleted().unwrap().len(), 1);
+
+        storage.restore_entry((id, removed.clone())).unwrap();
+
+        assert!(storage.get_deleted().unwrap().is_empty());
+        assert_eq!(storage.get_in_range((removed.date, removed.date)).unwrap(), vec![(id, removed.clone())]);
+        assert_eq!(storage.restore_entry((id, removed)), Err(DataStorageError::NotFound));
+    }
+
+    #[test]
+    fn purges_entries_removed_before() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let kept = storage.add_entry(entry("a")).unwrap();
+        let id = storage.add_entry(entry("b")).unwrap();
+        storage.remove_entry(id).unwrap();
+
+        assert!(storage.purge_deleted_before(Local::now().naive_local() - TimeDelta::days(1)).unwrap().is_empty());
+        assert_eq!(storage.purge_deleted_before(Local::now().naive_local() + TimeDelta::seconds(1)).unwrap(), vec![id]);
+        assert!(storage.get_deleted().unwrap().is_empty());
+        assert_eq!(storage.get_in_range((entry("").date, entry("").date)).unwrap().len(), 1);
+        assert_eq!(storage.get_in_range((entry("").date, entry("").date)).unwrap()[0].0, kept);
+    }
+
+    #[test]
+    fn removing_a_project_is_logged() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let project = storage.add_project(ProjectData::new("Titra".to_owned(), None)).unwrap();
+        let data = TimeEntryData {
+            project_id: Some(project),
+            ..entry("a")
+        };
+        let id = storage.add_entry(data.clone()).unwrap();
+        let trashed = storage.add_entry(data.clone()).unwrap();
+        storage.remove_entry(trashed).unwrap();
+
+        storage.remove_project(project).unwrap();
+
+        for entry_id in [id, trashed] {
+            let change = storage.get_changes(entry_id).unwrap().pop().unwrap();
+            assert_eq!(change.kind, ChangeKind::Updated);
+            assert_eq!(audit::decode(change.old_value.as_deref()).unwrap().project_id, Some(project));
+            assert_eq!(audit::decode(change.new_value.as_deref()).unwrap().project_id, None);
+        }
+        assert_eq!(audit::verify(&storage.get_change_log().unwrap()), Ok(()));
+    }
+
+    #[test]
+    fn change_log_is_append_only() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let id = storage.add_entry(entry("a")).unwrap();
+        storage.update_entry(id, entry("b")).unwrap();
+        assert_eq!(storage.get_changes(id).unwrap().len(), 2);
+
+        assert!(storage.connection.execute("UPDATE entry_changes set new_value = NULL", ()).is_err());
+        assert!(storage.connection.execute("DELETE from entry_changes", ()).is_err());
+
+        storage
+            .connection
+            .execute_batch(
+                "DROP TRIGGER entry_changes_no_update;
+                UPDATE entry_changes set new_value = replace(new_value, '\"b\"', '\"c\"') where id = 2;",
+            )
+            .unwrap();
+        let changes = storage.get_change_log().unwrap();
+        assert_eq!(audit::verify(&changes), Err(changes[1].id));
+    }
+
+    #[test]
+    fn filters_the_range_by_project() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let project = storage.add_project(ProjectData::new("Titra".to_owned(), None)).unwrap();
+        let other = storage.add_project(ProjectData::new("Other".to_owned(), None)).unwrap();
+        let id = storage.add_entry(TimeEntryData { project_id: Some(project), ..entry("a") }).unwrap();
+        storage.add_entry(TimeEntryData { project_id: Some(other), ..entry("b") }).unwrap();
+        storage.add_entry(entry("c")).unwrap();
+        let date = entry("").date;
+
+        let entries = storage.get_in_range_for_project((date, date), project).unwrap();
+
+        assert_eq!(entries.iter().map(|e| e.0).collect::<Vec<_>>(), vec![id]);
+    }
+
+    #[test]
+    fn timers_running_too_long_keep_running() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let start = entry("").start_datetime();
+        storage.start_timer(OpenTimeEntry::new(start, "".to_owned(), None)).unwrap();
+
+        assert_eq!(storage.stop_timer(start + TimeDelta::days(2)), Err(DataStorageError::TimerTooLong));
+        assert!(storage.get_running_timer().unwrap().is_some());
+        assert!(storage.get_in_range((start.date(), start.date())).unwrap().is_empty());
+
+        storage.discard_timer().unwrap();
+        assert_eq!(storage.get_running_timer().unwrap(), None);
+    }
+
+    #[test]
+    fn daily_totals_split_entries_at_midnight_and_month_ends() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        let jan_31 = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        let feb_1 = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        for (date, start, end) in [(jan_31, 8, 12), (jan_31, 22, 2), (feb_1, 9, 10)] {
+            storage.add_entry(TimeEntryData::new(date, time(start), time(end), "".to_owned(), None).unwrap()).unwrap();
+        }
+
+        let january = storage.get_daily_totals((NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), jan_31)).unwrap();
+        let february = storage.get_daily_totals((feb_1, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap())).unwrap();
+
+        assert_eq!(january, BTreeMap::from([(jan_31, TimeDelta::hours(6))]));
+        assert_eq!(february, BTreeMap::from([(feb_1, TimeDelta::hours(3))]));
+    }
+
+    #[test]
+    fn later_profiles_get_their_own_data_file() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let first = storage.add_user(UserData::new("A".to_owned())).unwrap();
+        let second = storage.add_user(UserData::new("B".to_owned())).unwrap();
+        let third = storage.add_user(UserData::new("C".to_owned())).unwrap();
+
+        assert_eq!(storage.get_data_file(first).unwrap(), None);
+        let second = storage.get_data_file(second).unwrap().unwrap();
+        let third = storage.get_data_file(third).unwrap().unwrap();
+        assert!(second.starts_with("profile-") && second.ends_with(".sqlite"));
+        assert_ne!(second, third);
+        assert_eq!(storage.get_data_file(99), Err(DataStorageError::NotFound));
+    }
+
+    #[test]
+    fn nothing_is_planned_on_holidays_and_days_off() {
+        let mut storage = SqliteStorage::in_memory().unwrap();
+        let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        storage.add_schedule(ScheduleData::weekdays(date(1, 1), TimeDelta::hours(8))).unwrap();
+        storage.add_day_off(DayOffData::new(date(10, 30), "Betriebsausflug".to_owned())).unwrap();
+        storage.set(date(5, 1), TimeDelta::hours(4)).unwrap();
+
+        let nationwide = storage.get_range((date(10, 29), date(10, 31)), None).unwrap();
+        let saxony = storage.get_range((date(10, 29), date(10, 31)), Some(State::Saxony)).unwrap();
+
+        assert_eq!(nationwide, HashMap::from([(date(10, 29), TimeDelta::hours(8)), (date(10, 30), TimeDelta::zero()), (date(10, 31), TimeDelta::hours(8))]));
+        assert_eq!(saxony[&date(10, 31)], TimeDelta::zero());
+        assert_eq!(storage.get(date(5, 1), None).unwrap(), TimeDelta::zero());
+    }
+}
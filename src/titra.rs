@@ -3,12 +3,14 @@ use std::{
     thread::{spawn, JoinHandle},
 };
 
+use chrono::{Local, NaiveDateTime};
 use eframe::App;
 use egui::{Ui};
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    model::{date_range::DateRange, error::ApplicationError, time_entry::{TimeEntry, TimeEntryData, TimeEntryId}}, state::AppState, storage::{cache::CachedStorage, error::DataStorageError, null::NullService, sqlite::SqliteStorage, PlannedHoursStorage, StorageImplementation, TimeStorage}, views::{overview::Overview, scaffold::Scaffold}
+    absence::AbsenceRules, balance::{BalanceSettings, Sources}, compliance::ComplianceRules, config::{self, Theme}, export::{csv::CsvOptions, ExportSettings}, format::FormatSettings, history::{Command, History}, holidays::State, i18n::Language, model::time_entry::{TimeEntryData, TimeEntryId}, overlap::{OverlapHandling, OverlapSettings}, rules::TimeRules, state::AppState, storage::{cache::CachedStorage, AbsenceStorage, BalanceStorage, error::DataStorageError, null::NullService, sqlite::SqliteStorage, PlannedHoursStorage, StorageImplementation, TimeStorage, TrashSettings, UserDataStorage}, user::{UserId, UserProfile}, views::{overview::Overview, scaffold::Scaffold}
 };
 
 
@@ -16,17 +18,148 @@ use crate::{
 pub struct Services {
     pub time_service: Box<dyn TimeStorage + Send>,
     pub hour_service: Box<dyn PlannedHoursStorage + Send>,
+    pub user_service: Box<dyn UserDataStorage + Send>,
+    pub absence_service: Box<dyn AbsenceStorage + Send>,
+    pub balance_service: Box<dyn BalanceStorage + Send>,
+    pub config: TitraConfig,
+    pub history: History,
 }
 
 impl Services {
-    pub fn new(time_service: Box<dyn TimeStorage + Send>, hour_service: Box<dyn PlannedHoursStorage + Send>) -> Self {
-        Self { time_service, hour_service }
+    pub fn new(time_service: Box<dyn TimeStorage + Send>, hour_service: Box<dyn PlannedHoursStorage + Send>, user_service: Box<dyn UserDataStorage + Send>, absence_service: Box<dyn AbsenceStorage + Send>, balance_service: Box<dyn BalanceStorage + Send>, config: TitraConfig) -> Self {
+        Self { time_service, hour_service, user_service, absence_service, balance_service, config, history: History::default() }
     }
     
     pub(crate) fn empty() -> Self {
         Self {
             time_service: Box::new(NullService),
-            hour_service: Box::new(NullService)
+            hour_service: Box::new(NullService),
+            user_service: Box::new(NullService),
+            absence_service: Box::new(NullService),
+            balance_service: Box::new(NullService),
+            config: TitraConfig::default(),
+            history: History::default(),
+        }
+    }
+
+    /// The profile selected in the config, falls back to the first one.
+    pub fn active_profile(&self) -> Result<Option<UserProfile>, DataStorageError> {
+        active_profile(&self.config, self.user_service.as_ref())
+    }
+
+    /// Makes the profile active and loads its recorded data, `None` falls back to the first profile.
+    /// The undo history belongs to the data of the previous profile and is dropped.
+    pub fn switch_profile(&mut self, user_id: Option<UserId>) -> Result<(), DataStorageError> {
+        let mut config = self.config.clone();
+        config.active_profile = user_id;
+        match config.storage_impl {
+            StorageImplementation::Sqlite => {
+                let data = open_profile(&config, self.user_service.as_ref())?;
+                self.time_service = Box::new(CachedStorage::new_time(data.clone()));
+                self.hour_service = Box::new(CachedStorage::new_hours(data.clone()));
+                self.absence_service = Box::new(data.clone());
+                self.balance_service = Box::new(data);
+            }
+        }
+        self.config = config;
+        self.history = History::default();
+        Ok(())
+    }
+
+    pub fn balance_sources(&self) -> Sources<'_> {
+        Sources {
+            time: self.time_service.as_ref(),
+            hours: self.hour_service.as_ref(),
+            absences: self.absence_service.as_ref(),
+            balance: self.balance_service.as_ref(),
+        }
+    }
+
+    /// Applies a change of entries or planned hours, so it can be undone.
+    pub fn execute(&mut self, command: Command) -> Result<(), DataStorageError> {
+        self.check_overlaps(&command)?;
+        let inverse = command.apply(self.time_service.as_mut(), self.hour_service.as_mut())?;
+        self.history.push(inverse);
+        Ok(())
+    }
+
+    /// Deletes an entry in the trash permanently, steps changing it can't be undone anymore.
+    pub fn purge_entry(&mut self, entry_id: TimeEntryId) -> Result<(), DataStorageError> {
+        self.time_service.purge_entry(entry_id)?;
+        self.history.forget(&[entry_id]);
+        Ok(())
+    }
+
+    /// Empties the trash up to the given time, like `purge_entry`.
+    pub fn purge_deleted_before(&mut self, before: NaiveDateTime) -> Result<usize, DataStorageError> {
+        let purged = self.time_service.purge_deleted_before(before)?;
+        self.history.forget(&purged);
+        Ok(purged.len())
+    }
+
+    /// Closes the running timer into an entry, which is undone like an added one.
+    /// The timer keeps running if the entry would overlap others and overlaps are blocked.
+    pub fn stop_timer(&mut self, end: NaiveDateTime) -> Result<TimeEntryId, DataStorageError> {
+        if let Some(open) = self.time_service.get_running_timer()? {
+            self.check_overlap(&open.close(end)?, None)?;
+        }
+        let id = self.time_service.stop_timer(end)?;
+        self.history.push(Command::RemoveEntry(id));
+        Ok(id)
+    }
+
+    /// Refuses entries overlapping others if the settings block them, undo and redo are not checked.
+    fn check_overlaps(&self, command: &Command) -> Result<(), DataStorageError> {
+        match command {
+            Command::AddEntry(data) => self.check_overlap(data, None),
+            Command::UpdateEntry(id, data) => self.check_overlap(data, Some(*id)),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_overlap(&self, data: &TimeEntryData, ignore: Option<TimeEntryId>) -> Result<(), DataStorageError> {
+        if self.config.overlaps.handling != OverlapHandling::Block {
+            return Ok(());
+        }
+        match self.time_service.get_overlapping(data, ignore)?.is_empty() {
+            true => Ok(()),
+            false => Err(DataStorageError::Overlap),
+        }
+    }
+
+    /// Reverts the last change, returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> Result<bool, DataStorageError> {
+        let Some(command) = self.history.pop_undo() else {
+            return Ok(false);
+        };
+        match command.clone().apply(self.time_service.as_mut(), self.hour_service.as_mut()) {
+            Ok(inverse) => {
+                self.history.push_redo(inverse);
+                Ok(true)
+            }
+            Err(err) => {
+                // the step stays available, e.g. to retry after the storage recovered
+                self.history.push_undo(command);
+                Err(err)
+            }
+        }
+    }
+
+    /// Applies the last undone change again, returns false if there is nothing to redo.
+    pub fn redo(&mut self) -> Result<bool, DataStorageError> {
+        let Some(command) = self.history.pop_redo() else {
+            return Ok(false);
+        };
+        match command.clone().apply(self.time_service.as_mut(), self.hour_service.as_mut()) {
+            Ok(inverse) => {
+                self.history.push_undo(inverse);
+                Ok(true)
+            }
+            Err(err) => {
+                // the step stays available, e.g. to retry after the storage recovered
+                self.history.push_redo(command);
+                Err(err)
+            }
         }
     }
     
@@ -42,15 +175,6 @@ pub enum TitraResult<T,E> {
 }
 
 impl<T,E> TitraResult<T,E> {
-    pub fn map_err<U, O: FnOnce(E) -> U>(self, f: O) -> TitraResult<T, U> {
-        match self {
-            TitraResult::InEdit => TitraResult::InEdit,
-            TitraResult::Done(d) => TitraResult::Done(d),
-            TitraResult::Error(e) => TitraResult::Error(f(e)),
-            TitraResult::NoChange => TitraResult::NoChange,
-        }
-    }
-
     pub fn then<U, O: FnOnce(T) -> U>(self, f: O)-> TitraResult<U, E> {
         match self {
             TitraResult::InEdit => TitraResult::InEdit,
@@ -104,15 +228,58 @@ impl<V: StateView<T,E>, T, E, S> TitraView<T,E,S> for V {
 
 
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct TitraConfig {
     pub root_dir: PathBuf,
     pub storage_impl: StorageImplementation,
+    pub language: Language,
+    pub theme: Theme,
+    /// Planned minutes for weekdays before the first schedule, 0 disables.
+    pub default_working_hours: i64,
+    pub active_profile: Option<UserId>,
+    /// Selects the public holidays, `None` keeps the nationwide ones only.
+    pub federal_state: Option<State>,
+    pub compliance: ComplianceRules,
+    pub rules: TimeRules,
+    pub absences: AbsenceRules,
+    pub balance: BalanceSettings,
+    pub csv: CsvOptions,
+    pub export: ExportSettings,
+    pub format: FormatSettings,
+    pub trash: TrashSettings,
+    pub overlaps: OverlapSettings,
+    /// The file the config was loaded from, settings are written back there.
+    #[serde(skip)]
+    pub config_file: Option<PathBuf>,
+}
+
+impl Default for TitraConfig {
+    fn default() -> Self {
+        Self {
+            root_dir: config::default_data_dir(),
+            storage_impl: StorageImplementation::Sqlite,
+            language: Language::default(),
+            theme: Theme::default(),
+            default_working_hours: 8 * 60,
+            active_profile: None,
+            federal_state: None,
+            compliance: ComplianceRules::default(),
+            rules: TimeRules::default(),
+            absences: AbsenceRules::default(),
+            balance: BalanceSettings::default(),
+            csv: CsvOptions::default(),
+            export: ExportSettings::default(),
+            format: FormatSettings::default(),
+            trash: TrashSettings::default(),
+            overlaps: OverlapSettings::default(),
+            config_file: None,
+        }
+    }
 }
 
 pub struct Titra {
     config: TitraConfig,
     state: AppState,
-    threads: Vec<JoinHandle<()>>,
     init_thread: Option<JoinHandle<Result<Services, DataStorageError>>>,
 }
 
@@ -121,16 +288,23 @@ impl Titra {
         Self {
             config,
             state: AppState::Init,
-            threads: Vec::new(),
+            init_thread: None,
+        }
+    }
+
+    pub fn failed(message: String) -> Self {
+        Self {
+            config: TitraConfig::default(),
+            state: AppState::Failed(message),
             init_thread: None,
         }
     }
 }
 
 impl App for Titra {
-    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
 
-        let mut scaffold = Scaffold::new(ctx, frame);
+        let mut scaffold = Scaffold::new(ctx);
         match &mut self.state {
             AppState::Init => {
                 let Some(init_thread) = &mut self.init_thread else {
@@ -149,7 +323,7 @@ impl App for Titra {
                     };
                     match join_res {
                         Ok(res) => {
-                            self.state = AppState::Loaded(Overview::new(), res)
+                            self.state = AppState::Loaded(Box::new(Overview::new()), Box::new(res))
                         }
                         Err(err) => {
                             self.state = AppState::Failed(err.to_string())
@@ -161,20 +335,145 @@ impl App for Titra {
                 }
                
             }
-            AppState::Loaded(view, services) => scaffold.render(view, services),
+            AppState::Loaded(view, services) => scaffold.render(view.as_mut(), services.as_mut()),
             AppState::Failed(message) => scaffold.failed(message.clone()),
         }
     }
 }
 
+fn active_profile(config: &TitraConfig, users: &dyn UserDataStorage) -> Result<Option<UserProfile>, DataStorageError> {
+    let users = users.get_users()?;
+    let active = users.iter().position(|u| Some(u.0) == config.active_profile).unwrap_or(0);
+    Ok(users.into_iter().nth(active))
+}
+
+/// Opens the database with the recorded data of the active profile and empties its trash.
+/// Without a profile of their own the data is kept in the main database.
+fn open_profile(config: &TitraConfig, users: &dyn UserDataStorage) -> Result<SqliteStorage, DataStorageError> {
+    let data_file = match active_profile(config, users)? {
+        Some((user_id, _)) => users.get_data_file(user_id)?,
+        None => None,
+    };
+    let mut sqlite = match data_file {
+        Some(file) => SqliteStorage::open(config.root_dir.join(file))?,
+        None => SqliteStorage::new(config.root_dir.clone())?,
+    };
+    if let Some(before) = config.trash.purge_before(Local::now().naive_local()) {
+        match sqlite.purge_deleted_before(before) {
+            Ok(purged) => info!("Purged {} entries from the trash", purged.len()),
+            Err(err) => warn!("Failed to purge the trash: {err}"),
+        }
+    }
+    Ok(sqlite)
+}
+
 fn init(config: TitraConfig) -> Result<Services, DataStorageError>{
     match config.storage_impl {
         StorageImplementation::Sqlite => {
-            let sqlite = SqliteStorage::new(config.root_dir.clone())?;
-            let time_service: Box<dyn TimeStorage + Send> = Box::new(CachedStorage::new_time(sqlite.clone()));
-            let hour_service: Box<dyn PlannedHoursStorage + Send> = Box::new(CachedStorage::new_hours(sqlite));
-            Ok(Services::new(time_service, hour_service))
+            let users = SqliteStorage::new(config.root_dir.clone())?;
+            let data = open_profile(&config, &users)?;
+            let time_service: Box<dyn TimeStorage + Send> = Box::new(CachedStorage::new_time(data.clone()));
+            let hour_service: Box<dyn PlannedHoursStorage + Send> = Box::new(CachedStorage::new_hours(data.clone()));
+            let user_service: Box<dyn UserDataStorage + Send> = Box::new(users);
+            let absence_service: Box<dyn AbsenceStorage + Send> = Box::new(data.clone());
+            let balance_service: Box<dyn BalanceStorage + Send> = Box::new(data);
+            Ok(Services::new(time_service, hour_service, user_service, absence_service, balance_service, config))
         }
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use crate::{model::time_entry::OpenTimeEntry, user::UserData};
+
+    use super::*;
+
+    fn services(handling: OverlapHandling) -> Services {
+        let mut config = TitraConfig::default();
+        config.overlaps.handling = handling;
+        Services::new(
+            Box::new(SqliteStorage::in_memory().unwrap()),
+            Box::new(SqliteStorage::in_memory().unwrap()),
+            Box::new(NullService),
+            Box::new(NullService),
+            Box::new(NullService),
+            config,
+        )
+    }
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 4).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn entry(start: u32, end: u32) -> TimeEntryData {
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        TimeEntryData::new(at(0).date(), time(start), time(end), "".to_owned(), None).unwrap()
+    }
+
+    #[test]
+    fn blocked_overlaps_are_not_saved() {
+        let mut services = services(OverlapHandling::Block);
+        services.execute(Command::AddEntry(entry(8, 12))).unwrap();
+
+        assert_eq!(services.execute(Command::AddEntry(entry(11, 13))), Err(DataStorageError::Overlap));
+        services.execute(Command::AddEntry(entry(12, 13))).unwrap();
+
+        services.time_service.start_timer(OpenTimeEntry::new(at(9), "".to_owned(), None)).unwrap();
+        assert_eq!(services.stop_timer(at(10)), Err(DataStorageError::Overlap));
+        assert!(services.time_service.get_running_timer().unwrap().is_some());
+    }
+
+    #[test]
+    fn stopped_timers_can_be_undone() {
+        let mut services = services(OverlapHandling::Warn);
+        services.execute(Command::AddEntry(entry(8, 12))).unwrap();
+        services.time_service.start_timer(OpenTimeEntry::new(at(9), "".to_owned(), None)).unwrap();
+
+        services.stop_timer(at(10)).unwrap();
+        assert_eq!(services.time_service.get_in_range((at(0).date(), at(0).date())).unwrap().len(), 2);
+
+        assert!(services.undo().unwrap());
+        assert_eq!(services.time_service.get_in_range((at(0).date(), at(0).date())).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn profiles_record_their_own_entries() {
+        let root_dir = std::env::temp_dir().join(format!("titra-profiles-{}", std::process::id()));
+        std::fs::create_dir_all(&root_dir).unwrap();
+        let config = TitraConfig {
+            root_dir: root_dir.clone(),
+            ..TitraConfig::default()
+        };
+        let mut services = init(config).unwrap();
+        services.execute(Command::AddEntry(entry(8, 12))).unwrap();
+        let first = services.user_service.add_user(UserData::new("A".to_owned())).unwrap();
+        let second = services.user_service.add_user(UserData::new("B".to_owned())).unwrap();
+        let day = (at(0).date(), at(0).date());
+
+        services.switch_profile(Some(second)).unwrap();
+        assert!(services.time_service.get_in_range(day).unwrap().is_empty());
+        assert!(!services.history.can_undo());
+        services.execute(Command::AddEntry(entry(13, 14))).unwrap();
+
+        services.switch_profile(Some(first)).unwrap();
+        let entries = services.time_service.get_in_range(day).unwrap();
+        std::fs::remove_dir_all(&root_dir).unwrap();
+        assert_eq!(entries.into_iter().map(|e| e.1).collect::<Vec<_>>(), vec![entry(8, 12)]);
+    }
+
+    #[test]
+    fn failed_undo_keeps_the_step() {
+        let mut services = services(OverlapHandling::Warn);
+        services.execute(Command::AddEntry(entry(8, 12))).unwrap();
+        let id = services.time_service.get_in_range((at(0).date(), at(0).date())).unwrap()[0].0;
+        services.time_service.remove_entry(id).unwrap();
+        services.time_service.purge_entry(id).unwrap();
+
+        assert_eq!(services.undo(), Err(DataStorageError::NotFound));
+        assert!(services.history.can_undo());
+        assert!(!services.history.can_redo());
+    }
+}
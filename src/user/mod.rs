@@ -1,15 +1,20 @@
+pub type UserId = i64;
+pub type UserProfile = (UserId, UserData);
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct UserData {
     pub name: String,
     pub street: String,
+    /// Postal code
     pub citycode: String,
-
+    pub city: String,
+    pub employee_number: String,
+    pub employer: String,
+    pub email: String,
 }
 
 impl UserData {
-    pub fn new(name: String, street: String, citycode: String) -> Self {
-        Self { name, street, citycode }
+    pub fn new(name: String) -> Self {
+        Self { name, ..Default::default() }
     }
 }
-
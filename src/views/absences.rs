@@ -0,0 +1,198 @@
+use chrono::{Datelike, Local, NaiveDate};
+use egui::{Button, Color32, ComboBox, Grid, RichText, TextEdit};
+use egui_extras::DatePickerButton;
+use log::warn;
+
+use crate::{
+    absence::{vacation_days_taken, Absence, AbsenceData, AbsenceKind},
+    format::formatter,
+    i18n::{t, Texts},
+    model::{date_range::DateRange, error::ApplicationError},
+    Services, TitraResult, TitraView,
+};
+
+/// Recording of absences and the vacation account of a year.
+pub struct Absences {
+    absences: Vec<Absence>,
+    year: i32,
+    kind: AbsenceKind,
+    start: NaiveDate,
+    end: NaiveDate,
+    half_day: bool,
+    remark: String,
+    entitlement: String,
+    taken: f64,
+    error: Option<String>,
+    requires_refresh: bool,
+}
+
+impl Absences {
+    pub fn new() -> Self {
+        let today = Local::now().date_naive();
+        Self {
+            absences: vec![],
+            year: today.year(),
+            kind: AbsenceKind::Vacation,
+            start: today,
+            end: today,
+            half_day: false,
+            remark: "".to_owned(),
+            entitlement: "".to_owned(),
+            taken: 0.0,
+            error: None,
+            requires_refresh: true,
+        }
+    }
+
+    fn year_range(&self) -> DateRange {
+        (
+            NaiveDate::from_ymd_opt(self.year, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(self.year, 12, 31).unwrap(),
+        )
+    }
+
+    fn refresh(&mut self, services: &Services) -> Result<(), ApplicationError> {
+        let range = self.year_range();
+        self.absences = services.absence_service.get_absences(range)?;
+        let entitlement = services
+            .absence_service
+            .get_vacation_entitlement(self.year)?
+            .unwrap_or(services.config.absences.vacation_days);
+        self.entitlement = formatter().days(entitlement);
+        let planned_hours = services
+            .config
+            .planned_hours(range, services.hour_service.get_range(range, services.config.federal_state)?);
+        self.taken = vacation_days_taken(&self.absences, &planned_hours);
+        Ok(())
+    }
+
+    fn add(&mut self, services: &mut Services) -> Result<(), ApplicationError> {
+        let absence = AbsenceData::new(
+            self.kind,
+            self.start,
+            self.end,
+            self.half_day,
+            self.remark.trim().to_owned(),
+        )?;
+        services.absence_service.add_absence(absence)?;
+        self.remark.clear();
+        self.year = self.start.year();
+        Ok(())
+    }
+
+    fn show_vacation(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let formatter = formatter();
+        let mut changed = false;
+        Grid::new("vacation_grid").num_columns(2).show(ui, |ui| {
+            ui.label(t(Texts::VacationEntitlement));
+            ui.horizontal(|ui| {
+                let response = ui.add(TextEdit::singleline(&mut self.entitlement).desired_width(40.0));
+                ui.label(t(Texts::Days));
+                if response.lost_focus() {
+                    let days = formatter.parse_days(&self.entitlement)?;
+                    services.absence_service.set_vacation_entitlement(self.year, days)?;
+                    changed = true;
+                }
+                Ok::<(), ApplicationError>(())
+            }).inner?;
+            ui.end_row();
+            ui.label(t(Texts::VacationTaken));
+            ui.label(formatter.days(self.taken));
+            ui.end_row();
+            ui.label(t(Texts::VacationRemaining));
+            let remaining = formatter.parse_days(&self.entitlement).map(|e| formatter.days(e - self.taken));
+            ui.label(RichText::new(remaining.unwrap_or_default()).strong());
+            ui.end_row();
+            Ok(changed)
+        }).inner
+    }
+
+    fn show_absences(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let formatter = formatter();
+        let mut changed = false;
+        Grid::new("absences_grid").striped(true).num_columns(6).show(ui, |ui| {
+            ui.label(t(Texts::Kind));
+            ui.label(t(Texts::From));
+            ui.label(t(Texts::To));
+            ui.label(t(Texts::HalfDay));
+            ui.label(t(Texts::Remark));
+            ui.end_row();
+            for (id, absence) in &self.absences {
+                ui.label(RichText::new(absence.kind.name()).color(absence.kind.color()));
+                ui.label(formatter.date_with_weekday(absence.start));
+                ui.label(formatter.date_with_weekday(absence.end));
+                ui.label(if absence.half_day { "½" } else { "" });
+                ui.label(&absence.remark);
+                if ui.button("x").clicked() {
+                    services.absence_service.remove_absence(*id)?;
+                    changed = true;
+                }
+                ui.end_row();
+            }
+
+            ComboBox::from_id_salt("new_absence_kind")
+                .selected_text(self.kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in AbsenceKind::ALL {
+                        ui.selectable_value(&mut self.kind, kind, kind.name());
+                    }
+                });
+            if ui.add(DatePickerButton::new(&mut self.start).id_salt("new_absence_start")).changed() && self.end < self.start {
+                self.end = self.start;
+            }
+            ui.add(DatePickerButton::new(&mut self.end).id_salt("new_absence_end"));
+            ui.checkbox(&mut self.half_day, "");
+            ui.add(TextEdit::singleline(&mut self.remark).desired_width(200.0));
+            if ui.add(Button::new("+")).clicked() {
+                self.add(services)?;
+                changed = true;
+            }
+            ui.end_row();
+            Ok(changed)
+        }).inner
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for Absences {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh {
+            if let Err(err) = self.refresh(services) {
+                warn!("Failed to load absences: {err}");
+                return TitraResult::Error(err);
+            }
+            self.requires_refresh = false;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("<").clicked() {
+                self.year -= 1;
+                self.requires_refresh = true;
+            }
+            ui.label(self.year.to_string());
+            if ui.button(">").clicked() {
+                self.year += 1;
+                self.requires_refresh = true;
+            }
+        });
+        let vacation = self.show_vacation(ui, services);
+        ui.separator();
+        let absences = self.show_absences(ui, services);
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        match (vacation, absences) {
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Failed to store absence: {e}");
+                self.error = Some(e.to_string());
+                TitraResult::Error(e)
+            }
+            (Ok(a), Ok(b)) if a || b => {
+                self.error = None;
+                self.requires_refresh = true;
+                TitraResult::Done(())
+            }
+            _ => TitraResult::NoChange,
+        }
+    }
+}
@@ -1,22 +1,32 @@
 
 
 use chrono::{Local, NaiveDate, NaiveTime};
-use eframe::App;
-use egui::{Button, Color32, Grid, InnerResponse, Response, RichText, TextEdit, Widget};
+use egui::{Button, Color32, Grid, RichText, TextEdit};
 use egui_extras::DatePickerButton;
-use log::{info, warn};
+use log::warn;
 
 use crate::{
-    model::{error::ApplicationError, time_entry::{TimeEntry, TimeEntryData}}, Services, StateView, TitraResult, TitraView
+    history::Command,
+    i18n::{t, Texts},
+    model::{error::ApplicationError, time_entry::{TimeEntry, TimeEntryData}},
+    overlap::{self, OverlapSettings},
+    Services, StateView, TitraResult, TitraView,
 };
 
-use super::time_edit::TimeEdit;
+use super::{project_select::ProjectSelect, time_edit::TimeEdit};
 
 pub struct AddEntry {
     date: NaiveDate,
     start: TimeEdit,
     end: TimeEdit,
     remark: String,
+    project: ProjectSelect,
+    requires_refresh: bool,
+    /// Entries around the day the existing ones were loaded for, to check overlaps.
+    existing: Option<(NaiveDate, Vec<TimeEntry>)>,
+    overlaps: OverlapSettings,
+    /// Why the last entry was not stored, the input is kept to correct it.
+    error: Option<String>,
 }
 
 
@@ -25,27 +35,61 @@ impl AddEntry {
     pub fn new() -> Self {
         Self {
             date: Local::now().date_naive(),
-            start: TimeEdit::new(Some("Startzeit".to_owned())),
-            end: TimeEdit::new_with_value(NaiveTime::from_hms_opt(17, 0, 0).unwrap(),Some("Endzeit".to_owned())),
+            start: TimeEdit::new(Some(Texts::Start)),
+            end: TimeEdit::new_with_value(NaiveTime::from_hms_opt(17, 0, 0).unwrap(),Some(Texts::End)),
             remark: "".to_owned(),
+            project: ProjectSelect::new("add_project".to_owned(), None, vec![]),
+            requires_refresh: true,
+            existing: None,
+            overlaps: OverlapSettings::default(),
+            error: None,
         }
     }
 
+    pub fn refresh_projects(&mut self) {
+        self.requires_refresh = true;
+    }
+
+    /// Reloads the entries checked for overlaps after they were changed elsewhere.
+    pub fn refresh_entries(&mut self) {
+        self.existing = None;
+    }
+
+    fn load_existing(&mut self, services: &Services) {
+        let range = (self.date.pred_opt().unwrap_or(self.date), self.date.succ_opt().unwrap_or(self.date));
+        let entries = services.time_service.get_in_range(range).unwrap_or_else(|err| {
+            warn!("Failed to load entries: {err}");
+            vec![]
+        });
+        self.existing = Some((self.date, entries));
+    }
+
+    fn overlapping(&self, entry: &TimeEntryData) -> Vec<TimeEntry> {
+        self.existing.as_ref().map(|(_, e)| overlap::find(entry, e, None)).unwrap_or_default()
+    }
+
     pub fn validate(&self) -> Result<TimeEntryData, ApplicationError> {
 
         let start = self.start.validate()?;
         let end = self.end.validate()?;
-         
-        if end <= start {
-            return Err(ApplicationError::InvalidRange);
-        }
 
-        Ok(TimeEntryData {
-            start,
-            end,
-            date: self.date.clone(),
-            remark: self.remark.clone()
-        })
+        let entry = TimeEntryData::new(self.date, start, end, self.remark.clone(), self.project.selected())?;
+        self.overlaps.check(&self.overlapping(&entry))?;
+        Ok(entry)
+    }
+
+    fn store(&mut self, services: &mut Services, entry: TimeEntryData) -> TitraResult<(), ApplicationError> {
+        match services.execute(Command::AddEntry(entry)) {
+            Ok(()) => {
+                self.error = None;
+                TitraResult::Done(())
+            }
+            Err(err) => {
+                warn!("Failed to store entry: {err}");
+                self.error = Some(err.to_string());
+                TitraResult::Error(err.into())
+            }
+        }
     }
 
     pub fn get_result(&self) -> TitraResult<TimeEntryData, ApplicationError> {
@@ -55,38 +99,57 @@ impl AddEntry {
             Err(e) => TitraResult::Error(e),
         }
     }
-    
-    fn get_unchanged(&self) -> TimeEntryData {
-        self.validate().unwrap()
-    }
 }
 
 impl TitraView<(), ApplicationError, Services> for AddEntry {
     fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
         let mut final_res = TitraResult::NoChange;
+        if self.requires_refresh {
+            match services.time_service.get_projects() {
+                Ok(projects) => self.project.set_projects(projects),
+                Err(err) => warn!("Failed to load projects: {err}"),
+            }
+            self.requires_refresh = false;
+        }
+        self.overlaps = services.config.overlaps;
+        if self.existing.as_ref().is_none_or(|(date, _)| *date != self.date) {
+            self.load_existing(services);
+        }
         ui.group(|ui| {
             ui.centered_and_justified(|ui|{
                 Grid::new("new_grid").spacing((30.0, 2.0)).show(ui, |ui| {
-                    let mut fn_res = TitraResult::NoChange;
-                    ui.label("Tag");
+                    ui.label(t(Texts::Day));
                     let dpb = DatePickerButton::new(&mut self.date).id_salt("add_date");
                     ui.add(dpb);
                     ui.end_row();
-                    let mut res = StateView::show(&mut self.start, ui);
+                    let times = StateView::show(&mut self.start, ui);
                     ui.end_row();
-                     res = res.combine_with(StateView::show(&mut self.end, ui));
+                    let times = times.combine_with(StateView::show(&mut self.end, ui));
+                    if let Ok(entry) = self.validate() {
+                        if entry.crosses_midnight() {
+                            ui.label(t(Texts::NextDay));
+                        }
+                        let overlapping = self.overlapping(&entry);
+                        if !overlapping.is_empty() {
+                            ui.colored_label(Color32::ORANGE, "⚠")
+                                .on_hover_text(format!("{}: {}", t(Texts::OverlapsWith), overlap::describe(&overlapping)));
+                        }
+                    }
                     ui.end_row();
-                    ui.label("Bemerkung");
+                    ui.label(t(Texts::Remark));
                     let res = ui.add(TextEdit::singleline(&mut self.remark).desired_width(240.0));
                     ui.end_row();
+                    ui.label(t(Texts::Project));
+                    StateView::show(&mut self.project, ui);
+                    ui.end_row();
 
-                    if res.changed() && res.has_focus() {
-                        fn_res = TitraResult::InEdit
+                    let fn_res = if matches!(times, TitraResult::InEdit) || (res.changed() && res.has_focus()) {
+                        TitraResult::InEdit
                     } else if res.changed() {
-                        fn_res = self.get_result();
+                        self.get_result()
                     } else {
-                        fn_res = TitraResult::NoChange;
-                    }
+                        TitraResult::NoChange
+                    };
 
                     let button = Button::new("+");
 
@@ -94,11 +157,7 @@ impl TitraView<(), ApplicationError, Services> for AddEntry {
                         TitraResult::Done(entry) => {
                             let response = ui.add(button);
                             if response.clicked() {
-                                let res = services.time_service.add_entry(entry);
-                                if let Err(err) = res {
-                                    warn!("Failed to store entry: {:?}", err.to_string());
-                                }
-                                final_res = TitraResult::Done(());
+                                final_res = self.store(services, entry);
                             }
                         }
                         TitraResult::Error(err) => {
@@ -107,22 +166,61 @@ impl TitraView<(), ApplicationError, Services> for AddEntry {
                         TitraResult::InEdit =>{ 
                             ui.add_enabled(false, button);
                         },
-                        TitraResult::NoChange => {  let response = ui.add(button);
-                            if response.clicked() {
-                                let res = services.time_service.add_entry(self.get_unchanged());
-                                if let Err(err) = res {
-                                    warn!("Failed to store entry: {:?}", err.to_string());
+                        TitraResult::NoChange => match self.validate() {
+                            Ok(entry) => {
+                                let response = ui.add(button);
+                                if response.clicked() {
+                                    final_res = self.store(services, entry);
                                 }
-                                final_res = TitraResult::Done(());
                             }
-                        }
+                            Err(err) => {
+                                ui.add_enabled(false, button).show_tooltip_text(RichText::new(err.to_string()).color(Color32::DARK_RED));
+                            }
+                        },
                     }
                     ui.end_row();
                 });
             });
-
-
+            if let Some(error) = &self.error {
+                ui.colored_label(Color32::RED, error);
+            }
         });
         final_res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        overlap::OverlapHandling,
+        storage::{error::DataStorageError, null::NullService, sqlite::SqliteStorage},
+        TitraConfig,
+    };
+
+    use super::*;
+
+    #[test]
+    fn failed_entries_keep_the_error() {
+        let mut config = TitraConfig::default();
+        config.overlaps.handling = OverlapHandling::Block;
+        let mut services = Services::new(
+            Box::new(SqliteStorage::in_memory().unwrap()),
+            Box::new(NullService),
+            Box::new(NullService),
+            Box::new(NullService),
+            Box::new(NullService),
+            config,
+        );
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let entry = |start, end| TimeEntryData::new(date, time(start), time(end), "".to_owned(), None).unwrap();
+        let mut view = AddEntry::new();
+
+        assert_eq!(view.store(&mut services, entry(8, 12)), TitraResult::Done(()));
+        assert_eq!(
+            view.store(&mut services, entry(11, 13)),
+            TitraResult::Error(DataStorageError::Overlap.into())
+        );
+        assert_eq!(view.error, Some(DataStorageError::Overlap.to_string()));
+    }
+}
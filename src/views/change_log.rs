@@ -0,0 +1,170 @@
+use egui::{Color32, Grid, ScrollArea};
+use log::warn;
+
+use crate::{
+    audit::{self, Change, ChangeId},
+    format::formatter,
+    i18n::{t, Texts},
+    model::{
+        error::ApplicationError,
+        time_entry::TimeEntryId,
+    },
+    Services, TitraResult, TitraView,
+};
+
+/// Falls back to the stored text for values this version can't read.
+fn describe(value: Option<&str>) -> String {
+    let formatter = formatter();
+    match audit::decode(value) {
+        Some(d) => format!(
+            "{} {} – {} {}",
+            formatter.date(d.date),
+            formatter.time(d.start),
+            formatter.time(d.end),
+            d.remark
+        ),
+        None => value.unwrap_or_default().to_owned(),
+    }
+}
+
+/// Shows the changes as a table, `with_entry` adds the id of the changed entry.
+pub fn show_changes(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, changes: &[Change], with_entry: bool) {
+    let formatter = formatter();
+    let columns = if with_entry { 5 } else { 4 };
+    Grid::new(id_salt).striped(true).num_columns(columns).show(ui, |ui| {
+        if with_entry {
+            ui.label(t(Texts::Entry));
+        }
+        ui.label(t(Texts::ChangedAt));
+        ui.label(t(Texts::Action));
+        ui.label(t(Texts::Before));
+        ui.label(t(Texts::After));
+        ui.end_row();
+        for change in changes {
+            if with_entry {
+                ui.label(change.entry_id.to_string());
+            }
+            ui.label(format!(
+                "{} {}",
+                formatter.date(change.changed_at.date()),
+                formatter.time(change.changed_at.time())
+            ))
+            .on_hover_text(format!("{}: {}", t(Texts::Hash), change.hash));
+            ui.label(change.kind.name());
+            ui.label(describe(change.old_value.as_deref()));
+            ui.label(describe(change.new_value.as_deref()));
+            ui.end_row();
+        }
+    });
+}
+
+/// The recorded changes of a single entry, shown in its own window.
+pub struct EntryChanges {
+    entry_id: TimeEntryId,
+    changes: Vec<Change>,
+}
+
+impl EntryChanges {
+    pub fn load(services: &Services, entry_id: TimeEntryId) -> Result<Self, ApplicationError> {
+        Ok(Self {
+            entry_id,
+            changes: services.time_service.get_changes(entry_id)?,
+        })
+    }
+
+    /// Returns false once the window was closed.
+    pub fn show_window(&self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+        egui::Window::new(format!("{} – {} {}", t(Texts::ChangeLog), t(Texts::Entry), self.entry_id))
+            .id(egui::Id::new(("entry_changes", self.entry_id)))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                show_changes(ui, ("entry_changes_grid", self.entry_id), &self.changes, false);
+            });
+        open
+    }
+}
+
+/// The complete change log with the result of checking its hash chain.
+pub struct ChangeLog {
+    changes: Vec<Change>,
+    verified: Result<(), ChangeId>,
+    error: Option<String>,
+    message: Option<String>,
+    requires_refresh: bool,
+}
+
+impl ChangeLog {
+    pub fn new() -> Self {
+        Self {
+            changes: vec![],
+            verified: Ok(()),
+            error: None,
+            message: None,
+            requires_refresh: true,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        self.requires_refresh = true;
+    }
+
+    fn export(&mut self, services: &Services) {
+        let dialog = rfd::FileDialog::new()
+            .add_filter("Csv", &["csv"])
+            .set_directory(&services.config.export.directory)
+            .set_file_name("change_log.csv");
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        match audit::export_to_file(&self.changes, services.config.csv.delimiter, &path) {
+            Ok(()) => {
+                self.error = None;
+                self.message = Some(format!("{} {}", t(Texts::ExportedTo), path.display()));
+            }
+            Err(err) => {
+                warn!("Failed to export change log: {err}");
+                self.error = Some(err.to_string());
+            }
+        }
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for ChangeLog {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh {
+            match services.time_service.get_change_log() {
+                Ok(changes) => {
+                    self.verified = audit::verify(&changes);
+                    self.changes = changes;
+                }
+                Err(err) => {
+                    warn!("Failed to load change log: {err}");
+                    return TitraResult::Error(err.into());
+                }
+            }
+            self.requires_refresh = false;
+        }
+
+        ui.horizontal(|ui| {
+            match self.verified {
+                Ok(()) => ui.colored_label(Color32::DARK_GREEN, t(Texts::LogIntact)),
+                Err(id) => ui.colored_label(Color32::RED, format!("{} {id}", t(Texts::LogModified))),
+            };
+            if ui.button(t(Texts::Export)).clicked() {
+                self.export(services);
+            }
+        });
+        if let Some(message) = &self.message {
+            ui.label(message);
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+        ui.separator();
+        ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            show_changes(ui, "change_log_grid", &self.changes, true);
+        });
+        TitraResult::NoChange
+    }
+}
@@ -1,11 +1,13 @@
-use std::thread::{spawn, JoinHandle};
+use std::{path::PathBuf, thread::{spawn, JoinHandle}};
 
-use egui::{Button, ComboBox};
+use egui::{Button, Color32, ComboBox};
 
 use crate::{
-    export::{excel::XlsxExporter, Exporter}, model::{date_range::DateRange, error::ApplicationError}, storage::error::DataStorageError, user::UserData, Services, TitraResult, TitraView
+    balance::{self, Days, Sources}, compliance, format::formatter, holidays, i18n::{t, Texts}, export::{csv::CsvExporter, excel::XlsxExporter, export_to_file, ExportData, ExportError, Exporter}, model::{date_range::DateRange, error::ApplicationError, project::Project}, statistics::{self, Granularity}, user::UserData, Services, TitraConfig, TitraResult, TitraView
 };
 
+use super::project_select::ProjectFilter;
+
 
 #[derive(Debug, PartialEq, PartialOrd)]
 pub enum ExportFormat {
@@ -14,10 +16,10 @@ pub enum ExportFormat {
 }
 
 impl ExportFormat {
-    pub fn get_exporter(&self) -> Box<dyn Exporter + Send> {
+    pub fn get_exporter(&self, config: &TitraConfig) -> Box<dyn Exporter + Send> {
         match self {
-            ExportFormat::Csv => todo!(),
-            ExportFormat::Xlsx => Box::new(XlsxExporter::new()),
+            ExportFormat::Csv => Box::new(CsvExporter::new(config.csv.clone(), formatter())),
+            ExportFormat::Xlsx => Box::new(XlsxExporter::new(formatter())),
         }
     }
 
@@ -32,71 +34,191 @@ impl ExportFormat {
 pub struct Export {
     export_format: ExportFormat,
     range: DateRange,
-    user_data: UserData,
-    current_export: Option<JoinHandle<()>>,
+    user_data: Option<UserData>,
+    projects: Vec<Project>,
+    /// Only the entries of the selected project are exported.
+    project_filter: ProjectFilter,
+    requires_refresh: bool,
+    /// Chosen by the user, otherwise the configured directory and file name template are used.
+    destination: Option<PathBuf>,
+    current_export: Option<JoinHandle<Result<PathBuf, ExportError>>>,
+    last_result: Option<Result<PathBuf, ExportError>>,
 }
 
 impl Export {
-    pub fn new(
-        range: DateRange,
-        user_data: UserData,
-    ) -> Self {
+    pub fn new(range: DateRange) -> Self {
         Self {
             export_format: ExportFormat::Xlsx,
             range,
-            user_data,
+            user_data: None,
+            projects: vec![],
+            project_filter: ProjectFilter::new("export_project_filter".to_owned()),
+            requires_refresh: true,
+            destination: None,
             current_export: None,
+            last_result: None,
         }
     }
 
     pub fn set_range(&mut self, range: DateRange) {
         self.range = range;
+        self.destination = None;
+    }
+
+    /// Reloads the active profile before the next export.
+    pub fn refresh_profile(&mut self) {
+        self.requires_refresh = true;
+        self.destination = None;
+    }
+
+    /// Reloads the projects to filter by.
+    pub fn refresh_projects(&mut self) {
+        self.requires_refresh = true;
+    }
+
+    fn destination(&self, config: &TitraConfig) -> PathBuf {
+        let extension = self.export_format.get_exporter(config).extension();
+        match (&self.destination, &self.user_data) {
+            (Some(path), _) => path.with_extension(extension),
+            (None, Some(user_data)) => config.export.default_path(user_data, self.range, extension),
+            (None, None) => config.export.directory.clone(),
+        }
+    }
+
+    fn choose_destination(&mut self, config: &TitraConfig) {
+        let current = self.destination(config);
+        let extension = self.export_format.get_exporter(config).extension();
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter(self.export_format.as_string(), &[extension]);
+        if let Some(dir) = current.parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        if let Some(name) = current.file_name() {
+            dialog = dialog.set_file_name(name.to_string_lossy());
+        }
+        if let Some(path) = dialog.save_file() {
+            self.destination = Some(path);
+        }
     }
 
-    pub fn export(&mut self, services: &mut Services) -> Result<(), DataStorageError> {
+    pub fn export(&mut self, services: &mut Services) {
+        let Some(user_data) = self.user_data.clone() else {
+            return;
+        };
         let clone = services.time_service.clone();
-        let user_data = self.user_data.clone();
-        let exporter = self.export_format.get_exporter();
+        let hours = services.hour_service.clone();
+        let absence_service = services.absence_service.clone();
+        let balance_service = services.balance_service.clone();
+        let exporter = self.export_format.get_exporter(&services.config);
+        let config = services.config.clone();
         let range = (self.range.0, self.range.1);
+        let path = self.destination(&services.config);
+        let project = self.project_filter.selected();
         let handle = spawn(move || {
-            let data = clone.get_in_range(range);
-            if data.is_err() {
-                return;
+            let sources = Sources {
+                time: clone.as_ref(),
+                hours: hours.as_ref(),
+                absences: absence_service.as_ref(),
+                balance: balance_service.as_ref(),
+            };
+            let days = balance::load_days(&sources, &config, range)?;
+            let statistics = statistics::compute(&days, range, Granularity::Week);
+            let Days { mut entries, durations, planned_hours } = days;
+            // entries of the day before are needed to check the rest period on the first day
+            let mut violations = compliance::check(&config.compliance, &entries);
+            violations.retain(|date, _| *date >= range.0);
+            // an entry crossing midnight belongs to the export containing its start
+            entries.retain(|e| e.1.date >= range.0 && e.1.date <= range.1);
+            if let Some(project_id) = project {
+                entries = clone.get_in_range_for_project(range, project_id)?;
+                entries.retain(|e| e.1.date >= range.0);
             }
-            exporter.export(data.unwrap(), user_data.clone()).unwrap();
+            let holidays = holidays::load(hours.as_ref(), range, config.federal_state)?;
+            let absences = absence_service.get_absences(range)?;
+            let balance = balance::compute(&sources, &config, range)?;
+            let data = ExportData {
+                range,
+                entries,
+                user_data,
+                violations,
+                durations,
+                planned_hours,
+                holidays,
+                absences,
+                balance,
+                statistics,
+                projects: clone.get_projects()?,
+            };
+            export_to_file(exporter.as_ref(), &data, &path)?;
+            Ok(path)
         });
 
+        self.last_result = None;
         self.current_export = Some(handle);
-
-        Ok(())
     }
 
     pub fn check_finished(&mut self) {
-        if let Some(handle) = &mut self.current_export {
-            if handle.is_finished() {
-                self.current_export.take().unwrap().join().unwrap();
-            }
+        if self.current_export.as_ref().is_some_and(|h| h.is_finished()) {
+            let result = self.current_export.take().unwrap().join()
+                .unwrap_or_else(|_| Err(ExportError::Unknown(t(Texts::ExportAborted).to_owned())));
+            self.last_result = Some(result);
         }
     }
 }
 
 impl TitraView<(), ApplicationError, Services> for Export {
     fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh {
+            match services.active_profile() {
+                Ok(profile) => self.user_data = profile.map(|p| p.1),
+                Err(err) => self.last_result = Some(Err(err.into())),
+            }
+            match services.time_service.get_projects() {
+                Ok(projects) => self.projects = projects,
+                Err(err) => self.last_result = Some(Err(err.into())),
+            }
+            self.requires_refresh = false;
+        }
         ui.group(|ui| {
             ui.vertical(|ui| {
-                ComboBox::from_label("ExportFormat")
+                ComboBox::from_label(t(Texts::ExportFormat))
                     .selected_text(self.export_format.as_string())
                     .show_ui(ui, |ui| {
                         ui.selectable_value(&mut self.export_format, ExportFormat::Xlsx, "Excel");
                         ui.selectable_value(&mut self.export_format, ExportFormat::Csv, "Csv");
                     });
 
-                let button = Button::new("Export");
-                if self.current_export.is_some() {
+                ui.horizontal(|ui| {
+                    ui.label(t(Texts::Project));
+                    self.project_filter.show(ui, &mut self.projects);
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(self.destination(&services.config).display().to_string());
+                    if ui.button("…").on_hover_text(t(Texts::ChooseDestination)).clicked() {
+                        self.choose_destination(&services.config);
+                    }
+                });
+
+                let button = Button::new(t(Texts::Export));
+                if self.user_data.is_none() {
+                    ui.add_enabled(false, button).on_disabled_hover_text(t(Texts::NoProfile));
+                } else if self.current_export.is_some() {
                     ui.add_enabled(false, button);
                     self.check_finished();
+                    ui.ctx().request_repaint();
                 } else if ui.add(button).clicked() {
-                    self.export(services).unwrap();
+                    self.export(services);
+                }
+
+                match &self.last_result {
+                    Some(Ok(path)) => {
+                        ui.colored_label(Color32::DARK_GREEN, format!("{} {}", t(Texts::ExportedTo), path.display()));
+                    }
+                    Some(Err(err)) => {
+                        ui.colored_label(Color32::RED, err.to_string());
+                    }
+                    None => {}
                 }
             });
         });
@@ -1,4 +1,4 @@
-use crate::{Services, StaticView, TitraView};
+use crate::StaticView;
 
 pub struct Failed {
     message: String
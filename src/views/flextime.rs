@@ -0,0 +1,199 @@
+use chrono::{Datelike, Local, NaiveDate, TimeDelta};
+use egui::{Button, Color32, ComboBox, Grid, RichText, TextEdit};
+use egui_extras::DatePickerButton;
+use log::warn;
+
+use crate::{
+    balance::{self, Adjustment, AdjustmentData, AdjustmentKind},
+    config,
+    format::formatter,
+    i18n::{t, Texts},
+    model::{date_range::DateRange, error::ApplicationError},
+    Services, TitraResult, TitraView,
+};
+
+/// Start and opening balance of the flextime account and its corrections and payouts of a year.
+pub struct Flextime {
+    adjustments: Vec<Adjustment>,
+    year: i32,
+    start: NaiveDate,
+    opening_balance: String,
+    closing: Option<TimeDelta>,
+    date: NaiveDate,
+    kind: AdjustmentKind,
+    amount: String,
+    remark: String,
+    error: Option<String>,
+    requires_refresh: bool,
+}
+
+impl Flextime {
+    pub fn new() -> Self {
+        let today = Local::now().date_naive();
+        Self {
+            adjustments: vec![],
+            year: today.year(),
+            start: today,
+            opening_balance: "".to_owned(),
+            closing: None,
+            date: today,
+            kind: AdjustmentKind::Correction,
+            amount: "".to_owned(),
+            remark: "".to_owned(),
+            error: None,
+            requires_refresh: true,
+        }
+    }
+
+    fn year_range(&self) -> DateRange {
+        (
+            NaiveDate::from_ymd_opt(self.year, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(self.year, 12, 31).unwrap(),
+        )
+    }
+
+    fn refresh(&mut self, services: &Services) -> Result<(), ApplicationError> {
+        let range = self.year_range();
+        self.adjustments = services.balance_service.get_adjustments(range)?;
+        let settings = &services.config.balance;
+        if let Some(start) = settings.start {
+            self.start = start;
+        }
+        self.opening_balance = formatter().duration(TimeDelta::minutes(settings.opening_balance));
+        self.closing = balance::compute(&services.balance_sources(), &services.config, range)?.closing;
+        Ok(())
+    }
+
+    fn save_settings(&mut self, services: &mut Services, start: Option<NaiveDate>) -> Result<(), ApplicationError> {
+        let opening = formatter().parse_signed_duration(&self.opening_balance)?;
+        services.config.balance.start = start;
+        services.config.balance.opening_balance = opening.num_minutes();
+        config::save(&services.config)?;
+        Ok(())
+    }
+
+    fn add(&mut self, services: &mut Services) -> Result<(), ApplicationError> {
+        let amount = formatter().parse_duration(&self.amount)?;
+        let adjustment = AdjustmentData::new(self.date, self.kind, amount, self.remark.trim().to_owned());
+        services.balance_service.add_adjustment(adjustment)?;
+        self.amount.clear();
+        self.remark.clear();
+        self.year = self.date.year();
+        Ok(())
+    }
+
+    fn show_settings(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let mut changed = false;
+        Grid::new("flextime_settings_grid").num_columns(2).show(ui, |ui| {
+            ui.label(t(Texts::BalanceStart));
+            ui.horizontal(|ui| {
+                let mut enabled = services.config.balance.start.is_some();
+                let mut update = ui.checkbox(&mut enabled, "").changed();
+                update |= ui
+                    .add_enabled(enabled, DatePickerButton::new(&mut self.start).id_salt("flextime_start"))
+                    .changed();
+                if update {
+                    self.save_settings(services, enabled.then_some(self.start))?;
+                    changed = true;
+                }
+                Ok::<(), ApplicationError>(())
+            }).inner?;
+            ui.end_row();
+            ui.label(t(Texts::OpeningBalance));
+            if ui.add(TextEdit::singleline(&mut self.opening_balance).desired_width(60.0)).lost_focus() {
+                self.save_settings(services, services.config.balance.start)?;
+                changed = true;
+            }
+            ui.end_row();
+            ui.label(t(Texts::FlextimeBalance));
+            let closing = self.closing.map(|c| formatter().duration(c));
+            ui.label(RichText::new(closing.unwrap_or_default()).strong());
+            ui.end_row();
+            Ok(changed)
+        }).inner
+    }
+
+    fn show_adjustments(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let formatter = formatter();
+        let mut changed = false;
+        Grid::new("adjustments_grid").striped(true).num_columns(5).show(ui, |ui| {
+            ui.label(t(Texts::Date));
+            ui.label(t(Texts::Kind));
+            ui.label(t(Texts::Amount));
+            ui.label(t(Texts::Remark));
+            ui.end_row();
+            for (id, adjustment) in &self.adjustments {
+                ui.label(formatter.date_with_weekday(adjustment.date));
+                ui.label(adjustment.kind.name());
+                ui.label(formatter.duration(adjustment.effect()));
+                ui.label(&adjustment.remark);
+                if ui.button("x").clicked() {
+                    services.balance_service.remove_adjustment(*id)?;
+                    changed = true;
+                }
+                ui.end_row();
+            }
+
+            ui.add(DatePickerButton::new(&mut self.date).id_salt("new_adjustment_date"));
+            ComboBox::from_id_salt("new_adjustment_kind")
+                .selected_text(self.kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in AdjustmentKind::ALL {
+                        ui.selectable_value(&mut self.kind, kind, kind.name());
+                    }
+                });
+            ui.add(TextEdit::singleline(&mut self.amount).desired_width(60.0));
+            ui.add(TextEdit::singleline(&mut self.remark).desired_width(200.0));
+            if ui.add_enabled(!self.amount.trim().is_empty(), Button::new("+")).clicked() {
+                self.add(services)?;
+                changed = true;
+            }
+            ui.end_row();
+            Ok(changed)
+        }).inner
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for Flextime {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh {
+            if let Err(err) = self.refresh(services) {
+                warn!("Failed to load flextime account: {err}");
+                return TitraResult::Error(err);
+            }
+            self.requires_refresh = false;
+        }
+
+        let settings = self.show_settings(ui, services);
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("<").clicked() {
+                self.year -= 1;
+                self.requires_refresh = true;
+            }
+            ui.label(self.year.to_string());
+            if ui.button(">").clicked() {
+                self.year += 1;
+                self.requires_refresh = true;
+            }
+        });
+        let adjustments = self.show_adjustments(ui, services);
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        match (settings, adjustments) {
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Failed to store flextime account: {e}");
+                self.error = Some(e.to_string());
+                TitraResult::Error(e)
+            }
+            (Ok(a), Ok(b)) if a || b => {
+                self.error = None;
+                self.requires_refresh = true;
+                TitraResult::Done(())
+            }
+            _ => TitraResult::NoChange,
+        }
+    }
+}
@@ -1,5 +1,5 @@
 
-use crate::{Services, StaticView, TitraView};
+use crate::StaticView;
 
 pub struct Loading {
     ctx: LoadingContext,
@@ -1,5 +1,3 @@
-
-
 pub mod overview;
 pub mod loading;
 pub mod failed;
@@ -10,3 +8,15 @@ pub mod export;
 pub mod time_edit;
 pub mod time_entry_edit;
 pub mod scaffold;
+pub mod project_select;
+pub mod projects;
+pub mod timer;
+pub mod settings;
+pub mod profiles;
+pub mod schedules;
+pub mod absences;
+pub mod flextime;
+pub mod year_overview;
+pub mod statistics;
+pub mod trash;
+pub mod change_log;
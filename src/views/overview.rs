@@ -1,18 +1,41 @@
-use log::debug;
+use egui::{Button, Key, Modifiers};
+use log::{debug, warn};
 
-use crate::{ extensions::naive_date_ext::NaiveDateExt, model::error::ApplicationError, user::UserData, Services, StateView, StaticView, TitraResult, TitraView};
+use crate::{ i18n::{t, Texts}, model::error::ApplicationError, Services, StateView, TitraResult, TitraView};
 
 use super::{
-    add_entry::AddEntry, export::Export, overview_table::OverviewTable,
-    select_date_range::SelectDateRange,
+    absences::Absences, add_entry::AddEntry, change_log::ChangeLog, export::Export, flextime::Flextime, overview_table::OverviewTable, projects::Projects,
+    profiles::Profiles, schedules::Schedules, select_date_range::{Period, SelectDateRange}, settings::Settings, timer::Timer,
+    statistics::Statistics, trash::Trash, year_overview::YearOverview,
 };
 
 
 pub struct Overview {
     select_date_range: SelectDateRange,
     edit: AddEntry,
+    timer: Timer,
     overview_table: OverviewTable,
     export: Export,
+    projects: Projects,
+    show_projects: bool,
+    settings: Settings,
+    show_settings: bool,
+    profiles: Profiles,
+    show_profiles: bool,
+    schedules: Schedules,
+    show_schedules: bool,
+    absences: Absences,
+    show_absences: bool,
+    flextime: Flextime,
+    show_flextime: bool,
+    year_overview: YearOverview,
+    show_year_overview: bool,
+    statistics: Statistics,
+    show_statistics: bool,
+    trash: Trash,
+    show_trash: bool,
+    change_log: ChangeLog,
+    show_change_log: bool,
 }
 impl Overview {
     pub fn new() -> Self {
@@ -20,43 +43,242 @@ impl Overview {
         let select_date_range = SelectDateRange::new();
         Self {
             edit: AddEntry::new(),
+            timer: Timer::new(),
             select_date_range: select_date_range.clone(),
             overview_table: OverviewTable::new( select_date_range.date),
-            export: Export::new(
-                select_date_range.get_range(),
-                UserData::new(
-                    "Daniel".to_owned(),
-                    "Reichswaldstraße".to_owned(),
-                    "91052".to_owned(),
-                ),
-            ),
+            export: Export::new(select_date_range.get_range()),
+            projects: Projects::new(),
+            show_projects: false,
+            settings: Settings::new(),
+            show_settings: false,
+            profiles: Profiles::new(),
+            show_profiles: false,
+            schedules: Schedules::new(),
+            show_schedules: false,
+            absences: Absences::new(),
+            show_absences: false,
+            flextime: Flextime::new(),
+            show_flextime: false,
+            year_overview: YearOverview::new(),
+            show_year_overview: false,
+            statistics: Statistics::new(),
+            show_statistics: false,
+            trash: Trash::new(),
+            show_trash: false,
+            change_log: ChangeLog::new(),
+            show_change_log: false,
         }
     }
+
+    /// Reloads everything that shows recorded times after a change.
+    fn refresh(&mut self) {
+        self.overview_table.set_date(self.select_date_range.date);
+        self.year_overview.refresh();
+        self.statistics.refresh();
+        self.trash.refresh();
+        self.change_log.refresh();
+        self.edit.refresh_entries();
+    }
+
+    /// Ctrl+Z and Ctrl+Shift+Z, unless a text field has the focus and handles them itself.
+    fn undo_shortcuts(ui: &mut egui::Ui) -> (bool, bool) {
+        if ui.ctx().memory(|m| m.focused().is_some()) {
+            return (false, false);
+        }
+        ui.input_mut(|i| {
+            let redo = i.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z);
+            let undo = i.consume_key(Modifiers::COMMAND, Key::Z);
+            (undo, redo)
+        })
+    }
+
+    fn undo(&mut self, services: &mut Services, redo: bool) {
+        let res = if redo { services.redo() } else { services.undo() };
+        match res {
+            Ok(true) => self.refresh(),
+            Ok(false) => {}
+            Err(err) => warn!("Failed to undo or redo: {err}"),
+        }
+    }
+
+    /// The views hold data of the previous profile, they start over with the new one.
+    fn reload_profile(&mut self) {
+        self.edit = AddEntry::new();
+        self.timer = Timer::new();
+        self.overview_table = OverviewTable::new(self.select_date_range.date);
+        self.export = Export::new(self.select_date_range.get_range());
+        self.projects = Projects::new();
+        self.schedules = Schedules::new();
+        self.absences = Absences::new();
+        self.flextime = Flextime::new();
+        self.year_overview = YearOverview::new();
+        self.statistics = Statistics::new();
+        self.trash = Trash::new();
+        self.change_log = ChangeLog::new();
+        self.select_range();
+    }
+
+    fn select_range(&mut self) {
+        self.overview_table.set_period(self.select_date_range.period);
+        self.overview_table.set_date(self.select_date_range.date);
+        self.export.set_range(self.select_date_range.get_range());
+    }
 }
 
 impl TitraView<(), ApplicationError, Services> for Overview {
     fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
 
-        let res = StateView::show(&mut self.select_date_range, ui);
-        match res {
-            crate::TitraResult::Done(d) => {
-                self.overview_table.set_date(d);
-                self.export.set_range(d.as_month_range());
-            },
-            _ => {}
+        let (mut undo, mut redo) = Self::undo_shortcuts(ui);
+        let res = ui.horizontal(|ui| {
+            let res = StateView::show(&mut self.select_date_range, ui);
+            undo |= ui
+                .add_enabled(services.history.can_undo(), Button::new("↺"))
+                .on_hover_text(t(Texts::Undo))
+                .clicked();
+            redo |= ui
+                .add_enabled(services.history.can_redo(), Button::new("↻"))
+                .on_hover_text(t(Texts::Redo))
+                .clicked();
+            ui.toggle_value(&mut self.show_projects, t(Texts::Projects));
+            ui.toggle_value(&mut self.show_profiles, t(Texts::Profile));
+            ui.toggle_value(&mut self.show_schedules, t(Texts::WorkSchedules));
+            ui.toggle_value(&mut self.show_absences, t(Texts::Absences));
+            ui.toggle_value(&mut self.show_flextime, t(Texts::FlextimeAccount));
+            ui.toggle_value(&mut self.show_year_overview, t(Texts::YearOverview));
+            ui.toggle_value(&mut self.show_statistics, t(Texts::Statistics));
+            ui.toggle_value(&mut self.show_trash, t(Texts::Trash));
+            ui.toggle_value(&mut self.show_change_log, t(Texts::ChangeLog));
+            ui.toggle_value(&mut self.show_settings, t(Texts::Settings));
+            res
+        }).inner;
+        if let TitraResult::Done(_) = res {
+            self.select_range();
         }
+        if undo || redo {
+            self.undo(services, redo);
+        }
+        let mut show_projects = self.show_projects;
+        egui::Window::new(t(Texts::Projects))
+            .open(&mut show_projects)
+            .show(ui.ctx(), |ui| {
+                if let TitraResult::Done(_) = self.projects.show(ui, services) {
+                    self.edit.refresh_projects();
+                    self.timer.refresh_projects();
+                    self.export.refresh_projects();
+                    self.refresh();
+                }
+            });
+        self.show_projects = show_projects;
+
+        let mut show_profiles = self.show_profiles;
+        let active_profile = services.config.active_profile;
+        egui::Window::new(t(Texts::Profile))
+            .open(&mut show_profiles)
+            .show(ui.ctx(), |ui| {
+                if let TitraResult::Done(_) = self.profiles.show(ui, services) {
+                    if services.config.active_profile != active_profile {
+                        self.reload_profile();
+                    }
+                    self.export.refresh_profile();
+                }
+            });
+        self.show_profiles = show_profiles;
+
+        let mut show_schedules = self.show_schedules;
+        egui::Window::new(t(Texts::WorkSchedules))
+            .open(&mut show_schedules)
+            .show(ui.ctx(), |ui| {
+                if let TitraResult::Done(_) = self.schedules.show(ui, services) {
+                    self.refresh();
+                }
+            });
+        self.show_schedules = show_schedules;
+
+        let mut show_absences = self.show_absences;
+        egui::Window::new(t(Texts::Absences))
+            .open(&mut show_absences)
+            .show(ui.ctx(), |ui| {
+                if let TitraResult::Done(_) = self.absences.show(ui, services) {
+                    self.refresh();
+                }
+            });
+        self.show_absences = show_absences;
+
+        let mut show_flextime = self.show_flextime;
+        egui::Window::new(t(Texts::FlextimeAccount))
+            .open(&mut show_flextime)
+            .show(ui.ctx(), |ui| {
+                if let TitraResult::Done(_) = self.flextime.show(ui, services) {
+                    self.refresh();
+                }
+            });
+        self.show_flextime = show_flextime;
+
+        let mut show_year_overview = self.show_year_overview;
+        egui::Window::new(t(Texts::YearOverview))
+            .open(&mut show_year_overview)
+            .show(ui.ctx(), |ui| {
+                if let TitraResult::Done(date) = self.year_overview.show(ui, services) {
+                    self.select_date_range.select(date, Period::Month);
+                    self.select_range();
+                }
+            });
+        self.show_year_overview = show_year_overview;
+
+        let mut show_statistics = self.show_statistics;
+        egui::Window::new(t(Texts::Statistics))
+            .open(&mut show_statistics)
+            .show(ui.ctx(), |ui| {
+                self.statistics.show(ui, services);
+            });
+        self.show_statistics = show_statistics;
+
+        let mut show_trash = self.show_trash;
+        egui::Window::new(t(Texts::Trash))
+            .open(&mut show_trash)
+            .show(ui.ctx(), |ui| {
+                if let TitraResult::Done(_) = self.trash.show(ui, services) {
+                    self.refresh();
+                }
+            });
+        self.show_trash = show_trash;
+
+        let mut show_change_log = self.show_change_log;
+        egui::Window::new(t(Texts::ChangeLog))
+            .open(&mut show_change_log)
+            .show(ui.ctx(), |ui| {
+                self.change_log.show(ui, services);
+            });
+        self.show_change_log = show_change_log;
+
+        let mut show_settings = self.show_settings;
+        egui::Window::new(t(Texts::Settings))
+            .open(&mut show_settings)
+            .show(ui.ctx(), |ui| {
+                if let TitraResult::Done(_) = self.settings.show(ui, services) {
+                    self.refresh();
+                }
+            });
+        self.show_settings = show_settings;
+
         ui.group(|ui|{
             ui.set_width(ui.available_width());
-            self.overview_table.show(ui, services);
+            if let TitraResult::Done(_) = self.overview_table.show(ui, services) {
+                self.year_overview.refresh();
+                self.statistics.refresh();
+                self.trash.refresh();
+                self.change_log.refresh();
+                self.edit.refresh_entries();
+            }
         });
 
         ui.horizontal(|ui| {
 
-            match self.edit.show( ui, services) {
-                TitraResult::Done(_) => {
-                    self.overview_table.set_date(self.select_date_range.date);
-                } 
-                _ => {}
+            if let TitraResult::Done(_) = self.edit.show(ui, services) {
+                self.refresh();
+            }
+            if let TitraResult::Done(_) = self.timer.show(ui, services) {
+                self.refresh();
             }
             self.export.show( ui, services);
         });
@@ -1,92 +1,221 @@
-use std::{collections::HashMap, ops::Range};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::Range,
+};
 
-use chrono::{Datelike, NaiveDate, TimeDelta};
-use egui::{Align, Grid, Label, RichText, ScrollArea};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta};
+use egui::{Align, Color32, Grid, Label, RichText, ScrollArea, TextEdit};
 use log::{info, warn};
 
 use crate::{
-    extensions::naive_date_ext::NaiveDateExt,
+    absence::{absence_on, Absence},
+    balance::{self, Balance},
+    compliance::{self, Violation, Violations},
+    format::formatter,
+    history::Command,
+    holidays,
+    overlap::{self, OverlapSettings},
+    i18n::{t, Texts},
     model::{
+        date_range::DateRange,
         error::ApplicationError,
+        project::{Project, ProjectId},
         time_entry::{TimeEntry, TimeEntryId},
     },
+    rules::DayDuration,
     Services, StateView, StaticView, TitraResult, TitraView,
 };
 
-use super::time_entry_edit::TimeEntryEdit;
+use super::{
+    change_log::EntryChanges, project_select::ProjectFilter, select_date_range::Period, time_entry_edit::TimeEntryEdit,
+};
+
+const HOLIDAY_COLOR: Color32 = Color32::from_rgb(60, 160, 80);
 
 pub struct OverviewTable {
     header: OverviewHeader,
     footer: OverviewFooter,
     month_view: MonthView,
+    project_filter: ProjectFilter,
+    /// Input of the bulk action for all weekdays of the displayed period.
+    weekday_hours: String,
+    weekday_hours_error: Option<ApplicationError>,
 }
 
 impl OverviewTable {
     pub fn new(at: NaiveDate) -> Self {
         Self {
             header: OverviewHeader {},
-            footer: OverviewFooter {},
-            month_view: MonthView::new(at, vec![]),
+            footer: OverviewFooter {
+                totals: vec![],
+                total: DayDuration::zero(),
+                balance: None,
+            },
+            month_view: MonthView::new(at, Period::Month, vec![]),
+            weekday_hours: "".to_owned(),
+            weekday_hours_error: None,
+            project_filter: ProjectFilter::new("overview_project_filter".to_owned()),
         }
     }
 
+    fn show_weekday_hours(&mut self, ui: &mut egui::Ui, services: &mut Services) {
+        ui.horizontal(|ui| {
+            let default = TimeDelta::minutes(services.config.default_working_hours);
+            ui.add(
+                TextEdit::singleline(&mut self.weekday_hours)
+                    .desired_width(60.0)
+                    .hint_text(formatter().duration(default)),
+            );
+            if ui.button(t(Texts::SetWeekdayHours)).clicked() {
+                let hours = if self.weekday_hours.trim().is_empty() {
+                    Ok(default)
+                } else {
+                    parse_planned_hours(&self.weekday_hours)
+                };
+                self.weekday_hours_error = hours
+                    .and_then(|hours| self.month_view.set_weekday_hours(services, hours))
+                    .err();
+            }
+            if let Some(err) = &self.weekday_hours_error {
+                ui.colored_label(Color32::RED, err.to_string());
+            }
+        });
+    }
+
     pub fn set_date(&mut self, date: NaiveDate) {
         self.month_view.set_date(date);
     }
+
+    pub fn set_period(&mut self, period: Period) {
+        self.month_view.set_period(period);
+    }
+}
+
+/// Planned hours of a day, at most 24 hours.
+pub fn parse_planned_hours(input: &str) -> Result<TimeDelta, ApplicationError> {
+    formatter()
+        .parse_duration(input)
+        .and_then(|d| match d <= TimeDelta::hours(24) {
+            true => Ok(d),
+            false => Err(ApplicationError::InvalidTime(input.to_owned())),
+        })
 }
 
 #[derive(Clone)]
 struct PlannedHourView {
+    date: NaiveDate,
     planned_hour: TimeDelta,
     repr: String,
-    date: NaiveDate,
-    actual_time: TimeDelta,
-}
-
-fn format_time_delta_hh_mm(delta: TimeDelta) -> String {
-    format!("{:0>2}:{:0>2}", delta.num_hours(), delta.num_minutes() % 60)
+    error: Option<String>,
+    actual_time: DayDuration,
+    /// Nothing can be planned on holidays.
+    holiday: bool,
 }
 
 impl PlannedHourView {
-    fn new(date: NaiveDate, planned_hour: TimeDelta) -> Self {
+    fn new(date: NaiveDate, planned_hour: TimeDelta, holiday: bool) -> Self {
         Self {
             date,
             planned_hour,
-            repr: format_time_delta_hh_mm(planned_hour),
-            actual_time: TimeDelta::zero(),
+            repr: formatter().duration(planned_hour),
+            error: None,
+            actual_time: DayDuration::zero(),
+            holiday,
         }
     }
 
-    fn set_actual_time(&mut self, delta: TimeDelta) {
-        self.actual_time = delta;
+    fn set_actual_time(&mut self, duration: DayDuration) {
+        self.actual_time = duration;
     }
 }
 
+/// The days of a month or an ISO week with their entries and planned hours.
 struct MonthView {
     date: NaiveDate,
+    period: Period,
     entries: HashMap<NaiveDate, Vec<TimeEntry>>,
+    /// Worked time per day and project, entries crossing midnight are split.
+    attributed: Vec<(NaiveDate, Option<ProjectId>, TimeDelta)>,
     planned_hours: HashMap<NaiveDate, TimeDelta>,
+    holidays: BTreeMap<NaiveDate, String>,
+    absences: Vec<Absence>,
+    balance: Option<Balance>,
+    violations: Violations,
+    durations: BTreeMap<NaiveDate, DayDuration>,
     requires_refresh: bool,
     range: Range<usize>,
     flatten_entries: Vec<MonthViewEntry>,
+    projects: Vec<Project>,
+    project_totals: Vec<ProjectTotal>,
+    /// Only the entries of this project are listed and totalled, daily durations,
+    /// violations and the balance still cover the whole working day.
+    project: Option<ProjectId>,
+    /// Entries overlapping another one, they are highlighted.
+    overlapping: HashSet<TimeEntryId>,
+    overlaps: OverlapSettings,
+}
+
+#[derive(Clone)]
+struct ProjectTotal {
+    name: String,
+    color: Color32,
+    total: TimeDelta,
 }
 
 struct MonthViewEntry {
     time: Option<(TimeEntryId, TimeEntryEdit)>,
     label: Option<String>,
+    /// Holiday or absence of the day, shown next to the date.
+    note: Option<(String, Color32)>,
     planned_time: Option<PlannedHourView>,
+    violations: Vec<Violation>,
+    /// Recorded changes of the entry, shown while loaded.
+    changes: Option<EntryChanges>,
+    overlapping: bool,
+    /// Why the last edit was not saved.
+    error: Option<String>,
+    /// Time between two entries, shown as a break instead of an entry.
+    gap: Option<(NaiveDateTime, NaiveDateTime)>,
+}
+
+impl MonthViewEntry {
+    fn new(time: Option<(TimeEntryId, TimeEntryEdit)>) -> Self {
+        Self {
+            time,
+            label: None,
+            note: None,
+            planned_time: None,
+            violations: vec![],
+            changes: None,
+            overlapping: false,
+            error: None,
+            gap: None,
+        }
+    }
 }
 
 impl MonthView {
-    fn new(date: NaiveDate, entries: Vec<TimeEntry>) -> Self {
-        let map = Self::get_mapped_entries(date, entries);
+    fn new(date: NaiveDate, period: Period, entries: Vec<TimeEntry>) -> Self {
+        let range = period.range(date);
         let mut ret = Self {
             date,
-            entries: map,
+            period,
+            entries: Self::get_mapped_entries(range, &entries),
+            attributed: Self::get_attributed(range, &entries),
             planned_hours: HashMap::new(),
+            holidays: BTreeMap::new(),
+            absences: vec![],
+            balance: None,
+            violations: Violations::new(),
+            durations: BTreeMap::new(),
             requires_refresh: true,
-            range: 0..date.days_in_month() as usize,
+            range: 0..range.0.iter_days().take_while(|d| *d <= range.1).count(),
             flatten_entries: vec![],
+            projects: vec![],
+            project_totals: vec![],
+            project: None,
+            overlapping: overlap::overlapping_ids(&entries),
+            overlaps: OverlapSettings::default(),
         };
 
         ret.flatten_entries();
@@ -97,27 +226,47 @@ impl MonthView {
         self.range = range
     }
 
+    fn date_range(&self) -> DateRange {
+        self.period.range(self.date)
+    }
+
     fn get_mapped_entries(
-        date: NaiveDate,
-        entries: Vec<TimeEntry>,
+        range: DateRange,
+        entries: &[TimeEntry],
     ) -> HashMap<NaiveDate, Vec<TimeEntry>> {
-        let range = date.as_month_range();
         let mut map = HashMap::new();
-        for d in range.0.day()..=range.1.day() {
+        for cur_date in range.0.iter_days().take_while(|d| *d <= range.1) {
             let data = entries
                 .iter()
-                .filter(|e| e.1.date.day() == d)
+                .filter(|e| e.1.date == cur_date)
                 .cloned()
                 .collect();
-            map.insert(date.with_day(d).unwrap(), data);
+            map.insert(cur_date, data);
         }
         map
     }
 
+    fn get_attributed(
+        range: DateRange,
+        entries: &[TimeEntry],
+    ) -> Vec<(NaiveDate, Option<ProjectId>, TimeDelta)> {
+        entries
+            .iter()
+            .flat_map(|e| {
+                e.1.split_at_midnight()
+                    .into_iter()
+                    .map(|(d, duration)| (d, e.1.project_id, duration))
+            })
+            .filter(|(d, _, _)| *d >= range.0 && *d <= range.1)
+            .collect()
+    }
+
     fn set_entries(&mut self, entries: Vec<TimeEntry>) {
         self.entries.clear();
         self.entries
-            .extend(Self::get_mapped_entries(self.date, entries));
+            .extend(Self::get_mapped_entries(self.date_range(), &entries));
+        self.attributed = Self::get_attributed(self.date_range(), &entries);
+        self.overlapping = overlap::overlapping_ids(&entries);
         self.flatten_entries();
         self.set_displayed_range(0..self.rows());
     }
@@ -129,59 +278,158 @@ impl MonthView {
         self.set_displayed_range(0..self.rows());
     }
 
+    fn set_projects(&mut self, projects: Vec<Project>) {
+        self.projects = projects;
+        self.flatten_entries();
+    }
+
+    fn compute_project_totals(&mut self) {
+        let mut totals: HashMap<Option<ProjectId>, TimeDelta> = HashMap::new();
+        for (_, project_id, duration) in &self.attributed {
+            *totals.entry(*project_id).or_insert(TimeDelta::zero()) += *duration;
+        }
+        let mut res: Vec<ProjectTotal> = totals
+            .into_iter()
+            .map(|(id, total)| match self.projects.iter().find(|p| Some(p.0) == id) {
+                Some((_, p)) => ProjectTotal {
+                    name: p.name.clone(),
+                    color: p.color,
+                    total,
+                },
+                None => ProjectTotal {
+                    name: t(Texts::NoProject).to_owned(),
+                    color: Color32::GRAY,
+                    total,
+                },
+            })
+            .collect();
+        res.sort_by(|a, b| a.name.cmp(&b.name));
+        self.project_totals = res;
+    }
+
+    fn total(&self) -> DayDuration {
+        let range = self.date_range();
+        self.durations.range(range.0..=range.1).map(|(_, d)| *d).sum()
+    }
+
     fn set_date(&mut self, date: NaiveDate) {
         self.date = date;
         self.requires_refresh = true;
     }
 
+    fn set_period(&mut self, period: Period) {
+        self.period = period;
+        self.requires_refresh = true;
+    }
+
+    fn set_project(&mut self, project: Option<ProjectId>) {
+        self.project = project;
+        self.requires_refresh = true;
+    }
+
+    fn set_weekday_hours(&mut self, services: &mut Services, hours: TimeDelta) -> Result<(), ApplicationError> {
+        let range = self.date_range();
+        let commands = range
+            .0
+            .iter_days()
+            .take_while(|d| *d <= range.1)
+            .filter(|d| d.weekday().num_days_from_monday() < 5)
+            .map(|d| Command::SetPlannedHours(d, Some(hours)))
+            .collect();
+        services.execute(Command::Batch(commands))?;
+        self.requires_refresh = true;
+        Ok(())
+    }
+
+    /// An absence is shown with its own color, even on a holiday.
+    fn note(&self, date: NaiveDate) -> Option<(String, Color32)> {
+        let holiday = self.holidays.get(&date).cloned();
+        let Some(absence) = absence_on(&self.absences, date) else {
+            return holiday.map(|h| (h, HOLIDAY_COLOR));
+        };
+        let mut name = absence.kind.name().to_owned();
+        if absence.half_day {
+            name.push_str(" ½");
+        }
+        let text = match holiday {
+            Some(h) => format!("{h}, {name}"),
+            None => name,
+        };
+        Some((text, absence.kind.color()))
+    }
+
+    /// The entries of the neighbouring days are passed along to check edits for overlaps.
+    fn entry_row(&self, entry: &TimeEntry) -> MonthViewEntry {
+        let others = [entry.1.date.pred_opt(), Some(entry.1.date), entry.1.date.succ_opt()]
+            .into_iter()
+            .flatten()
+            .filter_map(|d| self.entries.get(&d))
+            .flatten()
+            .cloned()
+            .collect();
+        let edit = TimeEntryEdit::new(entry.0, entry.1.clone(), self.projects.clone(), others, self.overlaps);
+        MonthViewEntry {
+            overlapping: self.overlapping.contains(&entry.0),
+            ..MonthViewEntry::new(Some((entry.0, edit)))
+        }
+    }
+
     fn rows(&self) -> usize {
         self.flatten_entries.len()
     }
 
     fn flatten_entries(&mut self) {
         let mut res = vec![];
-        for d in 1..=self.date.days_in_month() {
-            let cur_date = self.date.with_day(d).unwrap();
-            let entries_for_day = self.entries.get(&cur_date).unwrap();
+        let range = self.date_range();
+        for cur_date in range.0.iter_days().take_while(|d| *d <= range.1) {
+            // the entries of a new period are only mapped once they are loaded
+            let entries_for_day = self.entries.get(&cur_date).map(Vec::as_slice).unwrap_or_default();
+            let holiday = self.holidays.get(&cur_date).cloned();
             let mut planned = PlannedHourView::new(
                 cur_date,
                 *self
                     .planned_hours
                     .get(&cur_date)
                     .unwrap_or(&TimeDelta::zero()),
+                holiday.is_some(),
             );
-            planned.set_actual_time(entries_for_day.iter().map(|e| e.1.end - e.1.start).sum());
-            if entries_for_day.len() == 0 {
-                res.push(MonthViewEntry {
-                    time: None,
-                    label: Some(cur_date.format("%x").to_string()),
-                    planned_time: Some(planned),
-                });
-            } else if entries_for_day.len() == 1 {
-                res.push(MonthViewEntry {
-                    time: Some((
-                        entries_for_day[0].0,
-                        TimeEntryEdit::from(entries_for_day[0].1.clone()),
-                    )),
-                    label: Some(cur_date.format("%x").to_string()),
-                    planned_time: Some(planned),
-                });
+            planned.set_actual_time(
+                self.durations
+                    .get(&cur_date)
+                    .copied()
+                    .unwrap_or(DayDuration::zero()),
+            );
+            let mut vec: Vec<MonthViewEntry> = if entries_for_day.is_empty() {
+                vec![MonthViewEntry::new(None)]
             } else {
-                let mut vec: Vec<MonthViewEntry> = entries_for_day
-                    .iter()
-                    .map(|e| MonthViewEntry {
-                        time: Some((e.0, TimeEntryEdit::from(e.1.clone()))),
-                        label: None,
-                        planned_time: None,
-                    })
-                    .collect();
-                vec[0].label = Some(cur_date.format("%x").to_string());
-                vec[0].planned_time = Some(planned);
-                res.extend(vec);
-            }
+                let mut day = entries_for_day.to_vec();
+                let mut gaps = vec![];
+                if self.overlaps.show_gaps {
+                    day.sort_by_key(|e| e.1.start_datetime());
+                    gaps = overlap::gaps(&day);
+                }
+                let mut gaps = gaps.into_iter().peekable();
+                let mut rows = vec![];
+                for entry in &day {
+                    while let Some(gap) = gaps.next_if(|g| g.1 <= entry.1.start_datetime()) {
+                        rows.push(MonthViewEntry {
+                            gap: Some(gap),
+                            ..MonthViewEntry::new(None)
+                        });
+                    }
+                    rows.push(self.entry_row(entry));
+                }
+                rows
+            };
+            vec[0].label = Some(formatter().date_with_weekday(cur_date));
+            vec[0].note = self.note(cur_date);
+            vec[0].planned_time = Some(planned);
+            vec[0].violations = self.violations.get(&cur_date).cloned().unwrap_or_default();
+            res.extend(vec);
         }
 
         self.flatten_entries = res;
+        self.compute_project_totals();
     }
 }
 
@@ -192,27 +440,93 @@ impl TitraView<(), ApplicationError, Services> for MonthViewEntry {
         services: &mut Services,
     ) -> TitraResult<(), ApplicationError> {
         match &mut self.label {
-            Some(l) => ui.label(l.clone()),
+            Some(l) if !self.violations.is_empty() => {
+                let text = self
+                    .violations
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                ui.label(RichText::new(format!("{l} ⚠")).color(Color32::RED))
+                    .on_hover_text(text)
+            }
+            Some(l) => match &self.note {
+                Some((note, color)) => ui.label(RichText::new(format!("{l} {note}")).color(*color)),
+                None => ui.label(l.clone()),
+            },
             None => ui.label(""),
         };
         let change1 = match &mut self.time {
             Some((id, edit)) => {
-                let res = StateView::show(edit, ui).then(|d| {
-                    if let Err(err) = services.time_service.update_entry(*id, d) {
-                        warn!("Failed to update: {err}");
-                    };
-                });
-                let res2 = if ui.button("x").clicked() {
-                    _ = services.time_service.remove_entry(*id);
-                    TitraResult::Done(())
+                // a failed change keeps the row as edited, a refresh would drop the error
+                let res = match StateView::show(edit, ui) {
+                    TitraResult::Done(d) => match services.execute(Command::UpdateEntry(*id, d)) {
+                        Ok(()) => {
+                            self.error = None;
+                            TitraResult::Done(())
+                        }
+                        Err(err) => {
+                            warn!("Failed to update: {err}");
+                            self.error = Some(err.to_string());
+                            TitraResult::Error(err.into())
+                        }
+                    },
+                    TitraResult::Error(err) => {
+                        self.error = Some(err.to_string());
+                        TitraResult::Error(err)
+                    }
+                    other => other.then(|_| ()),
+                };
+                let (remove, history) = ui
+                    .horizontal(|ui| {
+                        let remove = ui.button("x").clicked();
+                        let history = ui.button("ℹ").on_hover_text(t(Texts::ChangeLog)).clicked();
+                        if let Some(error) = &self.error {
+                            ui.colored_label(Color32::RED, "⚠").on_hover_text(error);
+                        } else if self.overlapping {
+                            ui.colored_label(Color32::ORANGE, "⚠").on_hover_text(t(Texts::Overlapping));
+                        }
+                        (remove, history)
+                    })
+                    .inner;
+                if history {
+                    match EntryChanges::load(services, *id) {
+                        Ok(changes) => self.changes = Some(changes),
+                        Err(err) => warn!("Failed to load changes: {err}"),
+                    }
+                }
+                if self.changes.as_ref().is_some_and(|c| !c.show_window(ui.ctx())) {
+                    self.changes = None;
+                }
+                let res2 = if remove {
+                    match services.execute(Command::RemoveEntry(*id)) {
+                        Ok(()) => TitraResult::Done(()),
+                        Err(err) => {
+                            warn!("Failed to remove: {err}");
+                            self.error = Some(err.to_string());
+                            TitraResult::Error(err.into())
+                        }
+                    }
                 } else {
                     TitraResult::NoChange
                 };
                 res.combine_with(res2)
             }
             None => {
-                ui.label("");
-                ui.label("");
+                match self.gap {
+                    Some((start, end)) => {
+                        let formatter = formatter();
+                        let weak = |text: String| RichText::new(text).italics().weak();
+                        ui.label(weak(formatter.time(start.time())));
+                        ui.label(weak(formatter.time(end.time())));
+                        ui.label(weak(format!("{} ({})", t(Texts::ImplicitBreak), formatter.duration(end - start))));
+                    }
+                    None => {
+                        ui.label("");
+                        ui.label("");
+                        ui.label("");
+                    }
+                }
                 ui.label("");
                 ui.label("");
                 TitraResult::NoChange
@@ -220,11 +534,9 @@ impl TitraView<(), ApplicationError, Services> for MonthViewEntry {
         };
 
         let change2 = match &mut self.planned_time {
-            Some(e) => {
-                StaticView::show(e, ui);
-                TitraResult::NoChange
-            }
+            Some(e) => e.show(ui, services),
             None => {
+                ui.label("");
                 ui.label("");
                 ui.label("");
                 TitraResult::NoChange
@@ -237,12 +549,72 @@ impl TitraView<(), ApplicationError, Services> for MonthViewEntry {
 
 struct OverviewHeader {}
 
-struct OverviewFooter {}
+struct OverviewFooter {
+    totals: Vec<ProjectTotal>,
+    total: DayDuration,
+    balance: Option<Balance>,
+}
 
-impl StaticView for PlannedHourView {
-    fn show(&mut self, ui: &mut egui::Ui) {
-        ui.label(format_time_delta_hh_mm(self.actual_time));
-        ui.label(format_time_delta_hh_mm(self.planned_hour));
+impl TitraView<(), ApplicationError, Services> for PlannedHourView {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        let formatter = formatter();
+        ui.label(formatter.duration(self.actual_time.raw));
+        let adjusted = ui.label(formatter.duration(self.actual_time.adjusted));
+        let mut hints = vec![];
+        if self.actual_time.deducted_break > TimeDelta::zero() {
+            hints.push(format!(
+                "{}: {}",
+                t(Texts::BreakDeducted),
+                formatter.duration(self.actual_time.deducted_break)
+            ));
+        }
+        if self.actual_time.credited > TimeDelta::zero() {
+            hints.push(format!("{}: {}", t(Texts::Credited), formatter.duration(self.actual_time.credited)));
+        }
+        if !hints.is_empty() {
+            adjusted.on_hover_text(hints.join("\n"));
+        }
+        if self.holiday {
+            ui.label(formatter.duration(self.planned_hour));
+            return TitraResult::NoChange;
+        }
+        let mut edit = TextEdit::singleline(&mut self.repr)
+            .desired_width(60.0)
+            .horizontal_align(Align::RIGHT);
+        if self.error.is_some() {
+            edit = edit.text_color(Color32::RED);
+        }
+        let response = ui.add(edit);
+        let response = match &self.error {
+            Some(err) => response.on_hover_text(err),
+            None => response.on_hover_text(t(Texts::ResetToSchedule)),
+        };
+        if !response.lost_focus() || self.repr == formatter.duration(self.planned_hour) {
+            return TitraResult::NoChange;
+        }
+        let res = if self.repr.trim().is_empty() {
+            services
+                .execute(Command::SetPlannedHours(self.date, None))
+                .and_then(|_| services.hour_service.get(self.date, services.config.federal_state))
+                .map_err(ApplicationError::from)
+        } else {
+            parse_planned_hours(&self.repr).and_then(|hours| {
+                services.execute(Command::SetPlannedHours(self.date, Some(hours)))?;
+                Ok(hours)
+            })
+        };
+        match res {
+            Ok(hours) => {
+                self.planned_hour = hours;
+                self.error = None;
+                TitraResult::Done(())
+            }
+            Err(err) => {
+                warn!("Failed to set planned hours: {err}");
+                self.error = Some(err.to_string());
+                TitraResult::Error(err)
+            }
+        }
     }
 }
 
@@ -254,31 +626,64 @@ impl TitraView<(), ApplicationError, Services> for MonthView {
     ) -> TitraResult<(), ApplicationError> {
         if self.requires_refresh {
             info!("Refreshing");
-            if let Ok(entries) = services
-                .time_service
-                .get_in_range(self.date.as_month_range())
-            {
-                self.set_entries(entries);
+            // the day before is needed to check the rest period on the first day
+            let range = self.date_range();
+            self.overlaps = services.config.overlaps;
+            let loaded = (range.0.pred_opt().unwrap(), range.1);
+            if let Ok(entries) = services.time_service.get_in_range(loaded) {
+                self.violations = compliance::check(&services.config.compliance, &entries);
+                self.durations = services.config.rules.evaluate(&services.config.compliance, &entries);
+                let listed = match self.project {
+                    Some(project_id) => services
+                        .time_service
+                        .get_in_range_for_project(loaded, project_id)
+                        .unwrap_or_else(|err| {
+                            warn!("Failed to load the entries of the project: {err}");
+                            vec![]
+                        }),
+                    None => entries,
+                };
+                self.set_entries(listed);
             } else {
+                self.violations.clear();
+                self.durations.clear();
                 self.set_entries(vec![]);
             }
-            if let Ok(planned_hours) = services.hour_service.get_range(self.date.as_month_range()) {
-                self.set_planned_hours(planned_hours);
-            } else {
-                self.set_planned_hours(HashMap::new());
+            match services.time_service.get_projects() {
+                Ok(projects) => self.set_projects(projects),
+                Err(err) => warn!("Failed to load projects: {err}"),
             }
+            self.holidays = holidays::load(services.hour_service.as_ref(), range, services.config.federal_state)
+                .unwrap_or_else(|err| {
+                    warn!("Failed to load holidays: {err}");
+                    BTreeMap::new()
+                });
+            self.absences = services.absence_service.get_absences(range).unwrap_or_else(|err| {
+                warn!("Failed to load absences: {err}");
+                vec![]
+            });
+            let planned_hours = match services.hour_service.get_range(range, services.config.federal_state) {
+                Ok(planned_hours) => services.config.planned_hours(range, planned_hours),
+                Err(_) => HashMap::new(),
+            };
+            services.config.absences.apply(range, &self.absences, &planned_hours, &mut self.durations);
+            self.set_planned_hours(planned_hours);
+            self.balance = balance::compute(&services.balance_sources(), &services.config, range)
+                .inspect_err(|err| warn!("Failed to compute the balance: {err}"))
+                .ok();
             self.requires_refresh = false;
         }
 
+        let mut res = TitraResult::NoChange;
         for d in self.flatten_entries[self.range.clone()].iter_mut() {
-            match d.show(ui, services) {
-                TitraResult::Done(_) => self.requires_refresh = true,
-                _ => {}
+            if let TitraResult::Done(_) = d.show(ui, services) {
+                self.requires_refresh = true;
+                res = TitraResult::Done(());
             }
             ui.end_row();
         }
 
-        TitraResult::NoChange
+        res
     }
 }
 
@@ -288,11 +693,17 @@ impl TitraView<(), ApplicationError, Services> for OverviewTable {
         ui: &mut egui::Ui,
         services: &mut Services,
     ) -> TitraResult<(), ApplicationError> {
+        let mut res = TitraResult::NoChange;
         ui.vertical(|ui|{
-         
+            ui.horizontal(|ui| {
+                ui.label(t(Texts::Project));
+                if let TitraResult::Done(project) = self.project_filter.show(ui, &mut self.month_view.projects) {
+                    self.month_view.set_project(project);
+                }
+            });
 
             Grid::new("header")
-            .num_columns(7)
+            .num_columns(9)
             .min_col_width(120.0)
             .show(ui, |ui| {
                 StaticView::show(&mut self.header, ui);
@@ -308,44 +719,93 @@ impl TitraView<(), ApplicationError, Services> for OverviewTable {
                   
                     Grid::new("overview_all")
                         .striped(true)
-                        .num_columns(7)
+                        .num_columns(9)
                         .min_col_width(120.0)
                         .show(ui, |ui| {
                             self.month_view.set_displayed_range(range);
-                            self.month_view.show(ui, services);
+                            res = self.month_view.show(ui, services);
                         });
                 },
             );
+        self.show_weekday_hours(ui, services);
+        self.footer.totals.clone_from(&self.month_view.project_totals);
+        self.footer.total = self.month_view.total();
+        self.footer.balance = self.month_view.balance;
         Grid::new("footer")
-            .num_columns(7)
+            .num_columns(3)
             .show(ui, |ui| {
                 StaticView::show(&mut self.footer, ui);
             });
 
         });
 
-        TitraResult::NoChange
+        res
     }
 }
 
 impl StaticView for OverviewHeader {
     fn show(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Date  ");
-        ui.heading("Beginn");
-        ui.heading("Ende  ");
+        ui.heading(t(Texts::Date));
+        ui.heading(t(Texts::Start));
+        ui.heading(t(Texts::End));
         ui.add_sized(
             (240.0, 25.0),
-            Label::new(RichText::new("Bemerkung").heading()).halign(Align::LEFT),
+            Label::new(RichText::new(t(Texts::Remark)).heading()).halign(Align::LEFT),
         );
+        ui.heading(t(Texts::Project));
         ui.separator();
-        ui.heading("Ist   ");
-        ui.heading("Soll  ");
+        ui.heading(t(Texts::Actual));
+        ui.heading(t(Texts::Net));
+        ui.heading(t(Texts::Planned));
     }
 }
 
 impl StaticView for OverviewFooter {
     fn show(&mut self, ui: &mut egui::Ui) {
-        ui.heading("total");
-        ui.heading("");
+        ui.heading(t(Texts::Total));
+        let formatter = formatter();
+        ui.heading(formatter.duration(self.total.raw));
+        ui.heading(formatter.duration(self.total.adjusted));
+        ui.end_row();
+        if let Some(balance) = &self.balance {
+            let mut row = |text: Texts, value: TimeDelta| {
+                ui.label(t(text));
+                ui.label("");
+                ui.label(formatter.duration(value));
+                ui.end_row();
+            };
+            row(Texts::Planned, balance.planned);
+            row(Texts::Difference, balance.difference());
+            if let Some(carry_over) = balance.carry_over {
+                row(Texts::CarryOver, carry_over);
+            }
+            if balance.adjustments != TimeDelta::zero() {
+                row(Texts::Adjustments, balance.adjustments);
+            }
+            if let Some(closing) = balance.closing {
+                ui.heading(t(Texts::FlextimeBalance));
+                ui.label("");
+                ui.heading(formatter.duration(closing));
+                ui.end_row();
+            }
+        }
+        for t in &self.totals {
+            ui.label(RichText::new(&t.name).color(t.color));
+            ui.label(formatter.duration(t.total));
+            ui.end_row();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changing_the_month_before_loading_entries() {
+        let mut view = MonthView::new(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(), Period::Month, vec![]);
+        view.set_date(NaiveDate::from_ymd_opt(2024, 2, 15).unwrap());
+        view.set_projects(vec![]);
+        assert_eq!(view.rows(), 29);
     }
 }
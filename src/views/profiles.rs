@@ -0,0 +1,167 @@
+use egui::{Button, Color32, Grid, TextEdit};
+use log::warn;
+
+use crate::{
+    config,
+    i18n::{t, Texts},
+    model::error::ApplicationError,
+    user::{UserData, UserId, UserProfile},
+    Services, TitraResult, TitraView,
+};
+
+const FIELDS: [Texts; 7] = [
+    Texts::Name,
+    Texts::Street,
+    Texts::PostalCode,
+    Texts::City,
+    Texts::EmployeeNumber,
+    Texts::Employer,
+    Texts::Email,
+];
+
+fn fields_mut(data: &mut UserData) -> [&mut String; 7] {
+    [
+        &mut data.name,
+        &mut data.street,
+        &mut data.citycode,
+        &mut data.city,
+        &mut data.employee_number,
+        &mut data.employer,
+        &mut data.email,
+    ]
+}
+
+/// Management of the user profiles. Every profile records its own times, the active one
+/// is shown and used for exports. Removing a profile keeps its data file.
+pub struct Profiles {
+    profiles: Vec<UserProfile>,
+    /// The profile being edited, `None` as id for a new one.
+    edit: Option<(Option<UserId>, UserData)>,
+    error: Option<String>,
+    requires_refresh: bool,
+}
+
+impl Profiles {
+    pub fn new() -> Self {
+        Self {
+            profiles: vec![],
+            edit: None,
+            error: None,
+            requires_refresh: true,
+        }
+    }
+
+    fn set_active(&mut self, services: &mut Services, user_id: Option<UserId>) {
+        self.error = match services.switch_profile(user_id) {
+            Ok(()) => config::save(&services.config).err().map(|err| err.to_string()),
+            Err(err) => Some(err.to_string()),
+        };
+    }
+
+    fn save(&mut self, services: &mut Services) -> Result<(), ApplicationError> {
+        let Some((id, data)) = self.edit.take() else {
+            return Ok(());
+        };
+        match id {
+            Some(id) => services.user_service.update_user(id, data)?,
+            None => {
+                let id = services.user_service.add_user(data)?;
+                if self.profiles.is_empty() {
+                    self.set_active(services, Some(id));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn show_list(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let active = services.config.active_profile
+            .filter(|id| self.profiles.iter().any(|p| p.0 == *id))
+            .or(self.profiles.first().map(|p| p.0));
+        let mut changed = false;
+        Grid::new("profiles_grid").striped(true).num_columns(3).show(ui, |ui| {
+            for (id, data) in self.profiles.clone() {
+                if ui.radio(active == Some(id), &data.name).on_hover_text(t(Texts::ActiveProfile)).clicked() {
+                    self.set_active(services, Some(id));
+                    changed = true;
+                }
+                if ui.button(t(Texts::Edit)).clicked() {
+                    self.edit = Some((Some(id), data));
+                }
+                if ui.button("x").clicked() {
+                    services.user_service.remove_user(id)?;
+                    if active == Some(id) {
+                        self.set_active(services, None);
+                    }
+                    changed = true;
+                }
+                ui.end_row();
+            }
+            Ok::<(), ApplicationError>(())
+        }).inner?;
+        if ui.button(t(Texts::NewProfile)).clicked() {
+            self.edit = Some((None, UserData::default()));
+        }
+        Ok(changed)
+    }
+
+    fn show_edit(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let Some((_, data)) = &mut self.edit else {
+            return Ok(false);
+        };
+        ui.separator();
+        Grid::new("profile_edit_grid").num_columns(2).show(ui, |ui| {
+            for (label, value) in FIELDS.iter().zip(fields_mut(data)) {
+                ui.label(t(*label));
+                ui.add(TextEdit::singleline(value).desired_width(240.0));
+                ui.end_row();
+            }
+        });
+        let valid = !data.name.trim().is_empty();
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(valid, Button::new(t(Texts::Save))).clicked() {
+                changed = true;
+            }
+            if ui.button(t(Texts::Cancel)).clicked() {
+                self.edit = None;
+            }
+        });
+        if changed {
+            self.save(services)?;
+        }
+        Ok(changed)
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for Profiles {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh {
+            match services.user_service.get_users() {
+                Ok(profiles) => self.profiles = profiles,
+                Err(err) => {
+                    warn!("Failed to load profiles: {err}");
+                    return TitraResult::Error(err.into());
+                }
+            }
+            self.requires_refresh = false;
+        }
+
+        let res = self.show_list(ui, services).and_then(|changed| {
+            Ok(self.show_edit(ui, services)? || changed)
+        });
+
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        match res {
+            Ok(true) => {
+                self.requires_refresh = true;
+                TitraResult::Done(())
+            }
+            Ok(false) => TitraResult::NoChange,
+            Err(err) => TitraResult::Error(err),
+        }
+    }
+}
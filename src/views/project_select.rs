@@ -0,0 +1,122 @@
+use egui::{ComboBox, RichText};
+
+use crate::{
+    i18n::{t, Texts},
+    model::{
+        error::ApplicationError,
+        project::{Project, ProjectId},
+    },
+    StateView, TitraResult, TitraView,
+};
+
+#[derive(Clone, Debug)]
+pub struct ProjectSelect {
+    salt: String,
+    selected: Option<ProjectId>,
+    projects: Vec<Project>,
+}
+
+impl ProjectSelect {
+    pub fn new(salt: String, selected: Option<ProjectId>, projects: Vec<Project>) -> Self {
+        Self {
+            salt,
+            selected,
+            projects,
+        }
+    }
+
+    pub fn selected(&self) -> Option<ProjectId> {
+        self.selected
+    }
+
+    pub fn set_projects(&mut self, projects: Vec<Project>) {
+        self.projects = projects;
+    }
+
+    fn selected_text(&self) -> RichText {
+        self.projects
+            .iter()
+            .find(|p| Some(p.0) == self.selected)
+            .map(|p| RichText::new(&p.1.name).color(p.1.color))
+            .unwrap_or_else(|| RichText::new(t(Texts::NoProject)))
+    }
+}
+
+impl StateView<Option<ProjectId>, ApplicationError> for ProjectSelect {
+    fn show(&mut self, ui: &mut egui::Ui) -> TitraResult<Option<ProjectId>, ApplicationError> {
+        let before = self.selected;
+        ComboBox::from_id_salt(&self.salt)
+            .width(120.0)
+            .selected_text(self.selected_text())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.selected, None, t(Texts::NoProject));
+                // archived projects stay selectable for entries that already use them
+                for (id, project) in self
+                    .projects
+                    .iter()
+                    .filter(|p| !p.1.archived || Some(p.0) == before)
+                {
+                    ui.selectable_value(
+                        &mut self.selected,
+                        Some(*id),
+                        RichText::new(&project.name).color(project.color),
+                    );
+                }
+            });
+        if before != self.selected {
+            TitraResult::Done(self.selected)
+        } else {
+            TitraResult::NoChange
+        }
+    }
+}
+
+/// Restricts a view to the entries of one project, `None` shows all entries.
+#[derive(Clone, Debug)]
+pub struct ProjectFilter {
+    salt: String,
+    selected: Option<ProjectId>,
+}
+
+impl ProjectFilter {
+    pub fn new(salt: String) -> Self {
+        Self { salt, selected: None }
+    }
+
+    pub fn selected(&self) -> Option<ProjectId> {
+        self.selected
+    }
+}
+
+impl TitraView<Option<ProjectId>, ApplicationError, Vec<Project>> for ProjectFilter {
+    fn show(&mut self, ui: &mut egui::Ui, projects: &mut Vec<Project>) -> TitraResult<Option<ProjectId>, ApplicationError> {
+        let before = self.selected;
+        // a removed project can no longer be filtered by
+        if self.selected.is_some_and(|id| !projects.iter().any(|p| p.0 == id)) {
+            self.selected = None;
+        }
+        let selected_text = projects
+            .iter()
+            .find(|p| Some(p.0) == self.selected)
+            .map(|p| RichText::new(&p.1.name).color(p.1.color))
+            .unwrap_or_else(|| RichText::new(t(Texts::AllProjects)));
+        ComboBox::from_id_salt(&self.salt)
+            .width(120.0)
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.selected, None, t(Texts::AllProjects));
+                for (id, project) in projects.iter() {
+                    ui.selectable_value(
+                        &mut self.selected,
+                        Some(*id),
+                        RichText::new(&project.name).color(project.color),
+                    );
+                }
+            });
+        if before != self.selected {
+            TitraResult::Done(self.selected)
+        } else {
+            TitraResult::NoChange
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use egui::{Button, ComboBox, Grid, TextEdit};
+use log::warn;
+
+use crate::{
+    i18n::{t, Texts},
+    model::{
+        error::ApplicationError,
+        project::{Client, ClientData, ClientId, Project, ProjectData},
+    },
+    Services, TitraResult, TitraView,
+};
+
+/// Management of clients and projects, shown in its own window.
+pub struct Projects {
+    clients: Vec<Client>,
+    projects: Vec<Project>,
+    new_client: String,
+    new_project: String,
+    new_project_client: Option<ClientId>,
+    show_archived: bool,
+    requires_refresh: bool,
+}
+
+impl Projects {
+    pub fn new() -> Self {
+        Self {
+            clients: vec![],
+            projects: vec![],
+            new_client: "".to_owned(),
+            new_project: "".to_owned(),
+            new_project_client: None,
+            show_archived: false,
+            requires_refresh: true,
+        }
+    }
+
+    fn refresh(&mut self, services: &Services) -> Result<(), ApplicationError> {
+        self.clients = services.time_service.get_clients()?;
+        self.projects = services.time_service.get_projects()?;
+        Ok(())
+    }
+
+    fn client_name(&self, client_id: Option<ClientId>) -> String {
+        self.clients
+            .iter()
+            .find(|c| Some(c.0) == client_id)
+            .map(|c| c.1.name.clone())
+            .unwrap_or_else(|| "-".to_owned())
+    }
+
+    fn show_clients(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let mut changed = false;
+        Grid::new("clients_grid").striped(true).num_columns(4).show(ui, |ui| {
+            for (id, client) in self.clients.iter_mut().filter(|c| self.show_archived || !c.1.archived) {
+                let mut update = ui.add(TextEdit::singleline(&mut client.name).desired_width(200.0)).lost_focus();
+                update |= ui.color_edit_button_srgba(&mut client.color).changed();
+                update |= ui.checkbox(&mut client.archived, t(Texts::Archived)).changed();
+                if update {
+                    services.time_service.update_client(*id, client.clone())?;
+                    changed = true;
+                }
+                if ui.button("x").clicked() {
+                    services.time_service.remove_client(*id)?;
+                    changed = true;
+                }
+                ui.end_row();
+            }
+            ui.add(TextEdit::singleline(&mut self.new_client).desired_width(200.0).hint_text(t(Texts::NewClient)));
+            if ui.add_enabled(!self.new_client.trim().is_empty(), Button::new("+")).clicked() {
+                services.time_service.add_client(ClientData::new(self.new_client.trim().to_owned()))?;
+                self.new_client.clear();
+                changed = true;
+            }
+            ui.end_row();
+            Ok(changed)
+        }).inner
+    }
+
+    fn show_projects(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let mut changed = false;
+        let names: Vec<(Option<ClientId>, String)> = std::iter::once(None)
+            .chain(self.clients.iter().map(|c| Some(c.0)))
+            .map(|id| (id, self.client_name(id)))
+            .collect();
+        Grid::new("projects_grid").striped(true).num_columns(5).show(ui, |ui| {
+            for (id, project) in self.projects.iter_mut().filter(|p| self.show_archived || !p.1.archived) {
+                let mut update = ui.add(TextEdit::singleline(&mut project.name).desired_width(200.0)).lost_focus();
+                let before = project.client_id;
+                ComboBox::from_id_salt(format!("project_client_{id}"))
+                    .selected_text(&names.iter().find(|n| n.0 == project.client_id).unwrap().1)
+                    .show_ui(ui, |ui| {
+                        for (client_id, name) in &names {
+                            ui.selectable_value(&mut project.client_id, *client_id, name);
+                        }
+                    });
+                update |= before != project.client_id;
+                update |= ui.color_edit_button_srgba(&mut project.color).changed();
+                update |= ui.checkbox(&mut project.archived, t(Texts::Archived)).changed();
+                if update {
+                    services.time_service.update_project(*id, project.clone())?;
+                    changed = true;
+                }
+                if ui.button("x").clicked() {
+                    services.time_service.remove_project(*id)?;
+                    changed = true;
+                }
+                ui.end_row();
+            }
+            ui.add(TextEdit::singleline(&mut self.new_project).desired_width(200.0).hint_text(t(Texts::NewProject)));
+            ComboBox::from_id_salt("new_project_client")
+                .selected_text(&names.iter().find(|n| n.0 == self.new_project_client).unwrap().1)
+                .show_ui(ui, |ui| {
+                    for (client_id, name) in &names {
+                        ui.selectable_value(&mut self.new_project_client, *client_id, name);
+                    }
+                });
+            if ui.add_enabled(!self.new_project.trim().is_empty(), Button::new("+")).clicked() {
+                services.time_service.add_project(ProjectData::new(
+                    self.new_project.trim().to_owned(),
+                    self.new_project_client,
+                ))?;
+                self.new_project.clear();
+                changed = true;
+            }
+            ui.end_row();
+            Ok(changed)
+        }).inner
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for Projects {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh {
+            if let Err(err) = self.refresh(services) {
+                warn!("Failed to load projects: {err}");
+                return TitraResult::Error(err);
+            }
+            self.requires_refresh = false;
+        }
+        ui.checkbox(&mut self.show_archived, t(Texts::ShowArchived));
+        ui.heading(t(Texts::Clients));
+        let clients = self.show_clients(ui, services);
+        ui.separator();
+        ui.heading(t(Texts::Projects));
+        let projects = self.show_projects(ui, services);
+
+        match (clients, projects) {
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Failed to store project data: {e}");
+                self.requires_refresh = true;
+                TitraResult::Error(e)
+            }
+            (Ok(a), Ok(b)) if a || b => {
+                self.requires_refresh = true;
+                TitraResult::Done(())
+            }
+            _ => TitraResult::NoChange,
+        }
+    }
+}
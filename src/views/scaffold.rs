@@ -1,17 +1,16 @@
-use crate::{model::error::ApplicationError, Services, StaticView, TitraView};
+use crate::{model::error::ApplicationError, Services, TitraView};
 
 use super::{failed::Failed, loading::Loading};
 
 
 
 
-pub struct Scaffold<'a, 'b> {
+pub struct Scaffold<'a> {
     ctx: &'a egui::Context,
-    frame: &'b mut eframe::Frame
 }
 
 
-impl<'a,'b> Scaffold<'a,'b> {
+impl<'a> Scaffold<'a> {
     pub fn render(&mut self, view: &mut impl TitraView<(),ApplicationError, Services>, services: &mut Services){
         
         
@@ -29,10 +28,9 @@ impl<'a,'b> Scaffold<'a,'b> {
         self.render(&mut Loading::new(), &mut Services::empty());
     }
     
-    pub fn new(ctx: &'a egui::Context, frame: &'b mut eframe::Frame) -> Self {
+    pub fn new(ctx: &'a egui::Context) -> Self {
         Self {
             ctx,
-            frame
         }
     }
     
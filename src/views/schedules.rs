@@ -0,0 +1,229 @@
+use chrono::{Datelike, Local, NaiveDate, TimeDelta, Weekday};
+use egui::{Align, Button, Color32, Grid, TextEdit};
+use egui_extras::DatePickerButton;
+use log::warn;
+
+use crate::{
+    format::formatter,
+    holidays::{DayOff, DayOffData},
+    i18n::{t, Texts},
+    model::{
+        error::ApplicationError,
+        schedule::{schedule_at, Schedule, ScheduleData, ScheduleId},
+    },
+    Services, TitraResult, TitraView,
+};
+
+use super::overview_table::parse_planned_hours;
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+struct ScheduleRow {
+    id: ScheduleId,
+    data: ScheduleData,
+    inputs: [String; 7],
+}
+
+impl ScheduleRow {
+    fn new((id, data): Schedule) -> Self {
+        let formatter = formatter();
+        Self {
+            id,
+            inputs: data.hours.map(|h| formatter.duration(h)),
+            data,
+        }
+    }
+
+    fn validate(&self) -> Result<ScheduleData, ApplicationError> {
+        let mut hours = [TimeDelta::zero(); 7];
+        for (hour, input) in hours.iter_mut().zip(&self.inputs) {
+            *hour = parse_planned_hours(input)?;
+        }
+        Ok(ScheduleData::new(self.data.valid_from, hours))
+    }
+}
+
+/// Editor for the weekly work schedules, each one applies until the next one starts,
+/// and for the company days off of a year.
+pub struct Schedules {
+    rows: Vec<ScheduleRow>,
+    new_valid_from: NaiveDate,
+    days_off: Vec<DayOff>,
+    year: i32,
+    new_day_off: NaiveDate,
+    new_day_off_name: String,
+    error: Option<String>,
+    requires_refresh: bool,
+}
+
+impl Schedules {
+    pub fn new() -> Self {
+        let today = Local::now().date_naive();
+        Self {
+            rows: vec![],
+            new_valid_from: today,
+            days_off: vec![],
+            year: today.year(),
+            new_day_off: today,
+            new_day_off_name: "".to_owned(),
+            error: None,
+            requires_refresh: true,
+        }
+    }
+
+    fn refresh(&mut self, services: &Services) -> Result<(), ApplicationError> {
+        self.rows = services
+            .hour_service
+            .get_schedules()?
+            .into_iter()
+            .map(ScheduleRow::new)
+            .collect();
+        let year = (
+            NaiveDate::from_ymd_opt(self.year, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(self.year, 12, 31).unwrap(),
+        );
+        self.days_off = services.hour_service.get_days_off(year)?;
+        Ok(())
+    }
+
+    /// A new schedule starts with the hours in effect at its start.
+    fn add(&mut self, services: &mut Services) -> Result<(), ApplicationError> {
+        let schedules: Vec<Schedule> = self.rows.iter().map(|r| (r.id, r.data.clone())).collect();
+        let data = match schedule_at(&schedules, self.new_valid_from) {
+            Some(current) => ScheduleData::new(self.new_valid_from, current.hours),
+            None => ScheduleData::weekdays(
+                self.new_valid_from,
+                TimeDelta::minutes(services.config.default_working_hours.max(0)),
+            ),
+        };
+        services.hour_service.add_schedule(data)?;
+        Ok(())
+    }
+
+    fn show_rows(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let formatter = formatter();
+        let mut changed = false;
+        Grid::new("schedules_grid").striped(true).num_columns(10).show(ui, |ui| {
+            ui.label(t(Texts::ValidFrom));
+            for weekday in WEEKDAYS {
+                ui.label(formatter.weekday(weekday).chars().take(2).collect::<String>());
+            }
+            ui.label(t(Texts::WeeklyHours));
+            ui.end_row();
+
+            for row in self.rows.iter_mut() {
+                let mut update = ui
+                    .add(DatePickerButton::new(&mut row.data.valid_from).id_salt(&format!("schedule_{}", row.id)))
+                    .changed();
+                for input in row.inputs.iter_mut() {
+                    update |= ui
+                        .add(TextEdit::singleline(input).desired_width(45.0).horizontal_align(Align::RIGHT))
+                        .lost_focus();
+                }
+                ui.label(formatter.duration(row.data.weekly_total()));
+                if update {
+                    let data = row.validate()?;
+                    services.hour_service.update_schedule(row.id, data)?;
+                    changed = true;
+                }
+                if ui.button("x").clicked() {
+                    services.hour_service.remove_schedule(row.id)?;
+                    changed = true;
+                }
+                ui.end_row();
+            }
+            Ok(changed)
+        }).inner
+    }
+
+    fn show_days_off(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let formatter = formatter();
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if ui.button("<").clicked() {
+                self.year -= 1;
+                self.requires_refresh = true;
+            }
+            ui.label(self.year.to_string());
+            if ui.button(">").clicked() {
+                self.year += 1;
+                self.requires_refresh = true;
+            }
+        });
+        Grid::new("days_off_grid").striped(true).num_columns(3).show(ui, |ui| {
+            for (id, day_off) in &self.days_off {
+                ui.label(formatter.date_with_weekday(day_off.date));
+                ui.label(&day_off.name);
+                if ui.button("x").clicked() {
+                    services.hour_service.remove_day_off(*id)?;
+                    changed = true;
+                }
+                ui.end_row();
+            }
+            ui.add(DatePickerButton::new(&mut self.new_day_off).id_salt("new_day_off"));
+            ui.add(TextEdit::singleline(&mut self.new_day_off_name).desired_width(200.0).hint_text(t(Texts::Name)));
+            if ui.add_enabled(!self.new_day_off_name.trim().is_empty(), Button::new("+")).clicked() {
+                let name = self.new_day_off_name.trim().to_owned();
+                services.hour_service.add_day_off(DayOffData::new(self.new_day_off, name))?;
+                self.new_day_off_name.clear();
+                self.year = self.new_day_off.year();
+                changed = true;
+            }
+            ui.end_row();
+            Ok(changed)
+        }).inner
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for Schedules {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh {
+            if let Err(err) = self.refresh(services) {
+                warn!("Failed to load schedules: {err}");
+                return TitraResult::Error(err);
+            }
+            self.requires_refresh = false;
+        }
+
+        let mut res = self.show_rows(ui, services);
+        ui.horizontal(|ui| {
+            ui.add(DatePickerButton::new(&mut self.new_valid_from).id_salt("new_schedule"));
+            let exists = self.rows.iter().any(|r| r.data.valid_from == self.new_valid_from);
+            if ui.add_enabled(!exists, Button::new(t(Texts::NewSchedule))).clicked() {
+                res = self.add(services).map(|_| true);
+            }
+        });
+        ui.separator();
+        ui.heading(t(Texts::DaysOff));
+        let days_off = self.show_days_off(ui, services);
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        let res = match (res, days_off) {
+            (Err(e), _) | (_, Err(e)) => Err(e),
+            (Ok(a), Ok(b)) => Ok(a || b),
+        };
+        match res {
+            Err(e) => {
+                warn!("Failed to store schedule: {e}");
+                self.error = Some(e.to_string());
+                TitraResult::Error(e)
+            }
+            Ok(true) => {
+                self.error = None;
+                self.requires_refresh = true;
+                TitraResult::Done(())
+            }
+            Ok(false) => TitraResult::NoChange,
+        }
+    }
+}
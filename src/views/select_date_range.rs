@@ -1,15 +1,31 @@
-use std::time::Instant;
-
-use chrono::{ Datelike, Local, NaiveDate};
+use chrono::{ Datelike, Local, NaiveDate, TimeDelta, Weekday};
 use egui::ComboBox;
 use log::info;
 
-use crate::{extensions::naive_date_ext::NaiveDateExt, model::{date_range::DateRange, error::ApplicationError}, Services, StateView, TitraResult, TitraView};
+use crate::{extensions::naive_date_ext::NaiveDateExt, format::formatter, i18n::{month_name, t, Texts}, model::{date_range::DateRange, error::ApplicationError}, StateView, TitraResult};
+
+/// Whether the overview shows a calendar month or an ISO week.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Period {
+    #[default]
+    Month,
+    Week,
+}
+
+impl Period {
+    pub fn range(&self, date: NaiveDate) -> DateRange {
+        match self {
+            Period::Month => date.as_month_range(),
+            Period::Week => date.as_week_range(),
+        }
+    }
+}
 
 
 #[derive(Clone)]
 pub struct SelectDateRange {
     pub date: NaiveDate,
+    pub period: Period,
     state: SelectDateRangeState,
 }
 
@@ -20,41 +36,39 @@ impl SelectDateRange {
 
         Self {
             date: now.date_naive(),
+            period: Period::Month,
             state: SelectDateRangeState {
                 picker: YearMonthPicker::new(now.date_naive(), "year_month_picker".to_owned()),
+                week_picker: WeekPicker::new(now.date_naive(), "week_picker".to_owned()),
             },
         }
     }
 
 
     pub fn get_range(&self) -> DateRange {
-        (self.date.with_day(1).unwrap(), self.date.with_day(self.date.days_in_month()).unwrap())
+        self.period.range(self.date)
     }
-    
 
-}
+    /// Selects a date from outside, e.g. a month clicked in the year overview.
+    pub fn select(&mut self, date: NaiveDate, period: Period) {
+        self.period = period;
+        self.set_date(date);
+    }
 
-fn get_month_name(month: u32) -> Option<&'static str> {
-    match month {
-        1 => Some("Januar"),
-        2 => Some("Februar"),
-        3 => Some("März"),
-        4 => Some("April"),
-        5 => Some("Mai"),
-        6 => Some("Juni"),
-        7 => Some("Juli"),
-        8 => Some("August"),
-        9 => Some("September"),
-        10 => Some("Oktober"),
-        11 => Some("November"),
-        12 => Some("Dezember"),
-        _ => None,
+    /// Both pickers follow the selected date, so switching keeps the position.
+    fn set_date(&mut self, date: NaiveDate) {
+        self.date = date;
+        self.state.picker = YearMonthPicker::new(date, self.state.picker.month.salt.clone());
+        self.state.week_picker.set_date(date);
     }
+
+
 }
 
 #[derive(Clone)]
 struct SelectDateRangeState {
     picker: YearMonthPicker,
+    week_picker: WeekPicker,
 }
 
 #[derive(Clone)]
@@ -76,11 +90,11 @@ impl StateView<u32, ApplicationError> for MonthPicker {
     fn show(&mut self, ui: &mut egui::Ui) -> TitraResult<u32, ApplicationError> {
 
         let response = ComboBox::from_id_salt(&self.salt)
-            .selected_text(get_month_name(self.current_month).unwrap())
+            .selected_text(month_name(self.current_month).unwrap())
             .show_ui(ui, |ui| {
                 let before = self.current_month;
                 for i in 1..=12 {
-                    ui.selectable_value(&mut self.current_month, i, get_month_name(i).unwrap());
+                    ui.selectable_value(&mut self.current_month, i, month_name(i).unwrap());
                 }
                 (self.current_month, before != self.current_month)
             });
@@ -141,7 +155,22 @@ impl YearMonthPicker {
         Self {
             month: MonthPicker::new(date.month(), salt),
             year: YearPicker::new(date.year()),
-            date,
+            // any day of a month can be passed in, e.g. from the week picker
+            date: date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// Ignores years chrono can't represent and shows the previous one again.
+    fn select(&mut self, year: i32, month: u32) -> TitraResult<NaiveDate, ApplicationError> {
+        match self.date.with_year_month(year, month) {
+            Some(date) => {
+                self.date = date;
+                TitraResult::Done(date)
+            }
+            None => {
+                self.year = YearPicker::new(self.date.year());
+                TitraResult::NoChange
+            }
         }
     }
 }
@@ -156,19 +185,88 @@ impl StateView<NaiveDate, ApplicationError> for YearMonthPicker {
             (_, TitraResult::InEdit) => TitraResult::InEdit,
             (TitraResult::Error(e), _) => TitraResult::Error(e),
             (_, TitraResult::Error(e)) => TitraResult::Error(e),
-            (TitraResult::Done(d1), TitraResult::Done(d2)) => {
-                self.date = self.date.with_month(d1).unwrap().with_year(d2).unwrap();
-                TitraResult::Done(self.date)
-            }
+            (TitraResult::Done(m), TitraResult::Done(y)) => self.select(y, m),
             (TitraResult::NoChange, TitraResult::NoChange) => TitraResult::NoChange,
-            (TitraResult::NoChange, TitraResult::Done(y)) => {
-                self.date = self.date.with_year(y).unwrap();
-                TitraResult::Done(self.date)
-            },
-            (TitraResult::Done(m), TitraResult::NoChange) => {
-                self.date = self.date.with_month(m).unwrap();
+            (TitraResult::NoChange, TitraResult::Done(y)) => self.select(y, self.date.month()),
+            (TitraResult::Done(m), TitraResult::NoChange) => self.select(self.date.year(), m),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct WeekPicker {
+    pub salt: String,
+    pub year: YearPicker,
+    /// Monday of the selected week.
+    pub date: NaiveDate,
+}
+
+impl WeekPicker {
+    fn new(date: NaiveDate, salt: String) -> Self {
+        let mut picker = Self {
+            salt,
+            year: YearPicker::new(date.iso_week().year()),
+            date,
+        };
+        picker.set_date(date);
+        picker
+    }
+
+    fn set_date(&mut self, date: NaiveDate) {
+        self.date = date.as_week_range().0;
+        self.year = YearPicker::new(self.date.iso_week().year());
+    }
+
+    /// An ISO year has 53 weeks if it starts or, in leap years, ends on a Thursday.
+    fn weeks_in_year(year: i32) -> u32 {
+        match NaiveDate::from_isoywd_opt(year, 53, Weekday::Mon) {
+            Some(_) => 53,
+            None => 52,
+        }
+    }
+
+    fn week_label(week: u32) -> String {
+        format!("{} {week}", t(Texts::CalendarWeek))
+    }
+}
+
+impl StateView<NaiveDate, ApplicationError> for WeekPicker {
+    fn show(&mut self, ui: &mut egui::Ui) -> TitraResult<NaiveDate, ApplicationError> {
+        let mut date = self.date;
+        if ui.button("<").clicked() {
+            date -= TimeDelta::weeks(1);
+        }
+        let iso_week = self.date.iso_week();
+        let mut week = iso_week.week();
+        ComboBox::from_id_salt(&self.salt)
+            .selected_text(Self::week_label(week))
+            .show_ui(ui, |ui| {
+                for i in 1..=Self::weeks_in_year(iso_week.year()) {
+                    ui.selectable_value(&mut week, i, Self::week_label(i));
+                }
+            });
+        if week != iso_week.week() {
+            date = NaiveDate::from_isoywd_opt(iso_week.year(), week, Weekday::Mon).unwrap();
+        }
+        let year = StateView::show(&mut self.year, ui);
+        if let TitraResult::Done(year) = year {
+            let week = week.min(Self::weeks_in_year(year));
+            date = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).unwrap_or(date);
+        }
+        if ui.button(">").clicked() {
+            date += TimeDelta::weeks(1);
+        }
+        let formatter = formatter();
+        let range = date.as_week_range();
+        ui.label(format!("{} – {}", formatter.date(range.0), formatter.date(range.1)));
+
+        match year {
+            TitraResult::InEdit => TitraResult::InEdit,
+            _ if date != self.date => {
+                self.set_date(date);
                 TitraResult::Done(self.date)
             }
+            _ => TitraResult::NoChange,
         }
     }
 }
@@ -176,16 +274,23 @@ impl StateView<NaiveDate, ApplicationError> for YearMonthPicker {
 impl StateView<NaiveDate, ApplicationError> for SelectDateRange {
     fn show(&mut self, ui: &mut egui::Ui) -> TitraResult<NaiveDate, ApplicationError>{
         let res = ui.group(|ui| {
-            // Grid::new("overview_input_grid").show(ui, |ui| {
-            let response = ui
-                .horizontal(|ui| {
-
-                    let res = StateView::show(&mut self.state.picker, ui);
-                    res
-                   
-                });
-            response.inner
-            // });
+            ui.horizontal(|ui| {
+                let period = self.period;
+                ui.selectable_value(&mut self.period, Period::Month, t(Texts::Month));
+                ui.selectable_value(&mut self.period, Period::Week, t(Texts::Week));
+                let res = match self.period {
+                    Period::Month => StateView::show(&mut self.state.picker, ui),
+                    Period::Week => StateView::show(&mut self.state.week_picker, ui),
+                };
+                match res {
+                    TitraResult::Done(date) => {
+                        self.set_date(date);
+                        TitraResult::Done(date)
+                    }
+                    TitraResult::NoChange if period != self.period => TitraResult::Done(self.date),
+                    res => res,
+                }
+            }).inner
         });
         res.inner
     }
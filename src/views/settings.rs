@@ -0,0 +1,318 @@
+use egui::{Color32, ComboBox, Grid, TextEdit};
+
+use crate::{
+    config::{self, Theme},
+    extensions::time_delta_ext::TimeDeltaExt,
+    absence::{AbsenceKind, AbsenceRules},
+    export::ExportSettings,
+    format::{self, formatter, DateStyle, DurationStyle, FormatSettings, TimeStyle},
+    holidays::State,
+    i18n::{self, t, Language, Texts},
+    model::error::ApplicationError,
+    overlap::{OverlapHandling, OverlapSettings},
+    Services, TitraConfig, TitraResult, TitraView,
+};
+
+fn theme_name(theme: Theme) -> &'static str {
+    t(match theme {
+        Theme::System => Texts::ThemeSystem,
+        Theme::Light => Texts::ThemeLight,
+        Theme::Dark => Texts::ThemeDark,
+    })
+}
+
+fn date_style_name(style: Option<DateStyle>) -> &'static str {
+    match style {
+        None => t(Texts::Automatic),
+        Some(DateStyle::German) => "31.12.2025",
+        Some(DateStyle::Iso) => "2025-12-31",
+    }
+}
+
+fn time_style_name(style: Option<TimeStyle>) -> &'static str {
+    match style {
+        None => t(Texts::Automatic),
+        Some(TimeStyle::H24) => "17:30",
+        Some(TimeStyle::H12) => "5:30 pm",
+    }
+}
+
+fn state_name(state: Option<State>) -> &'static str {
+    match state {
+        None => t(Texts::NationwideOnly),
+        Some(state) => state.name(),
+    }
+}
+
+fn duration_style_name(style: DurationStyle) -> &'static str {
+    match style {
+        DurationStyle::HoursMinutes => "hh:mm",
+        DurationStyle::Decimal => t(Texts::DecimalHours),
+    }
+}
+
+/// The part of the config edited here. Other views change the config while the
+/// dialog is open, so only these fields are copied back on save.
+#[derive(Clone, Debug, PartialEq)]
+struct SettingsDraft {
+    language: Language,
+    theme: Theme,
+    format: FormatSettings,
+    federal_state: Option<State>,
+    absences: AbsenceRules,
+    overlaps: OverlapSettings,
+    export: ExportSettings,
+}
+
+impl SettingsDraft {
+    fn new(config: &TitraConfig) -> Self {
+        Self {
+            language: config.language,
+            theme: config.theme,
+            format: config.format,
+            federal_state: config.federal_state,
+            absences: config.absences.clone(),
+            overlaps: config.overlaps,
+            export: config.export.clone(),
+        }
+    }
+
+    fn apply(self, config: &mut TitraConfig) {
+        config.language = self.language;
+        config.theme = self.theme;
+        config.format = self.format;
+        config.federal_state = self.federal_state;
+        config.absences = self.absences;
+        config.overlaps = self.overlaps;
+        config.export = self.export;
+    }
+}
+
+/// Edits a draft of the settings, which is applied and written back on save.
+pub struct Settings {
+    draft: Option<SettingsDraft>,
+    working_hours: String,
+    vacation_days: String,
+    error: Option<String>,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self {
+            draft: None,
+            working_hours: "".to_owned(),
+            vacation_days: "".to_owned(),
+            error: None,
+        }
+    }
+
+    fn parse_working_hours(&self) -> Option<i64> {
+        formatter()
+            .parse_duration(&self.working_hours)
+            .ok()
+            .map(|d| d.num_minutes())
+            .filter(|minutes| *minutes < 24 * 60)
+    }
+
+    fn save(&mut self, ui: &egui::Ui, services: &mut Services) -> bool {
+        let Some(minutes) = self.parse_working_hours() else {
+            self.error = Some(t(Texts::InvalidWorkingHours).to_owned());
+            return false;
+        };
+        let vacation_days = match formatter().parse_days(&self.vacation_days) {
+            Ok(days) => days,
+            Err(err) => {
+                self.error = Some(err.to_string());
+                return false;
+            }
+        };
+        // the csv formats can only be changed in the config file
+        if let Err(err) = services.config.csv.validate() {
+            self.error = Some(err.to_string());
+            return false;
+        }
+        let Some(mut draft) = self.draft.clone() else {
+            return false;
+        };
+        draft.absences.vacation_days = vacation_days;
+        let mut config = services.config.clone();
+        draft.apply(&mut config);
+        config.default_working_hours = minutes;
+        if let Err(err) = config::save(&config) {
+            self.error = Some(err.to_string());
+            return false;
+        }
+        ui.ctx().set_theme(config.theme.preference());
+        i18n::set_language(config.language);
+        format::set_settings(config.format);
+        services.config = config;
+        self.draft = None;
+        self.error = None;
+        true
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for Settings {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        let draft = self.draft.get_or_insert_with(|| {
+            self.working_hours = chrono::TimeDelta::minutes(services.config.default_working_hours).format_hh_mm();
+            self.vacation_days = formatter().days(services.config.absences.vacation_days);
+            SettingsDraft::new(&services.config)
+        });
+
+        Grid::new("settings_grid").num_columns(2).show(ui, |ui| {
+            ui.label(t(Texts::Language));
+            ComboBox::from_id_salt("settings_language")
+                .selected_text(draft.language.name())
+                .show_ui(ui, |ui| {
+                    for language in Language::ALL {
+                        ui.selectable_value(&mut draft.language, language, language.name());
+                    }
+                });
+            ui.end_row();
+
+            ui.label(t(Texts::Theme));
+            ComboBox::from_id_salt("settings_theme")
+                .selected_text(theme_name(draft.theme))
+                .show_ui(ui, |ui| {
+                    for theme in [Theme::System, Theme::Light, Theme::Dark] {
+                        ui.selectable_value(&mut draft.theme, theme, theme_name(theme));
+                    }
+                });
+            ui.end_row();
+
+            ui.label(t(Texts::DateFormat));
+            ComboBox::from_id_salt("settings_date_format")
+                .selected_text(date_style_name(draft.format.date))
+                .show_ui(ui, |ui| {
+                    for style in [None, Some(DateStyle::German), Some(DateStyle::Iso)] {
+                        ui.selectable_value(&mut draft.format.date, style, date_style_name(style));
+                    }
+                });
+            ui.end_row();
+
+            ui.label(t(Texts::TimeFormat));
+            ComboBox::from_id_salt("settings_time_format")
+                .selected_text(time_style_name(draft.format.time))
+                .show_ui(ui, |ui| {
+                    for style in [None, Some(TimeStyle::H24), Some(TimeStyle::H12)] {
+                        ui.selectable_value(&mut draft.format.time, style, time_style_name(style));
+                    }
+                });
+            ui.end_row();
+
+            ui.label(t(Texts::DurationFormat));
+            ComboBox::from_id_salt("settings_duration_format")
+                .selected_text(duration_style_name(draft.format.duration))
+                .show_ui(ui, |ui| {
+                    for style in [DurationStyle::HoursMinutes, DurationStyle::Decimal] {
+                        ui.selectable_value(&mut draft.format.duration, style, duration_style_name(style));
+                    }
+                });
+            ui.end_row();
+
+            ui.label(t(Texts::DefaultWorkingHours));
+            ui.add(TextEdit::singleline(&mut self.working_hours).desired_width(60.0).hint_text("8:00"));
+            ui.end_row();
+
+            ui.label(t(Texts::VacationEntitlement));
+            ui.horizontal(|ui| {
+                ui.add(TextEdit::singleline(&mut self.vacation_days).desired_width(40.0));
+                ui.label(t(Texts::Days));
+            });
+            ui.end_row();
+
+            ui.label(t(Texts::CreditedAbsences));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut draft.absences.credit_vacation, AbsenceKind::Vacation.name());
+                ui.checkbox(&mut draft.absences.credit_sick_leave, AbsenceKind::SickLeave.name());
+                ui.checkbox(
+                    &mut draft.absences.credit_overtime_compensation,
+                    AbsenceKind::OvertimeCompensation.name(),
+                );
+            });
+            ui.end_row();
+
+            ui.label(t(Texts::FederalState));
+            ComboBox::from_id_salt("settings_federal_state")
+                .selected_text(state_name(draft.federal_state))
+                .show_ui(ui, |ui| {
+                    for state in std::iter::once(None).chain(State::ALL.map(Some)) {
+                        ui.selectable_value(&mut draft.federal_state, state, state_name(state));
+                    }
+                });
+            ui.end_row();
+
+            ui.label(t(Texts::OverlappingEntries));
+            ui.horizontal(|ui| {
+                ComboBox::from_id_salt("settings_overlaps")
+                    .selected_text(draft.overlaps.handling.name())
+                    .show_ui(ui, |ui| {
+                        for handling in OverlapHandling::ALL {
+                            ui.selectable_value(&mut draft.overlaps.handling, handling, handling.name());
+                        }
+                    });
+                ui.checkbox(&mut draft.overlaps.show_gaps, t(Texts::ShowGaps));
+            });
+            ui.end_row();
+
+            ui.label(t(Texts::ExportDirectory));
+            ui.horizontal(|ui| {
+                ui.label(draft.export.directory.display().to_string());
+                if ui.button("…").clicked() {
+                    if let Some(dir) = rfd::FileDialog::new().set_directory(&draft.export.directory).pick_folder() {
+                        draft.export.directory = dir;
+                    }
+                }
+            });
+            ui.end_row();
+
+            ui.label(t(Texts::FileName));
+            ui.add(TextEdit::singleline(&mut draft.export.filename_template).desired_width(240.0));
+            ui.end_row();
+
+            ui.label(t(Texts::DataDirectory));
+            ui.label(services.config.root_dir.display().to_string());
+            ui.end_row();
+        });
+
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        let mut res = TitraResult::NoChange;
+        ui.horizontal(|ui| {
+            if ui.button(t(Texts::Save)).clicked() && self.save(ui, services) {
+                res = TitraResult::Done(());
+            }
+            if ui.button(t(Texts::Discard)).clicked() {
+                self.draft = None;
+                self.error = None;
+            }
+        });
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draft_keeps_changes_made_elsewhere() {
+        let config = TitraConfig::default();
+        let mut draft = SettingsDraft::new(&config);
+        draft.language = Language::En;
+        draft.overlaps.show_gaps = true;
+
+        let mut current = config.clone();
+        current.active_profile = Some(3);
+        current.trash.retention_days = 7;
+        draft.apply(&mut current);
+
+        assert_eq!(current.language, Language::En);
+        assert!(current.overlaps.show_gaps);
+        assert_eq!(current.active_profile, Some(3));
+        assert_eq!(current.trash.retention_days, 7);
+    }
+}
@@ -0,0 +1,216 @@
+use std::thread::{spawn, JoinHandle};
+
+use chrono::{Local, NaiveDate, TimeDelta};
+use egui::{Color32, ComboBox, Grid};
+use egui_extras::DatePickerButton;
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, PlotPoints};
+use log::warn;
+
+use crate::{
+    balance::{self, Sources},
+    extensions::naive_date_ext::NaiveDateExt,
+    format::formatter,
+    i18n::{t, Texts},
+    model::error::ApplicationError,
+    statistics::{self, period_label, Granularity, Statistics as Figures},
+    Services, TitraResult, TitraView,
+};
+
+const ACTUAL_COLOR: Color32 = Color32::from_rgb(70, 140, 220);
+const PLANNED_COLOR: Color32 = Color32::from_rgb(220, 120, 60);
+const CHART_HEIGHT: f32 = 180.0;
+
+fn hours(duration: TimeDelta) -> f64 {
+    duration.num_minutes() as f64 / 60.0
+}
+
+/// Charts and key figures of a freely chosen date range.
+pub struct Statistics {
+    from: NaiveDate,
+    to: NaiveDate,
+    granularity: Granularity,
+    figures: Option<Figures>,
+    loading: Option<JoinHandle<Result<Figures, ApplicationError>>>,
+    error: Option<String>,
+    requires_refresh: bool,
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        let (from, to) = Local::now().date_naive().as_month_range();
+        Self {
+            from,
+            to,
+            granularity: Granularity::Day,
+            figures: None,
+            loading: None,
+            error: None,
+            requires_refresh: true,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        self.requires_refresh = true;
+    }
+
+    fn load(&mut self, services: &Services) {
+        let time = services.time_service.clone();
+        let hours = services.hour_service.clone();
+        let absences = services.absence_service.clone();
+        let balance = services.balance_service.clone();
+        let config = services.config.clone();
+        let range = (self.from, self.to.max(self.from));
+        let granularity = self.granularity;
+        self.loading = Some(spawn(move || {
+            let sources = Sources {
+                time: time.as_ref(),
+                hours: hours.as_ref(),
+                absences: absences.as_ref(),
+                balance: balance.as_ref(),
+            };
+            let days = balance::load_days(&sources, &config, range)?;
+            Ok(statistics::compute(&days, range, granularity))
+        }));
+    }
+
+    fn check_finished(&mut self) {
+        if self.loading.as_ref().is_some_and(|h| h.is_finished()) {
+            match self.loading.take().unwrap().join() {
+                Ok(Ok(figures)) => {
+                    self.figures = Some(figures);
+                    self.error = None;
+                }
+                Ok(Err(err)) => {
+                    warn!("Failed to load statistics: {err}");
+                    self.error = Some(err.to_string());
+                }
+                Err(_) => self.error = Some(t(Texts::LoadingAborted).to_owned()),
+            }
+        }
+    }
+
+    fn show_summary(ui: &mut egui::Ui, figures: &Figures) {
+        let formatter = formatter();
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "–".to_owned());
+        Grid::new("statistics_summary_grid").num_columns(4).show(ui, |ui| {
+            ui.label(t(Texts::Net));
+            ui.label(formatter.duration(figures.actual));
+            ui.label(t(Texts::WorkedDays));
+            ui.label(figures.worked_days.to_string());
+            ui.end_row();
+            ui.label(t(Texts::Planned));
+            ui.label(formatter.duration(figures.planned));
+            ui.label(t(Texts::AverageDay));
+            ui.label(optional(figures.average_day.map(|d| formatter.duration(d))));
+            ui.end_row();
+            ui.label(t(Texts::Difference));
+            ui.label(formatter.duration(figures.actual - figures.planned));
+            ui.label(t(Texts::AverageStart));
+            ui.label(optional(figures.average_start.map(|s| formatter.time(s))));
+            ui.end_row();
+            ui.label(t(Texts::LongestStreak));
+            ui.label(optional(figures.longest_streak.map(|s| {
+                format!("{} {} ({} – {})", s.days(), t(Texts::Days), formatter.date(s.start), formatter.date(s.end))
+            })));
+            ui.label(t(Texts::AverageEnd));
+            ui.label(optional(figures.average_end.map(|e| formatter.time(e))));
+            ui.end_row();
+        });
+    }
+
+    fn show_hours_chart(ui: &mut egui::Ui, figures: &Figures) {
+        let labels: Vec<String> = figures.buckets.iter().map(|b| period_label(figures.granularity, b.start)).collect();
+        let actual = figures
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(i, b)| Bar::new(i as f64, hours(b.actual)).name(&labels[i]))
+            .collect();
+        let planned: PlotPoints = figures
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(i, b)| [i as f64, hours(b.planned)])
+            .collect();
+        Plot::new("statistics_hours")
+            .height(CHART_HEIGHT)
+            .legend(Legend::default())
+            .allow_scroll(false)
+            .include_y(0.0)
+            .x_axis_formatter(move |mark, _| {
+                let index = mark.value.round();
+                match index == mark.value && index >= 0.0 {
+                    true => labels.get(index as usize).cloned().unwrap_or_default(),
+                    false => String::new(),
+                }
+            })
+            .show(ui, |plot| {
+                plot.bar_chart(BarChart::new(actual).width(0.7).color(ACTUAL_COLOR).name(t(Texts::Net)));
+                plot.line(Line::new(planned).color(PLANNED_COLOR).name(t(Texts::Planned)));
+            });
+    }
+
+    fn show_times_chart(ui: &mut egui::Ui, figures: &Figures) {
+        let bars = |counts: &[u32; 24], offset: f64| {
+            (0..24)
+                .map(|hour| Bar::new(hour as f64 + offset, counts[hour] as f64).width(0.4))
+                .collect()
+        };
+        Plot::new("statistics_times")
+            .height(CHART_HEIGHT)
+            .legend(Legend::default())
+            .allow_scroll(false)
+            .include_x(0.0)
+            .include_x(24.0)
+            .x_axis_formatter(|mark, _| format!("{:02}:00", mark.value as i64))
+            .show(ui, |plot| {
+                plot.bar_chart(BarChart::new(bars(&figures.start_hours, -0.2)).color(ACTUAL_COLOR).name(t(Texts::Start)));
+                plot.bar_chart(BarChart::new(bars(&figures.end_hours, 0.2)).color(PLANNED_COLOR).name(t(Texts::End)));
+            });
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for Statistics {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh && self.loading.is_none() {
+            self.load(services);
+            self.requires_refresh = false;
+        }
+        if self.loading.is_some() {
+            self.check_finished();
+            ui.ctx().request_repaint();
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(t(Texts::From));
+            self.requires_refresh |= ui.add(DatePickerButton::new(&mut self.from).id_salt("statistics_from")).changed();
+            ui.label(t(Texts::To));
+            self.requires_refresh |= ui.add(DatePickerButton::new(&mut self.to).id_salt("statistics_to")).changed();
+            ComboBox::from_id_salt("statistics_granularity")
+                .selected_text(self.granularity.name())
+                .show_ui(ui, |ui| {
+                    for granularity in Granularity::ALL {
+                        self.requires_refresh |= ui
+                            .selectable_value(&mut self.granularity, granularity, granularity.name())
+                            .changed();
+                    }
+                });
+            if self.loading.is_some() {
+                ui.spinner();
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+        let Some(figures) = &self.figures else {
+            return TitraResult::NoChange;
+        };
+
+        Self::show_summary(ui, figures);
+        ui.separator();
+        Self::show_hours_chart(ui, figures);
+        ui.label(t(Texts::StartEndDistribution));
+        Self::show_times_chart(ui, figures);
+        TitraResult::NoChange
+    }
+}
@@ -1,14 +1,14 @@
 use chrono::NaiveTime;
-use egui::{Align, Color32, Id, Layout, RichText, TextEdit};
+use egui::{Align, TextEdit};
 use log::info;
 
-use crate::{ model::error::ApplicationError, Services, StateView, TitraResult, TitraView};
+use crate::{format::formatter, i18n::{t, Texts}, model::error::ApplicationError, StateView, TitraResult};
 
 #[derive(Clone, Debug)]
 pub struct TimeEdit {
     time: NaiveTime,
     repr: String,
-    label: Option<String>,
+    label: Option<Texts>,
 }
 
 
@@ -16,13 +16,13 @@ pub struct TimeEdit {
 
 
 impl TimeEdit {
-    pub fn new(label: Option<String>) -> Self {
+    pub fn new(label: Option<Texts>) -> Self {
         let time = NaiveTime::default();
         Self::new_with_value(time, label)
     }
 
-    pub fn new_with_value(time: NaiveTime, label: Option<String>) -> Self {
-        let repr = time.format("%R").to_string();
+    pub fn new_with_value(time: NaiveTime, label: Option<Texts>) -> Self {
+        let repr = formatter().time(time);
         Self {
             time,
             repr,
@@ -30,13 +30,8 @@ impl TimeEdit {
         }
     }
 
-    pub fn get_value(&self) -> NaiveTime {
-        self.time
-    }
-
     pub fn validate(&self) -> Result<NaiveTime, ApplicationError> {
-        let time = NaiveTime::parse_from_str(&self.repr, "%R")?;
-        Ok(time)
+        formatter().parse_time(&self.repr)
     }
 
 
@@ -47,8 +42,8 @@ impl StateView<NaiveTime, ApplicationError> for TimeEdit {
         &mut self,
         ui: &mut egui::Ui,
     ) -> TitraResult<NaiveTime, ApplicationError> {
-            if let Some(l) = &self.label {
-                ui.label(l);
+            if let Some(l) = self.label {
+                ui.label(t(l));
             }
            
             let response = ui.add(
@@ -63,6 +58,7 @@ impl StateView<NaiveTime, ApplicationError> for TimeEdit {
                 match self.validate() {
                     Ok(v) => {
                         self.time = v;
+                        self.repr = formatter().time(v);
                         info!("done");
                         TitraResult::Done(v)
                     },
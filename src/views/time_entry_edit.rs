@@ -1,48 +1,63 @@
 use chrono::NaiveDate;
 use egui::TextEdit;
-use egui_extras::TableRow;
-use log::info;
 
 use crate::{
-    model::{error::ApplicationError, time_entry::TimeEntryData},
-    Services, StateView, TitraResult, TitraView,
+    i18n::{t, Texts},
+    model::{
+        error::ApplicationError,
+        project::Project,
+        time_entry::{TimeEntry, TimeEntryData, TimeEntryId},
+    },
+    overlap::{self, OverlapSettings},
+    StateView, TitraResult,
 };
 
-use super::time_edit::TimeEdit;
+use super::{project_select::ProjectSelect, time_edit::TimeEdit};
 
 #[derive(Clone, Debug)]
 pub struct TimeEntryEdit {
+    id: TimeEntryId,
     start: TimeEdit,
     end: TimeEdit,
     date: NaiveDate,
     remark: String,
+    project: ProjectSelect,
+    /// Entries around the day, checked for overlaps.
+    others: Vec<TimeEntry>,
+    overlaps: OverlapSettings,
 }
 
 impl TimeEntryEdit {
-    pub fn validate(&mut self) -> Result<TimeEntryData, ApplicationError> {
-        let start = self.start.validate()?;
-        let end = self.end.validate()?;
-        if start >= end {
-            return Err(ApplicationError::InvalidRange);
-        }
-        Ok(TimeEntryData {
-            date: self.date.clone(),
-            end,
-            start,
-            remark: self.remark.clone(),
-        })
-    }
-}
-
-impl From<TimeEntryData> for TimeEntryEdit {
-    fn from(value: TimeEntryData) -> Self {
+    pub fn new(
+        id: TimeEntryId,
+        value: TimeEntryData,
+        projects: Vec<Project>,
+        others: Vec<TimeEntry>,
+        overlaps: OverlapSettings,
+    ) -> Self {
         Self {
+            id,
             date: value.date,
             end: TimeEdit::new_with_value(value.end, None),
             remark: value.remark,
             start: TimeEdit::new_with_value(value.start, None),
+            project: ProjectSelect::new(format!("project_{id}"), value.project_id, projects),
+            others,
+            overlaps,
         }
     }
+
+    pub fn validate(&mut self) -> Result<TimeEntryData, ApplicationError> {
+        let start = self.start.validate()?;
+        let end = self.end.validate()?;
+        let entry = TimeEntryData::new(self.date, start, end, self.remark.clone(), self.project.selected())?;
+        self.overlaps.check(&overlap::find(&entry, &self.others, Some(self.id)))?;
+        Ok(entry)
+    }
+
+    fn crosses_midnight(&self) -> bool {
+        matches!((self.start.validate(), self.end.validate()), (Ok(start), Ok(end)) if end < start)
+    }
 }
 
 impl StateView<TimeEntryData, ApplicationError> for TimeEntryEdit {
@@ -57,7 +72,14 @@ impl StateView<TimeEntryData, ApplicationError> for TimeEntryEdit {
             TitraResult::NoChange => TitraResult::NoChange,
         };
 
-        let change2 = match StateView::show(&mut self.end, ui) {
+        let end = ui.horizontal(|ui| {
+            let res = StateView::show(&mut self.end, ui);
+            if self.crosses_midnight() {
+                ui.label("+1").on_hover_text(t(Texts::NextDay));
+            }
+            res
+        });
+        let change2 = match end.inner {
             TitraResult::InEdit => TitraResult::InEdit,
             TitraResult::Done(_) => match self.validate() {
                 Ok(d) => TitraResult::Done(d),
@@ -78,8 +100,13 @@ impl StateView<TimeEntryData, ApplicationError> for TimeEntryEdit {
         } else {
             TitraResult::NoChange
         };
-        let res = change1.combine_with(change2).combine_with(change3);
-
-        res
+        let change4 = match StateView::show(&mut self.project, ui) {
+            TitraResult::Done(_) => match self.validate() {
+                Ok(d) => TitraResult::Done(d),
+                Err(e) => TitraResult::Error(e),
+            },
+            _ => TitraResult::NoChange,
+        };
+        change1.combine_with(change2).combine_with(change3).combine_with(change4)
     }
 }
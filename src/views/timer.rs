@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use chrono::Local;
+use egui::{Button, Color32, Grid, RichText, TextEdit};
+use log::warn;
+
+use crate::{
+    format::formatter,
+    i18n::{t, Texts},
+    model::{error::ApplicationError, time_entry::OpenTimeEntry},
+    Services, StateView, TitraResult, TitraView,
+};
+
+use super::project_select::ProjectSelect;
+
+/// Start/stop stopwatch that records an open entry in storage while running.
+pub struct Timer {
+    running: Option<OpenTimeEntry>,
+    remark: String,
+    project: ProjectSelect,
+    /// Why the last start or stop failed, e.g. an overlap.
+    error: Option<String>,
+    requires_refresh: bool,
+}
+
+fn format_elapsed(seconds: i64) -> String {
+    format!(
+        "{:0>2}:{:0>2}:{:0>2}",
+        seconds / 3600,
+        (seconds / 60) % 60,
+        seconds % 60
+    )
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        Self {
+            running: None,
+            remark: "".to_owned(),
+            project: ProjectSelect::new("timer_project".to_owned(), None, vec![]),
+            error: None,
+            requires_refresh: true,
+        }
+    }
+
+    pub fn refresh_projects(&mut self) {
+        self.requires_refresh = true;
+    }
+
+    fn refresh(&mut self, services: &Services) -> Result<(), ApplicationError> {
+        self.project.set_projects(services.time_service.get_projects()?);
+        self.running = services.time_service.get_running_timer()?;
+        Ok(())
+    }
+
+    fn start(&mut self, services: &mut Services) -> Result<(), ApplicationError> {
+        let entry = OpenTimeEntry::new(
+            Local::now().naive_local(),
+            self.remark.clone(),
+            self.project.selected(),
+        );
+        services.time_service.start_timer(entry.clone())?;
+        self.running = Some(entry);
+        Ok(())
+    }
+
+    fn discard(&mut self, services: &mut Services) -> Result<(), ApplicationError> {
+        services.time_service.discard_timer()?;
+        self.running = None;
+        Ok(())
+    }
+
+    fn stop(&mut self, services: &mut Services) -> Result<(), ApplicationError> {
+        services.stop_timer(Local::now().naive_local())?;
+        self.running = None;
+        self.remark.clear();
+        Ok(())
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for Timer {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh {
+            if let Err(err) = self.refresh(services) {
+                warn!("Failed to load timer: {err}");
+            }
+            self.requires_refresh = false;
+        }
+
+        let mut final_res = TitraResult::NoChange;
+        ui.group(|ui| {
+            Grid::new("timer_grid").spacing((30.0, 2.0)).show(ui, |ui| {
+                match &self.running {
+                    Some(running) => {
+                        let elapsed = running.elapsed(Local::now().naive_local());
+                        ui.label(t(Texts::RunningSince));
+                        ui.label(formatter().time(running.start.time()));
+                        ui.end_row();
+                        ui.label(t(Texts::Duration));
+                        ui.label(RichText::new(format_elapsed(elapsed.num_seconds())).heading());
+                        ui.end_row();
+                        ui.label(t(Texts::Remark));
+                        ui.label(&running.remark);
+                        ui.end_row();
+                        if ui.add(Button::new(t(Texts::StopTimer))).clicked() {
+                            final_res = match self.stop(services) {
+                                Ok(_) => {
+                                    self.error = None;
+                                    TitraResult::Done(())
+                                }
+                                Err(err) => {
+                                    warn!("Failed to stop timer: {err}");
+                                    self.error = Some(err.to_string());
+                                    TitraResult::Error(err)
+                                }
+                            };
+                        }
+                        if ui.add(Button::new(t(Texts::DiscardTimer))).clicked() {
+                            match self.discard(services) {
+                                Ok(_) => self.error = None,
+                                Err(err) => {
+                                    warn!("Failed to discard timer: {err}");
+                                    self.error = Some(err.to_string());
+                                    final_res = TitraResult::Error(err);
+                                }
+                            }
+                        }
+                        ui.ctx().request_repaint_after(Duration::from_secs(1));
+                    }
+                    None => {
+                        ui.label(t(Texts::Remark));
+                        ui.add(TextEdit::singleline(&mut self.remark).desired_width(240.0));
+                        ui.end_row();
+                        ui.label(t(Texts::Project));
+                        StateView::show(&mut self.project, ui);
+                        ui.end_row();
+                        if ui.add(Button::new(t(Texts::StartTimer))).clicked() {
+                            match self.start(services) {
+                                Ok(_) => self.error = None,
+                                Err(err) => {
+                                    warn!("Failed to start timer: {err}");
+                                    self.error = Some(err.to_string());
+                                    final_res = TitraResult::Error(err);
+                                }
+                            }
+                        }
+                    }
+                }
+                ui.end_row();
+            });
+            if let Some(error) = &self.error {
+                ui.colored_label(Color32::RED, error);
+            }
+        });
+        final_res
+    }
+}
@@ -0,0 +1,124 @@
+use chrono::{Local, NaiveDateTime};
+use egui::{Button, Color32, DragValue, Grid, ScrollArea};
+use log::warn;
+
+use crate::{
+    config,
+    format::formatter,
+    history::Command,
+    i18n::{t, Texts},
+    model::{error::ApplicationError, time_entry::TimeEntry},
+    Services, TitraResult, TitraView,
+};
+
+/// Removed time entries, which can be restored or deleted permanently.
+pub struct Trash {
+    entries: Vec<(TimeEntry, NaiveDateTime)>,
+    error: Option<String>,
+    requires_refresh: bool,
+}
+
+impl Trash {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![],
+            error: None,
+            requires_refresh: true,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        self.requires_refresh = true;
+    }
+
+    fn show_settings(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<(), ApplicationError> {
+        ui.horizontal(|ui| {
+            ui.label(t(Texts::RetentionDays));
+            let mut days = services.config.trash.retention_days;
+            if ui.add(DragValue::new(&mut days).range(0..=3650)).changed() {
+                services.config.trash.retention_days = days;
+                config::save(&services.config)?;
+            }
+            Ok(())
+        })
+        .inner
+    }
+
+    /// Returns true if an entry was restored, purged entries don't change any other view.
+    fn show_entries(&mut self, ui: &mut egui::Ui, services: &mut Services) -> Result<bool, ApplicationError> {
+        let formatter = formatter();
+        let mut restored = false;
+        ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            Grid::new("trash_grid").striped(true).num_columns(7).show(ui, |ui| {
+                ui.label(t(Texts::Date));
+                ui.label(t(Texts::Start));
+                ui.label(t(Texts::End));
+                ui.label(t(Texts::Remark));
+                ui.label(t(Texts::DeletedAt));
+                ui.end_row();
+                for ((id, data), deleted_at) in &self.entries {
+                    ui.label(formatter.date_with_weekday(data.date));
+                    ui.label(formatter.time(data.start));
+                    ui.label(formatter.time(data.end));
+                    ui.label(&data.remark);
+                    ui.label(format!("{} {}", formatter.date(deleted_at.date()), formatter.time(deleted_at.time())));
+                    if ui.button(t(Texts::Restore)).clicked() {
+                        services.execute(Command::RestoreEntry((*id, data.clone())))?;
+                        restored = true;
+                        self.requires_refresh = true;
+                    }
+                    if ui.button("x").on_hover_text(t(Texts::DeletePermanently)).clicked() {
+                        services.purge_entry(*id)?;
+                        self.requires_refresh = true;
+                    }
+                    ui.end_row();
+                }
+                Ok(restored)
+            })
+            .inner
+        })
+        .inner
+    }
+}
+
+impl TitraView<(), ApplicationError, Services> for Trash {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<(), ApplicationError> {
+        if self.requires_refresh {
+            match services.time_service.get_deleted() {
+                Ok(entries) => self.entries = entries,
+                Err(err) => {
+                    warn!("Failed to load trash: {err}");
+                    return TitraResult::Error(err.into());
+                }
+            }
+            self.requires_refresh = false;
+        }
+
+        let settings = self.show_settings(ui, services);
+        ui.separator();
+        let entries = self.show_entries(ui, services);
+        if ui.add_enabled(!self.entries.is_empty(), Button::new(t(Texts::EmptyTrash))).clicked() {
+            if let Err(err) = services.purge_deleted_before(Local::now().naive_local()) {
+                warn!("Failed to empty trash: {err}");
+                self.error = Some(err.to_string());
+            }
+            self.requires_refresh = true;
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+
+        match (settings, entries) {
+            (Err(e), _) | (_, Err(e)) => {
+                warn!("Failed to change trash: {e}");
+                self.error = Some(e.to_string());
+                TitraResult::Error(e)
+            }
+            (Ok(_), Ok(true)) => {
+                self.error = None;
+                TitraResult::Done(())
+            }
+            _ => TitraResult::NoChange,
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    collections::BTreeMap,
+    thread::{spawn, JoinHandle},
+};
+
+use chrono::{Datelike, Local, NaiveDate, TimeDelta, Weekday};
+use egui::{pos2, vec2, Align2, Color32, FontId, Grid, Rect, RichText, Sense};
+use log::warn;
+
+use crate::{
+    balance::{self, Balance, Sources},
+    format::formatter,
+    i18n::{month_name, t, weekday_name, Texts},
+    model::error::ApplicationError,
+    Services, TitraResult, TitraView,
+};
+
+const CELL_SIZE: f32 = 12.0;
+const CELL_GAP: f32 = 2.0;
+const HEATMAP_COLOR: Color32 = Color32::from_rgb(40, 160, 70);
+
+/// Everything shown for a year, loaded off the UI thread.
+struct YearData {
+    months: Vec<Balance>,
+    /// Raw worked time per day.
+    days: BTreeMap<NaiveDate, TimeDelta>,
+}
+
+/// Monthly totals and a calendar heatmap of a year, a click on a month or day selects it.
+pub struct YearOverview {
+    year: i32,
+    data: Option<YearData>,
+    loading: Option<JoinHandle<Result<YearData, ApplicationError>>>,
+    error: Option<String>,
+    requires_refresh: bool,
+}
+
+impl YearOverview {
+    pub fn new() -> Self {
+        Self {
+            year: Local::now().year(),
+            data: None,
+            loading: None,
+            error: None,
+            requires_refresh: true,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        self.requires_refresh = true;
+    }
+
+    fn load(&mut self, services: &Services) {
+        let time = services.time_service.clone();
+        let hours = services.hour_service.clone();
+        let absences = services.absence_service.clone();
+        let balance = services.balance_service.clone();
+        let config = services.config.clone();
+        let year = self.year;
+        self.loading = Some(spawn(move || {
+            let sources = Sources {
+                time: time.as_ref(),
+                hours: hours.as_ref(),
+                absences: absences.as_ref(),
+                balance: balance.as_ref(),
+            };
+            let range = (
+                NaiveDate::from_ymd_opt(year, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(year, 12, 31).unwrap(),
+            );
+            Ok(YearData {
+                months: balance::months(&sources, &config, year)?,
+                days: time.get_daily_totals(range)?,
+            })
+        }));
+    }
+
+    fn check_finished(&mut self) {
+        if self.loading.as_ref().is_some_and(|h| h.is_finished()) {
+            match self.loading.take().unwrap().join() {
+                Ok(Ok(data)) => {
+                    self.data = Some(data);
+                    self.error = None;
+                }
+                Ok(Err(err)) => {
+                    warn!("Failed to load year overview: {err}");
+                    self.error = Some(err.to_string());
+                }
+                Err(_) => self.error = Some(t(Texts::LoadingAborted).to_owned()),
+            }
+        }
+    }
+
+    fn show_months(ui: &mut egui::Ui, data: &YearData, year: i32) -> Option<NaiveDate> {
+        let formatter = formatter();
+        let mut selected = None;
+        Grid::new("year_months_grid").striped(true).num_columns(5).show(ui, |ui| {
+            ui.label("");
+            ui.label(t(Texts::Net));
+            ui.label(t(Texts::Planned));
+            ui.label(t(Texts::Overtime));
+            ui.label(t(Texts::FlextimeBalance));
+            ui.end_row();
+            for (month, balance) in (1..=12).zip(&data.months) {
+                if ui.link(month_name(month).unwrap()).clicked() {
+                    selected = NaiveDate::from_ymd_opt(year, month, 1);
+                }
+                ui.label(formatter.duration(balance.actual));
+                ui.label(formatter.duration(balance.planned));
+                ui.label(formatter.duration(balance.difference()));
+                ui.label(balance.closing.map(|c| formatter.duration(c)).unwrap_or_default());
+                ui.end_row();
+            }
+            let actual: TimeDelta = data.months.iter().map(|b| b.actual).sum();
+            let planned: TimeDelta = data.months.iter().map(|b| b.planned).sum();
+            ui.label(RichText::new(t(Texts::Total)).strong());
+            ui.label(RichText::new(formatter.duration(actual)).strong());
+            ui.label(RichText::new(formatter.duration(planned)).strong());
+            ui.label(RichText::new(formatter.duration(actual - planned)).strong());
+            let closing = data.months.last().and_then(|b| b.closing);
+            ui.label(RichText::new(closing.map(|c| formatter.duration(c)).unwrap_or_default()).strong());
+            ui.end_row();
+        });
+        selected
+    }
+
+    /// One column per week and one row per weekday, darker cells mean more hours.
+    fn show_heatmap(ui: &mut egui::Ui, data: &YearData, year: i32) -> Option<NaiveDate> {
+        let first = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+        let last = NaiveDate::from_ymd_opt(year, 12, 31).unwrap();
+        let offset = first.weekday().num_days_from_monday() as usize;
+        let weeks = (offset + last.ordinal() as usize).div_ceil(7);
+        let label_width = 30.0;
+        let step = CELL_SIZE + CELL_GAP;
+        let size = vec2(label_width + weeks as f32 * step, 7.0 * step);
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+        let painter = ui.painter_at(rect);
+
+        let text_color = ui.visuals().text_color();
+        for weekday in [Weekday::Mon, Weekday::Wed, Weekday::Fri] {
+            let row = weekday.num_days_from_monday();
+            painter.text(
+                pos2(rect.left(), rect.top() + row as f32 * step + CELL_SIZE / 2.0),
+                Align2::LEFT_CENTER,
+                weekday_name(weekday).chars().take(2).collect::<String>(),
+                FontId::proportional(10.0),
+                text_color,
+            );
+        }
+
+        let max = data.days.values().max().copied().unwrap_or(TimeDelta::zero());
+        let cell = |date: NaiveDate| {
+            let index = offset + date.ordinal0() as usize;
+            let min = pos2(
+                rect.left() + label_width + (index / 7) as f32 * step,
+                rect.top() + (index % 7) as f32 * step,
+            );
+            Rect::from_min_size(min, vec2(CELL_SIZE, CELL_SIZE))
+        };
+        let hovered = response.hover_pos();
+        let mut hovered_day = None;
+        for date in first.iter_days().take_while(|d| *d <= last) {
+            let worked = data.days.get(&date).copied().unwrap_or(TimeDelta::zero());
+            let color = if worked > TimeDelta::zero() && max > TimeDelta::zero() {
+                let share = worked.num_seconds() as f32 / max.num_seconds() as f32;
+                HEATMAP_COLOR.gamma_multiply(0.25 + 0.75 * share)
+            } else {
+                ui.visuals().faint_bg_color
+            };
+            let rect = cell(date);
+            painter.rect_filled(rect, 2.0, color);
+            if hovered.is_some_and(|p| rect.contains(p)) {
+                hovered_day = Some((date, worked));
+            }
+        }
+
+        let (date, worked) = hovered_day?;
+        let formatter = formatter();
+        let clicked = response.clicked();
+        response.on_hover_text(format!("{}: {}", formatter.date_with_weekday(date), formatter.duration(worked)));
+        clicked.then_some(date)
+    }
+}
+
+impl TitraView<NaiveDate, ApplicationError, Services> for YearOverview {
+    fn show(&mut self, ui: &mut egui::Ui, services: &mut Services) -> TitraResult<NaiveDate, ApplicationError> {
+        if self.requires_refresh && self.loading.is_none() {
+            self.load(services);
+            self.requires_refresh = false;
+        }
+        if self.loading.is_some() {
+            self.check_finished();
+            ui.ctx().request_repaint();
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("<").clicked() {
+                self.year -= 1;
+                self.data = None;
+                self.requires_refresh = true;
+            }
+            ui.label(self.year.to_string());
+            if ui.button(">").clicked() {
+                self.year += 1;
+                self.data = None;
+                self.requires_refresh = true;
+            }
+            if self.loading.is_some() {
+                ui.spinner();
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(Color32::RED, error);
+        }
+        let Some(data) = &self.data else {
+            return TitraResult::NoChange;
+        };
+
+        let month = Self::show_months(ui, data, self.year);
+        ui.separator();
+        let day = Self::show_heatmap(ui, data, self.year);
+        match month.or(day) {
+            Some(date) => TitraResult::Done(date),
+            None => TitraResult::NoChange,
+        }
+    }
+}